@@ -0,0 +1,100 @@
+//! Hot-path benchmarks: `cargo bench`, or `cargo bench -- rsi` for a subset
+//!
+//! Prices and trades come from the synthetic generator with a fixed seed, so
+//! runs before and after a change see the same input.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use rsi_calculator::config::{Arithmetic, Config, GeneratorConfig, RsiWeighting};
+use rsi_calculator::generator::TradeGenerator;
+use rsi_calculator::{PriceHistory, RsiCalculator, Smoothing, TradeMessage};
+
+/// Prices kept per history, as with the default config
+const HISTORY: usize = 100;
+
+const PERIOD: usize = 14;
+
+/// Trades generated up front for each end-to-end run
+const TRADES: usize = 10_000;
+
+fn trades(count: usize) -> Vec<TradeMessage> {
+    let config = GeneratorConfig {
+        seed: Some(42),
+        rate_per_sec: 1000.0,
+        ..GeneratorConfig::default()
+    };
+    let mut generator = TradeGenerator::new(&config);
+    let mut trades = Vec::with_capacity(count);
+    let mut now = 1_700_000_000.0;
+    while trades.len() < count {
+        trades.extend(generator.tick(now, 1.0));
+        now += 1.0;
+    }
+    trades.truncate(count);
+    trades
+}
+
+/// A full history with Wilder state already seeded
+fn history(arithmetic: Arithmetic, prices: &[TradeMessage]) -> PriceHistory {
+    let mut history = PriceHistory::new(HISTORY, &[PERIOD], RsiWeighting::Equal, arithmetic);
+    for trade in prices {
+        history.add_price(trade.price_in_sol, trade.amount_in_sol);
+    }
+    history
+}
+
+fn price_history(c: &mut Criterion) {
+    let prices = trades(HISTORY * 2);
+    let mut group = c.benchmark_group("price_history");
+
+    for arithmetic in [Arithmetic::Float, Arithmetic::Decimal] {
+        let name = format!("{:?}", arithmetic).to_lowercase();
+        group.bench_function(BenchmarkId::new("add_price", &name), |b| {
+            b.iter_batched_ref(
+                || history(arithmetic, &prices),
+                |history| history.add_price(black_box(0.00003), black_box(1.0)),
+                BatchSize::SmallInput,
+            )
+        });
+
+        let history = history(arithmetic, &prices);
+        for smoothing in [Smoothing::Simple, Smoothing::Wilder] {
+            let id = format!("{}/{:?}", name, smoothing).to_lowercase();
+            group.bench_function(BenchmarkId::new("rsi", id), |b| {
+                b.iter(|| history.rsi(black_box(PERIOD), smoothing))
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Parse a JSON trade, run every configured indicator and serialize the
+/// outputs, as the consumer loop does minus Kafka
+fn process_message(c: &mut Criterion) {
+    let payloads: Vec<String> = trades(TRADES)
+        .iter()
+        .map(|trade| serde_json::to_string(trade).expect("trades serialize"))
+        .collect();
+    let config = Config::default();
+
+    let mut group = c.benchmark_group("process_message");
+    group.throughput(Throughput::Elements(TRADES as u64));
+    group.bench_function("parse_compute_serialize", |b| {
+        b.iter_batched_ref(
+            || RsiCalculator::new(&config),
+            |calculator| {
+                for payload in &payloads {
+                    let trade: TradeMessage = serde_json::from_str(payload).expect("payload parses");
+                    for output in calculator.process_trade(trade) {
+                        black_box(output.to_json().expect("output serializes"));
+                    }
+                }
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, price_history, process_message);
+criterion_main!(benches);
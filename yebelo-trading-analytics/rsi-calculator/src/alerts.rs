@@ -0,0 +1,190 @@
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::config::{AlertRoute, AlertsConfig};
+use crate::indicators::IndicatorOutput;
+use crate::RsiMessage;
+
+/// Alerts waiting to be sent before new ones are dropped
+const QUEUE_SIZE: usize = 256;
+
+/// A chat channel alerts can be delivered to
+#[derive(Debug)]
+enum Channel {
+    Telegram { bot_token: String, chat_id: String },
+    Slack { webhook_url: String },
+}
+
+impl Channel {
+    fn name(&self) -> &'static str {
+        match self {
+            Channel::Telegram { .. } => "Telegram",
+            Channel::Slack { .. } => "Slack",
+        }
+    }
+
+    async fn send(&self, client: &reqwest::Client, text: &str) -> Result<()> {
+        let request = match self {
+            Channel::Telegram { bot_token, chat_id } => client
+                .post(format!("https://api.telegram.org/bot{}/sendMessage", bot_token))
+                .json(&json!({ "chat_id": chat_id, "text": text })),
+            Channel::Slack { webhook_url } => client.post(webhook_url).json(&json!({ "text": text })),
+        };
+
+        request
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .with_context(|| format!("Failed to send {} alert", self.name()))?;
+        Ok(())
+    }
+}
+
+/// A channel together with which signal changes it should hear about
+#[derive(Debug)]
+struct Route {
+    channel: Arc<Channel>,
+    route: AlertRoute,
+    template: String,
+}
+
+/// Sends chat notifications when a token's RSI signal changes.
+///
+/// Only transitions alert (e.g. neutral → oversold), never repeats of the
+/// same signal. Delivery happens on a background task so a slow chat API
+/// never holds up the consumer loop; alerts are best-effort and dropped
+/// if the queue backs up.
+pub struct Alerts {
+    routes: Arc<Vec<Route>>,
+    // Last signal per (token, period, timeframe)
+    last_signal: HashMap<(String, usize, String), String>,
+    queue: mpsc::Sender<(Arc<Channel>, String)>,
+}
+
+impl Alerts {
+    /// Start the delivery task; `None` if alerting is disabled or no channel is
+    pub fn start(config: &AlertsConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let routes = routes(config);
+        if routes.is_empty() {
+            return Ok(None);
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .context("Failed to create alert HTTP client")?;
+        let (queue, pending) = mpsc::channel(QUEUE_SIZE);
+        tokio::spawn(deliver(client, pending));
+
+        Ok(Some(Self {
+            routes: Arc::new(routes),
+            last_signal: HashMap::new(),
+            queue,
+        }))
+    }
+
+    /// A handle sharing the same channels and delivery task, with its own
+    /// signal tracking (for a worker that owns a different set of tokens)
+    pub fn fork(&self) -> Self {
+        Self {
+            routes: Arc::clone(&self.routes),
+            last_signal: HashMap::new(),
+            queue: self.queue.clone(),
+        }
+    }
+
+    /// Route alerts by new rules and templates from now on, keeping the
+    /// signals seen so far; disabling alerts here silences them
+    pub fn reconfigure(&mut self, config: &AlertsConfig) {
+        self.routes = Arc::new(if config.enabled { routes(config) } else { Vec::new() });
+    }
+
+    /// Queue alerts for an RSI output whose signal changed
+    pub fn observe(&mut self, output: &IndicatorOutput) {
+        let IndicatorOutput::Rsi(msg) = output else {
+            return;
+        };
+
+        let key = (output.series_key().into_owned(), msg.period, msg.timeframe.clone());
+        let previous = self.last_signal.insert(key, msg.signal.clone());
+        // The first value seen for a series is not a change
+        let Some(previous) = previous else {
+            return;
+        };
+        if previous == msg.signal {
+            return;
+        }
+
+        for route in self.routes.iter() {
+            if !route.route.accepts(&msg.token_address, &msg.signal) {
+                continue;
+            }
+            let text = render(&route.template, msg, &previous);
+            if self.queue.try_send((Arc::clone(&route.channel), text)).is_err() {
+                warn!("⚠️  {} alert queue full, dropping alert", route.channel.name());
+            }
+        }
+    }
+}
+
+/// A route for every enabled channel
+fn routes(config: &AlertsConfig) -> Vec<Route> {
+    let mut routes = Vec::new();
+    if config.telegram.enabled {
+        let channel = Channel::Telegram {
+            bot_token: config.telegram.bot_token.clone(),
+            chat_id: config.telegram.chat_id.clone(),
+        };
+        routes.push(Route {
+            channel: Arc::new(channel),
+            template: config.telegram.route.template.clone().unwrap_or_else(|| config.template.clone()),
+            route: config.telegram.route.clone(),
+        });
+    }
+    if config.slack.enabled {
+        let channel = Channel::Slack {
+            webhook_url: config.slack.webhook_url.clone(),
+        };
+        routes.push(Route {
+            channel: Arc::new(channel),
+            template: config.slack.route.template.clone().unwrap_or_else(|| config.template.clone()),
+            route: config.slack.route.clone(),
+        });
+    }
+    routes
+}
+
+/// Fill `{token}`, `{token_short}`, `{period}`, `{timeframe}`, `{rsi}`,
+/// `{price}`, `{quote}`, `{signal}`, `{previous}` and `{timestamp}` in a template
+fn render(template: &str, msg: &RsiMessage, previous: &str) -> String {
+    template
+        .replace("{token}", &msg.token_address)
+        .replace("{token_short}", &msg.token_address[..msg.token_address.len().min(8)])
+        .replace("{period}", &msg.period.to_string())
+        .replace("{timeframe}", &msg.timeframe)
+        .replace("{rsi}", &format!("{:.2}", msg.rsi_value))
+        .replace("{price}", &format!("{:.8}", msg.current_price))
+        .replace("{quote}", &msg.quote_currency)
+        .replace("{signal}", &msg.signal)
+        .replace("{previous}", previous)
+        .replace("{timestamp}", &msg.timestamp)
+}
+
+/// Send queued alerts one at a time until every handle is dropped
+async fn deliver(client: reqwest::Client, mut pending: mpsc::Receiver<(Arc<Channel>, String)>) {
+    while let Some((channel, text)) = pending.recv().await {
+        match channel.send(&client, &text).await {
+            Ok(()) => debug!("🔔 Sent {} alert: {}", channel.name(), text),
+            Err(e) => warn!("⚠️  {:#}", e),
+        }
+    }
+}
@@ -0,0 +1,425 @@
+use anyhow::{Context, Result};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::Response;
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+use tokio::sync::broadcast;
+
+use crate::config::ApiConfig;
+use crate::indicators::IndicatorOutput;
+use crate::reload::{Edit, ReloadHandle, Thresholds};
+use crate::{RsiCalculator, TradeMessage};
+use crate::split_series_key;
+
+/// RSI messages buffered per WebSocket client before it starts missing some
+const UPDATE_BUFFER: usize = 1024;
+
+/// Token stats kept before those of untracked tokens (evicted or filtered
+/// out) are dropped, least recently updated first
+const MAX_STATS: usize = 10_000;
+
+/// Latest published values, shared between the processing loop and the HTTP API
+pub struct ApiState {
+    tokens: RwLock<HashMap<String, TokenSnapshot>>,
+    stats: RwLock<HashMap<String, TokenStats>>,
+    // Every published RSI message, for WebSocket clients
+    updates: broadcast::Sender<Arc<RsiUpdate>>,
+    reload: ReloadHandle,
+    admin_token: Option<String>,
+}
+
+/// A published RSI message, serialized once for all WebSocket clients
+struct RsiUpdate {
+    token_address: String,
+    json: String,
+}
+
+/// Sent by WebSocket clients to choose which tokens they receive; a client
+/// with no subscriptions receives every token
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+enum Subscription {
+    Subscribe { tokens: Vec<String> },
+    Unsubscribe { tokens: Vec<String> },
+}
+
+
+/// Latest RSI values for one token in one quote currency
+#[derive(Debug, Clone, Serialize)]
+struct TokenSnapshot {
+    token_address: String,
+    quote_currency: String,
+    current_price: f64,
+    /// Prices fed into the token's tick RSI so far
+    samples: u64,
+    updated_at: String,
+    rsi: Vec<RsiSnapshot>,
+}
+
+/// Processing counters for one token, to explain a missing or stale RSI
+#[derive(Debug, Clone, Default, Serialize)]
+struct TokenStats {
+    token_address: String,
+    quote_currency: String,
+    /// Trades that passed validation, including filtered and duplicate ones
+    trades: u64,
+    last_trade_time: Option<String>,
+    last_price: Option<f64>,
+    /// Whether the calculator holds state for the token; false once it is
+    /// filtered out, evicted or reset
+    tracked: bool,
+    /// Prices fed into the token's tick RSI since it was last tracked afresh
+    rsi_samples: u64,
+    /// RSI periods that have no value yet
+    warming_up: Vec<usize>,
+    evictions: u64,
+    /// Outputs that never reached their topic
+    produce_failures: u64,
+    #[serde(skip)]
+    updated: Option<Instant>,
+}
+
+/// One RSI series of a token, keyed by period and timeframe
+#[derive(Debug, Clone, Serialize)]
+struct RsiSnapshot {
+    period: usize,
+    timeframe: String,
+    rsi_value: f64,
+    signal: String,
+    /// Prices fed into this series so far
+    samples: u64,
+    timestamp: String,
+}
+
+impl ApiState {
+    pub fn new(config: &ApiConfig, reload: ReloadHandle) -> Self {
+        Self {
+            tokens: RwLock::default(),
+            stats: RwLock::default(),
+            updates: broadcast::channel(UPDATE_BUFFER).0,
+            reload,
+            admin_token: config.admin_token.clone(),
+        }
+    }
+
+    /// Count a trade that passed validation
+    pub fn record_trade(&self, trade: &TradeMessage) {
+        self.with_stats(&trade.series_key(), |stats| {
+            stats.trades += 1;
+            stats.last_trade_time = Some(trade.block_time.clone());
+            stats.last_price = Some(trade.price_in_sol);
+        });
+    }
+
+    /// Record where a token's RSI stands after one of its trades
+    pub fn record_state(&self, token_address: &str, calculator: &RsiCalculator) {
+        let warming_up = calculator.warming_up(token_address);
+        self.with_stats(token_address, |stats| {
+            stats.tracked = warming_up.is_some();
+            stats.rsi_samples = calculator.samples(token_address, "tick");
+            stats.warming_up = warming_up.unwrap_or_default();
+        });
+    }
+
+    /// Count tokens the calculator forgot to save memory
+    pub fn record_evictions(&self, token_addresses: &[String]) {
+        for token_address in token_addresses {
+            self.with_stats(token_address, |stats| {
+                stats.evictions += 1;
+                stats.tracked = false;
+                stats.rsi_samples = 0;
+                stats.warming_up.clear();
+            });
+        }
+    }
+
+    /// Count outputs that never reached their topic, by token
+    pub fn record_produce_failures(&self, token_addresses: &[String]) {
+        for token_address in token_addresses {
+            self.with_stats(token_address, |stats| stats.produce_failures += 1);
+        }
+    }
+
+    /// Update the stats of the series `key`, see [`crate::series_key`]
+    fn with_stats(&self, key: &str, update: impl FnOnce(&mut TokenStats)) {
+        let mut stats = self.stats.write().unwrap_or_else(|e| e.into_inner());
+        if stats.len() >= MAX_STATS && !stats.contains_key(key) {
+            let stalest = stats
+                .iter()
+                .filter(|(_, stats)| !stats.tracked)
+                .min_by_key(|(_, stats)| stats.updated)
+                .map(|(key, _)| key.clone());
+            if let Some(stalest) = stalest {
+                stats.remove(&stalest);
+            }
+        }
+        let entry = stats.entry(key.to_string()).or_insert_with(|| {
+            let (token_address, quote_currency) = split_series_key(key);
+            TokenStats {
+                token_address: token_address.to_string(),
+                quote_currency: quote_currency.to_string(),
+                ..TokenStats::default()
+            }
+        });
+        entry.updated = Some(Instant::now());
+        update(entry);
+    }
+
+    /// Record a published RSI value and push it to WebSocket clients
+    pub fn observe(&self, output: &IndicatorOutput, calculator: &RsiCalculator) {
+        let IndicatorOutput::Rsi(msg) = output else {
+            return;
+        };
+
+        if self.updates.receiver_count() > 0 {
+            if let Ok(json) = serde_json::to_string(msg) {
+                let update = RsiUpdate {
+                    token_address: msg.token_address.clone(),
+                    json,
+                };
+                // Only fails when the last client just disconnected
+                let _ = self.updates.send(Arc::new(update));
+            }
+        }
+
+        let key = output.series_key();
+        let mut tokens = self.tokens.write().unwrap_or_else(|e| e.into_inner());
+        let token = tokens
+            .entry(key.to_string())
+            .or_insert_with(|| TokenSnapshot {
+                token_address: msg.token_address.clone(),
+                quote_currency: msg.quote_currency.clone(),
+                current_price: msg.current_price,
+                samples: 0,
+                updated_at: msg.timestamp.clone(),
+                rsi: Vec::new(),
+            });
+
+        token.current_price = msg.current_price;
+        token.samples = calculator.samples(&key, "tick");
+        token.updated_at = msg.timestamp.clone();
+
+        let snapshot = RsiSnapshot {
+            period: msg.period,
+            timeframe: msg.timeframe.clone(),
+            rsi_value: msg.rsi_value,
+            signal: msg.signal.clone(),
+            samples: calculator.samples(&key, &msg.timeframe),
+            timestamp: msg.timestamp.clone(),
+        };
+        match token
+            .rsi
+            .iter_mut()
+            .find(|rsi| rsi.period == msg.period && rsi.timeframe == msg.timeframe)
+        {
+            Some(existing) => *existing = snapshot,
+            None => token.rsi.push(snapshot),
+        }
+    }
+}
+
+/// Every token with at least one published RSI value, by address
+async fn list_tokens(State(state): State<Arc<ApiState>>) -> Json<Vec<TokenSnapshot>> {
+    let tokens = state.tokens.read().unwrap_or_else(|e| e.into_inner());
+    let mut snapshots: Vec<TokenSnapshot> = tokens.values().cloned().collect();
+    snapshots.sort_by(|a, b| (&a.token_address, &a.quote_currency).cmp(&(&b.token_address, &b.quote_currency)));
+    Json(snapshots)
+}
+
+/// Latest RSI values of one token, or of one of its series as
+/// "<address>:<QUOTE>" for quotes other than SOL
+async fn token_rsi(
+    State(state): State<Arc<ApiState>>,
+    Path(address): Path<String>,
+) -> Result<Json<TokenSnapshot>, StatusCode> {
+    let tokens = state.tokens.read().unwrap_or_else(|e| e.into_inner());
+    tokens.get(&address).cloned().map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Processing counters of one token, addressed like [`token_rsi`]
+async fn token_stats(
+    State(state): State<Arc<ApiState>>,
+    Path(address): Path<String>,
+) -> Result<Json<TokenStats>, StatusCode> {
+    let stats = state.stats.read().unwrap_or_else(|e| e.into_inner());
+    stats.get(&address).cloned().map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Live RSI messages over a WebSocket
+async fn stream(ws: WebSocketUpgrade, State(state): State<Arc<ApiState>>) -> Response {
+    let updates = state.updates.subscribe();
+    ws.on_upgrade(move |socket| stream_updates(socket, updates))
+}
+
+/// Forward RSI messages to one client until it disconnects, applying its
+/// subscription messages as they arrive
+async fn stream_updates(mut socket: WebSocket, mut updates: broadcast::Receiver<Arc<RsiUpdate>>) {
+    let mut tokens: HashSet<String> = HashSet::new();
+
+    loop {
+        tokio::select! {
+            update = updates.recv() => match update {
+                Ok(update) => {
+                    if !tokens.is_empty() && !tokens.contains(&update.token_address) {
+                        continue;
+                    }
+                    if socket.send(Message::Text(update.json.clone())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!("WebSocket client fell behind, skipped {} updates", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str::<Subscription>(&text) {
+                    Ok(Subscription::Subscribe { tokens: added }) => tokens.extend(added),
+                    Ok(Subscription::Unsubscribe { tokens: removed }) => {
+                        for token in &removed {
+                            tokens.remove(token);
+                        }
+                    }
+                    Err(e) => {
+                        let error = serde_json::json!({ "error": format!("Invalid subscription: {}", e) });
+                        if socket.send(Message::Text(error.to_string())).await.is_err() {
+                            break;
+                        }
+                    }
+                },
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+type AdminResponse = (StatusCode, Json<serde_json::Value>);
+
+/// Reload the config file, as on SIGHUP
+async fn reload_config(State(state): State<Arc<ApiState>>, headers: HeaderMap) -> AdminResponse {
+    if !state.is_admin(&headers) {
+        return unauthorized();
+    }
+    respond(state.reload.reload().await)
+}
+
+/// Start processing a token: add it to the allowlist
+async fn allow_token(
+    State(state): State<Arc<ApiState>>,
+    Path(address): Path<String>,
+    headers: HeaderMap,
+) -> AdminResponse {
+    if !state.is_admin(&headers) {
+        return unauthorized();
+    }
+    respond(state.reload.edit(Edit::Allow(address)).await)
+}
+
+/// Stop processing a token: drop it from the allowlist
+async fn remove_token(
+    State(state): State<Arc<ApiState>>,
+    Path(address): Path<String>,
+    headers: HeaderMap,
+) -> AdminResponse {
+    if !state.is_admin(&headers) {
+        return unauthorized();
+    }
+    let outcome = state.reload.edit(Edit::Remove(address.clone())).await;
+    if outcome.is_ok() {
+        state.forget(&address);
+    }
+    respond(outcome)
+}
+
+/// Throw away a token's indicator state and last published values
+async fn reset_token(
+    State(state): State<Arc<ApiState>>,
+    Path(address): Path<String>,
+    headers: HeaderMap,
+) -> AdminResponse {
+    if !state.is_admin(&headers) {
+        return unauthorized();
+    }
+    let outcome = state.reload.edit(Edit::Reset(address.clone())).await;
+    if outcome.is_ok() {
+        state.forget(&address);
+    }
+    respond(outcome)
+}
+
+/// Override a token's RSI thresholds, e.g. `{"oversold": 20, "overbought": 80}`
+async fn token_thresholds(
+    State(state): State<Arc<ApiState>>,
+    Path(address): Path<String>,
+    headers: HeaderMap,
+    Json(thresholds): Json<Thresholds>,
+) -> AdminResponse {
+    if !state.is_admin(&headers) {
+        return unauthorized();
+    }
+    respond(state.reload.edit(Edit::Thresholds(address, thresholds)).await)
+}
+
+fn unauthorized() -> AdminResponse {
+    (StatusCode::UNAUTHORIZED, Json(serde_json::json!({ "error": "Admin token required" })))
+}
+
+fn respond(outcome: Result<String, String>) -> AdminResponse {
+    match outcome {
+        Ok(status) => (StatusCode::OK, Json(serde_json::json!({ "status": status }))),
+        Err(e) => (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({ "error": e }))),
+    }
+}
+
+impl ApiState {
+    /// Drop a token's last published values, in every quote currency
+    fn forget(&self, token_address: &str) {
+        let mut tokens = self.tokens.write().unwrap_or_else(|e| e.into_inner());
+        tokens.retain(|key, _| split_series_key(key).0 != token_address);
+    }
+
+    /// Whether a request carries the admin bearer token
+    fn is_admin(&self, headers: &HeaderMap) -> bool {
+        let Some(token) = &self.admin_token else {
+            return false;
+        };
+        headers
+            .get(axum::http::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|given| given == token)
+    }
+}
+
+/// Serve `/tokens`, `/tokens/{address}/rsi`, `/tokens/{address}/stats` and
+/// the `/ws` stream on the configured address, plus `/config/reload` and the
+/// `/admin/tokens/{address}` endpoints when an admin token is set
+pub async fn serve(config: &ApiConfig, state: Arc<ApiState>) -> Result<()> {
+    let mut app = Router::new()
+        .route("/tokens", get(list_tokens))
+        .route("/tokens/:address/rsi", get(token_rsi))
+        .route("/tokens/:address/stats", get(token_stats))
+        .route("/ws", get(stream));
+    if config.admin_token.is_some() {
+        app = app
+            .route("/config/reload", post(reload_config))
+            .route("/admin/tokens/:address", put(allow_token).delete(remove_token))
+            .route("/admin/tokens/:address/reset", post(reset_token))
+            .route("/admin/tokens/:address/thresholds", put(token_thresholds));
+    }
+    let app = app.with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(&config.bind_addr)
+        .await
+        .with_context(|| format!("Failed to bind API server to {}", config.bind_addr))?;
+
+    axum::serve(listener, app).await.context("API server failed")
+}
@@ -0,0 +1,54 @@
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use std::ops::{Add, Div, Mul, Sub};
+
+/// Number type RSI gains and losses are accumulated in
+///
+/// `f64` is fast but its rounding error builds up in long-running Wilder
+/// averages; [`Decimal`] keeps 28 significant digits exactly, at a cost.
+pub trait Real:
+    Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self>
+{
+    fn zero() -> Self;
+    fn from_f64(value: f64) -> Self;
+    fn from_usize(value: usize) -> Self;
+    fn to_f64(self) -> f64;
+}
+
+impl Real for f64 {
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn from_usize(value: usize) -> Self {
+        value as f64
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+impl Real for Decimal {
+    fn zero() -> Self {
+        Decimal::ZERO
+    }
+
+    /// The shortest decimal that round-trips to `value`, so a price parsed
+    /// from "0.000001234" is exactly 0.000001234; zero for NaN/infinity
+    fn from_f64(value: f64) -> Self {
+        <Decimal as FromPrimitive>::from_f64(value).unwrap_or_default()
+    }
+
+    fn from_usize(value: usize) -> Self {
+        Decimal::from(value)
+    }
+
+    fn to_f64(self) -> f64 {
+        ToPrimitive::to_f64(&self).unwrap_or_default()
+    }
+}
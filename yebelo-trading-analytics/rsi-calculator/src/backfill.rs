@@ -0,0 +1,159 @@
+use anyhow::{Context, Result};
+use log::{info, warn};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::cli::BackfillArgs;
+use crate::codec::Codec;
+use crate::headers::{Carried, OutputHeaders};
+use crate::config::Config;
+use crate::sol_usd::{self, SolUsd};
+use crate::indicators::IndicatorOutput;
+use crate::service::create_producer;
+use crate::{RsiCalculator, TradeMessage};
+
+/// Row of the original CSV trade dump; other columns are ignored
+#[derive(Debug, Deserialize)]
+struct CsvTrade {
+    token_address: String,
+    price_in_sol: Option<f64>,
+    block_time: String,
+    transaction_signature: String,
+    is_buy: String,
+    amount_in_sol: Option<f64>,
+    #[serde(default)]
+    quote_currency: String,
+}
+
+impl From<CsvTrade> for TradeMessage {
+    fn from(row: CsvTrade) -> Self {
+        Self {
+            token_address: row.token_address,
+            price_in_sol: row.price_in_sol.unwrap_or(0.0),
+            block_time: row.block_time,
+            transaction_signature: row.transaction_signature,
+            is_buy: row.is_buy.eq_ignore_ascii_case("true"),
+            amount_in_sol: row.amount_in_sol.unwrap_or(0.0),
+            processed_timestamp: String::new(),
+            quote_currency: row.quote_currency,
+        }
+    }
+}
+
+/// Replay a CSV dump through a fresh calculator
+///
+/// Trades are sorted by `block_time` first so candles close in order. Results
+/// are printed to stdout as JSON lines, or published to their usual topics
+/// with the trade time as the Kafka timestamp when `--publish` is given.
+///
+/// With USD normalization on, SOL/USD prices since the first trade are read
+/// back from the reference topic so the USD series can be replayed too; the
+/// http source has no history, so no trade is converted with it.
+pub async fn run(config: Config, args: &BackfillArgs) -> Result<()> {
+    let mut trades = read_trades(&args.file)?;
+    trades.sort_by_key(|trade| trade.block_time_secs().unwrap_or(i64::MAX));
+    info!("📂 Replaying {} trades from {}", trades.len(), args.file.display());
+
+    let mut calculator = RsiCalculator::new(&config);
+    let sol_usd = match trades.first().and_then(TradeMessage::block_time_secs) {
+        Some(first) if config.usd_normalization.enabled => {
+            let sol_usd = SolUsd::new(&config.usd_normalization);
+            let since = first - config.usd_normalization.max_age_secs as i64;
+            match sol_usd.load_history(&config, since).await {
+                Ok(loaded) => info!("💵 Loaded {} SOL/USD prices to convert trades with", loaded),
+                Err(e) => warn!("⚠️  Failed to load SOL/USD history, replaying SOL series only: {:#}", e),
+            }
+            Some(sol_usd)
+        }
+        _ => None,
+    };
+
+    let mut publisher = if args.publish {
+        Some((create_producer(&config.kafka)?, Codec::new(&config)?))
+    } else {
+        None
+    };
+
+    let mut output_count = 0u64;
+    for trade in trades {
+        let time_ms = trade.block_time_secs().map(|secs| secs * 1000);
+        let outputs = sol_usd::calculate(sol_usd.as_ref(), &mut calculator, trade);
+        output_count += outputs.len() as u64;
+
+        for output in &outputs {
+            match &mut publisher {
+                Some((producer, codec)) => publish(&config, producer, codec, output, time_ms).await?,
+                None => println!("{}", output.to_json()?),
+            }
+        }
+    }
+
+    // Close the trailing bars so candle-based indicators cover the whole dump
+    for output in calculator.finalize_all() {
+        output_count += 1;
+        match &mut publisher {
+            Some((producer, codec)) => publish(&config, producer, codec, &output, None).await?,
+            None => println!("{}", output.to_json()?),
+        }
+    }
+
+    info!(
+        "✅ Backfill complete: {} indicator values for {} tokens ({} filtered, {} duplicates, {} outliers, {} late trades, {} tokens evicted)",
+        output_count,
+        calculator.tokens().len(),
+        calculator.filtered_trades(),
+        calculator.duplicate_trades(),
+        calculator.outlier_trades(),
+        calculator.late_trades(),
+        calculator.evicted_idle() + calculator.evicted_lru()
+    );
+
+    Ok(())
+}
+
+/// Read every parseable row of the dump, skipping malformed ones
+pub fn read_trades(path: &Path) -> Result<Vec<TradeMessage>> {
+    let mut reader = csv::Reader::from_path(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+
+    let mut trades = Vec::new();
+    for (index, row) in reader.deserialize::<CsvTrade>().enumerate() {
+        match row {
+            Ok(row) => trades.push(TradeMessage::from(row)),
+            Err(e) => warn!("⚠️  Skipping row {}: {}", index + 2, e),
+        }
+    }
+
+    Ok(trades)
+}
+
+async fn publish(
+    config: &Config,
+    producer: &FutureProducer,
+    codec: &mut Codec,
+    output: &IndicatorOutput,
+    time_ms: Option<i64>,
+) -> Result<()> {
+    for topic in output.topics(config) {
+        let encoded = codec.encode(output, topic).await?;
+
+        // Replayed trades have no headers to carry, only the static ones
+        let key = output.key(&config.kafka.key_format);
+        let mut record = FutureRecord::to(topic)
+            .key(&key)
+            .payload(&encoded)
+            .headers(OutputHeaders::new(&config.headers).build(&Carried::new()));
+        if let Some(time_ms) = time_ms {
+            record = record.timestamp(time_ms);
+        }
+
+        producer
+            .send(record, Duration::from_secs(0))
+            .await
+            .map_err(|(e, _)| anyhow::anyhow!("Failed to publish {}: {}", output.kind(), e))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::message::Message;
+use rdkafka::{Offset, TopicPartitionList};
+use crate::strategy::{Backtest, BacktestReport};
+use std::fs::File;
+use std::io::Write;
+use std::time::Duration;
+
+use crate::backfill::read_trades;
+use crate::cli::{BacktestArgs, ReportFormat};
+use crate::codec::Codec;
+use crate::config::Config;
+use crate::warmup::{RangeReader, Ranges};
+use crate::service::consumer_client_config;
+use crate::{RsiCalculator, TradeMessage};
+
+/// Upper bound for each broker query made while locating the time range
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest a topic range may take to read
+const READ_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Run historical trades through a fresh calculator, trade the configured
+/// signal rules on its outputs and write the performance report
+///
+/// Trades come from a CSV dump or a time range of the input topic and are
+/// replayed in `block_time` order, exactly as `backfill` does.
+pub async fn run(config: Config, args: &BacktestArgs) -> Result<()> {
+    let mut trades = match (&args.file, args.from) {
+        (Some(file), _) => read_trades(file)?,
+        (None, Some(from)) => read_topic(&config, from, args.to.unwrap_or_else(Utc::now)).await?,
+        (None, None) => anyhow::bail!("backtest needs --file or --from"),
+    };
+    trades.sort_by_key(|trade| trade.block_time_secs().unwrap_or(i64::MAX));
+    info!("📈 Backtesting {} signals on {} trades", config.backtest.indicator, trades.len());
+
+    let mut calculator = RsiCalculator::new(&config);
+    let mut backtest = Backtest::new(&config.backtest);
+    let mut last_price = 0.0;
+    for trade in trades {
+        last_price = trade.price_in_sol;
+        for output in calculator.process_trade(trade) {
+            backtest.on_output(&output, last_price);
+        }
+    }
+    for output in calculator.finalize_all() {
+        backtest.on_output(&output, last_price);
+    }
+
+    let report = backtest.finish();
+    info!(
+        "✅ Backtest complete: {} trades, {:.1}% won, {:.6} SOL PnL, {:.6} SOL max drawdown",
+        report.summary.trades,
+        report.summary.win_rate * 100.0,
+        report.summary.pnl_sol,
+        report.summary.max_drawdown_sol
+    );
+
+    let writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(File::create(path).with_context(|| format!("Failed to create {}", path.display()))?),
+        None => Box::new(std::io::stdout()),
+    };
+    write_report(&report, args.report, writer)
+}
+
+fn write_report(report: &BacktestReport, format: ReportFormat, mut writer: impl Write) -> Result<()> {
+    match format {
+        ReportFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, report)?;
+            writeln!(writer)?;
+        }
+        ReportFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(writer);
+            for row in std::iter::once(&report.summary).chain(&report.tokens) {
+                csv.serialize(row)?;
+            }
+            csv.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Trades published to the input topics between `from` and `to`
+async fn read_topic(config: &Config, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<TradeMessage>> {
+    // Never commits, so the group only has to be distinct from the consumer's
+    let consumer: StreamConsumer = consumer_client_config(&config.kafka)
+        .set("group.id", format!("{}-backtest", config.kafka.group_id))
+        .set("enable.auto.commit", "false")
+        .set("enable.auto.offset.store", "false")
+        .create()
+        .context("Failed to create backtest consumer")?;
+
+    let mut ranges = Ranges::new();
+    for topic in config.kafka.subscriptions() {
+        if topic.starts_with('^') {
+            warn!("⚠️  Backtest skips pattern subscription '{}'", topic);
+            continue;
+        }
+        let metadata = consumer
+            .fetch_metadata(Some(topic), QUERY_TIMEOUT)
+            .with_context(|| format!("Failed to fetch metadata for '{}'", topic))?;
+        for partition in metadata.topics().iter().flat_map(|topic| topic.partitions()) {
+            let (_, high) = consumer.fetch_watermarks(topic, partition.id(), QUERY_TIMEOUT)?;
+            let start = offset_at(&consumer, topic, partition.id(), from)?.unwrap_or(high);
+            let end = offset_at(&consumer, topic, partition.id(), to)?.unwrap_or(high);
+            if start < end {
+                ranges.insert((topic.to_string(), partition.id()), (start, end));
+            }
+        }
+    }
+
+    let mut codec = Codec::new(config)?;
+    let mut reader = RangeReader::new(consumer, ranges, READ_TIMEOUT)?;
+    let mut trades = Vec::new();
+    while let Some(message) = reader.next().await {
+        let Some(payload) = message.payload() else {
+            continue;
+        };
+        match codec.decode_trades(message.topic(), payload).await {
+            Ok(decoded) => trades.extend(decoded),
+            Err(e) => debug!("Skipping undecodable trade: {:#}", e),
+        }
+    }
+    Ok(trades)
+}
+
+/// First offset of a partition at or after `time`; None past the end
+fn offset_at(consumer: &StreamConsumer, topic: &str, partition: i32, time: DateTime<Utc>) -> Result<Option<i64>> {
+    let mut query = TopicPartitionList::new();
+    query.add_partition_offset(topic, partition, Offset::Offset(time.timestamp_millis()))?;
+    let offsets = consumer
+        .offsets_for_times(query, QUERY_TIMEOUT)
+        .context("Failed to look up offsets by time")?;
+
+    Ok(match offsets.to_topic_map().get(&(topic.to_string(), partition)) {
+        Some(&Offset::Offset(offset)) => Some(offset),
+        _ => None,
+    })
+}
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// OHLCV bar aggregated from individual trades
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candle {
+    /// Bucket start, Unix seconds
+    pub start_time: i64,
+    pub interval_secs: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Sum of `amount_in_sol` across the bar's trades
+    pub volume: f64,
+    pub trade_count: u64,
+}
+
+impl Candle {
+    fn new(start_time: i64, interval_secs: i64, price: f64, volume: f64) -> Self {
+        Self {
+            start_time,
+            interval_secs,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume,
+            trade_count: 1,
+        }
+    }
+
+    fn add(&mut self, price: f64, volume: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += volume;
+        self.trade_count += 1;
+    }
+
+    /// Bucket end (exclusive), Unix seconds
+    pub fn end_time(&self) -> i64 {
+        self.start_time + self.interval_secs
+    }
+}
+
+/// Completed candle published to the candles topic
+#[derive(Debug, Serialize)]
+pub struct CandleMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Human-readable interval, e.g. "1m" or "15m"
+    pub interval: String,
+    pub interval_secs: i64,
+    /// Bucket start (RFC 3339)
+    pub open_time: String,
+    /// Bucket end, exclusive (RFC 3339)
+    pub close_time: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub trade_count: u64,
+}
+
+impl CandleMessage {
+    pub fn new(token_address: &str, candle: &Candle) -> Self {
+        Self {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            interval: format_interval(candle.interval_secs),
+            interval_secs: candle.interval_secs,
+            open_time: crate::format_unix_time(candle.start_time),
+            close_time: crate::format_unix_time(candle.end_time()),
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            volume: candle.volume,
+            trade_count: candle.trade_count,
+        }
+    }
+}
+
+/// Format an interval as "30s", "5m", "1h" or "1d"
+pub fn format_interval(secs: i64) -> String {
+    match secs {
+        s if s % 86_400 == 0 => format!("{}d", s / 86_400),
+        s if s % 3_600 == 0 => format!("{}h", s / 3_600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+/// Builds fixed-interval candles for one token from its trade stream.
+///
+/// Several buckets may be open at once so trades arriving slightly late still
+/// land in the right bar; a bar is finalized once the event-time watermark
+/// passes its end plus the allowed lateness.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandleBuilder {
+    interval_secs: i64,
+    open: BTreeMap<i64, Candle>,
+    // Start of the most recently finalized bucket
+    last_closed: Option<i64>,
+}
+
+impl CandleBuilder {
+    pub fn new(interval_secs: i64) -> Self {
+        Self {
+            interval_secs,
+            open: BTreeMap::new(),
+            last_closed: None,
+        }
+    }
+
+    /// Add a trade at `time` (Unix seconds).
+    ///
+    /// Returns `false` if the trade belongs to a bar that was already
+    /// finalized and published; such trades are dropped.
+    pub fn add_trade(&mut self, time: i64, price: f64, volume: f64) -> bool {
+        let bucket = time - time.rem_euclid(self.interval_secs);
+
+        if self.last_closed.is_some_and(|closed| bucket <= closed) {
+            return false;
+        }
+
+        self.open
+            .entry(bucket)
+            .and_modify(|candle| candle.add(price, volume))
+            .or_insert_with(|| Candle::new(bucket, self.interval_secs, price, volume));
+
+        true
+    }
+
+    /// Remove and return every bar whose end + `lateness` is at or before
+    /// `watermark`, oldest first
+    pub fn finalize(&mut self, watermark: i64, lateness: i64) -> Vec<Candle> {
+        let mut closed = Vec::new();
+
+        while let Some(entry) = self.open.first_entry() {
+            if entry.get().end_time() + lateness > watermark {
+                break;
+            }
+            let candle = entry.remove();
+            self.last_closed = Some(candle.start_time);
+            closed.push(candle);
+        }
+
+        closed
+    }
+}
+
+/// Per-token candle builders for every configured interval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandleAggregator {
+    builders: Vec<CandleBuilder>,
+}
+
+impl CandleAggregator {
+    pub fn new(intervals_secs: &[i64]) -> Self {
+        Self {
+            builders: intervals_secs.iter().map(|&secs| CandleBuilder::new(secs)).collect(),
+        }
+    }
+
+    /// Add a trade to every interval; returns `false` if any interval
+    /// rejected it as late
+    pub fn add_trade(&mut self, time: i64, price: f64, volume: f64) -> bool {
+        let mut accepted = true;
+        for builder in &mut self.builders {
+            accepted &= builder.add_trade(time, price, volume);
+        }
+        accepted
+    }
+
+    /// Finalize due bars across all intervals, ordered by close time
+    pub fn finalize(&mut self, watermark: i64, lateness: i64) -> Vec<Candle> {
+        let mut closed: Vec<Candle> = self
+            .builders
+            .iter_mut()
+            .flat_map(|builder| builder.finalize(watermark, lateness))
+            .collect();
+
+        closed.sort_by_key(|candle| (candle.end_time(), candle.interval_secs));
+        closed
+    }
+}
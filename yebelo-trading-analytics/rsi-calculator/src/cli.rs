@@ -0,0 +1,293 @@
+use chrono::{DateTime, Utc};
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::Smoothing;
+
+/// Real-time RSI calculator for pump.fun trades streamed through Redpanda
+#[derive(Debug, Parser)]
+#[command(name = "rsi-calculator", version, about, long_about = None)]
+pub struct Cli {
+    /// Path to a TOML config file (defaults to $RSI_CALC_CONFIG or ./config.toml if present)
+    #[arg(short, long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Log filter, e.g. "info" or "rsi_calculator=debug" (overrides logging.level)
+    #[arg(long, global = true, value_name = "FILTER")]
+    pub log_level: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Consume trades and publish RSI values (the default when no subcommand is given)
+    Run(RunArgs),
+    /// Replay a CSV trade dump through the calculator, printing or publishing the results
+    Backfill(BackfillArgs),
+    /// Trade the [backtest] signal rules over historical trades and report performance
+    Backtest(BacktestArgs),
+    /// Publish synthetic trades to the input topic for load and integration testing
+    Generate(GenerateArgs),
+    /// Check indicator output against reference values from a known TA implementation
+    Verify(VerifyArgs),
+}
+
+/// Options for the `run` subcommand
+#[derive(Debug, Default, Args)]
+pub struct RunArgs {
+    #[command(flatten)]
+    pub kafka: KafkaArgs,
+
+    #[command(flatten)]
+    pub rsi: RsiArgs,
+}
+
+/// Options for the `backfill` subcommand
+#[derive(Debug, Args)]
+pub struct BackfillArgs {
+    /// CSV trade dump with a header row (as read by the ingestion script)
+    #[arg(long, value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Publish results to Kafka with original timestamps instead of printing them
+    #[arg(long)]
+    pub publish: bool,
+
+    #[command(flatten)]
+    pub kafka: KafkaArgs,
+
+    #[command(flatten)]
+    pub rsi: RsiArgs,
+}
+
+/// Options for the `backtest` subcommand
+#[derive(Debug, Args)]
+#[command(group(clap::ArgGroup::new("source").required(true).args(["file", "from"])))]
+pub struct BacktestArgs {
+    /// CSV trade dump with a header row (as read by the ingestion script)
+    #[arg(long, value_name = "FILE")]
+    pub file: Option<PathBuf>,
+
+    /// Read trades from the input topic starting at this time (RFC 3339)...
+    #[arg(long, value_name = "TIME", value_parser = parse_time)]
+    pub from: Option<DateTime<Utc>>,
+
+    /// ...up to this time (defaults to now)
+    #[arg(long, value_name = "TIME", value_parser = parse_time, requires = "from")]
+    pub to: Option<DateTime<Utc>>,
+
+    /// Report format
+    #[arg(long, value_name = "FORMAT", default_value = "json")]
+    pub report: ReportFormat,
+
+    /// Write the report here instead of stdout
+    #[arg(long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub kafka: KafkaArgs,
+
+    #[command(flatten)]
+    pub rsi: RsiArgs,
+}
+
+/// Options for the `generate` subcommand
+#[derive(Debug, Args)]
+pub struct GenerateArgs {
+    /// Stop after this many trades
+    #[arg(long, value_name = "N")]
+    pub count: Option<u64>,
+
+    /// Stop after this many seconds
+    #[arg(long, value_name = "SECS")]
+    pub duration: Option<u64>,
+
+    /// Trades per second across all tokens (overrides generator.rate_per_sec)
+    #[arg(long, value_name = "N", value_parser = parse_rate)]
+    pub rate: Option<f64>,
+
+    /// Number of made-up tokens (overrides generator.tokens)
+    #[arg(long, value_name = "N", value_parser = parse_period)]
+    pub tokens: Option<usize>,
+
+    /// Seed for a reproducible run (overrides generator.seed)
+    #[arg(long, value_name = "N")]
+    pub seed: Option<u64>,
+
+    /// Print trades to stdout as JSON lines instead of publishing them
+    #[arg(long)]
+    pub print: bool,
+
+    #[command(flatten)]
+    pub kafka: KafkaArgs,
+}
+
+/// Options for the `verify` subcommand
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    /// Reference fixture (TOML); repeat for several. Defaults to every
+    /// fixture in fixtures/reference
+    #[arg(long = "fixture", value_name = "FILE")]
+    pub fixtures: Vec<PathBuf>,
+}
+
+/// Backtest report formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// Summary plus per-token results as one JSON document
+    Json,
+    /// One row for the total and one per token
+    Csv,
+}
+
+/// Kafka connection and topic overrides
+#[derive(Debug, Default, Args)]
+pub struct KafkaArgs {
+    /// Comma-separated bootstrap brokers (overrides kafka.brokers)
+    #[arg(long, value_name = "HOST:PORT", value_parser = parse_non_empty)]
+    pub brokers: Option<String>,
+
+    /// Consumer group id (overrides kafka.group_id)
+    #[arg(long, value_name = "GROUP", value_parser = parse_non_empty)]
+    pub group_id: Option<String>,
+
+    /// Topic to consume trades from (overrides kafka.input_topic)
+    #[arg(long, value_name = "TOPIC", value_parser = parse_non_empty)]
+    pub topic_in: Option<String>,
+
+    /// Topic to publish RSI values to (overrides kafka.output_topic)
+    #[arg(long, value_name = "TOPIC", value_parser = parse_non_empty)]
+    pub topic_out: Option<String>,
+}
+
+/// RSI parameter overrides
+#[derive(Debug, Default, Args)]
+pub struct RsiArgs {
+    /// RSI lookback period in trades; repeat or comma-separate for several (overrides rsi.periods)
+    #[arg(long = "period", value_name = "N", value_delimiter = ',', value_parser = parse_period)]
+    pub periods: Vec<usize>,
+
+    /// Smoothing mode: "wilder" or "simple" (overrides rsi.smoothing)
+    #[arg(long, value_name = "MODE")]
+    pub smoothing: Option<Smoothing>,
+
+    /// RSI level below which a token is oversold (overrides rsi.oversold)
+    #[arg(long, value_name = "RSI", value_parser = parse_rsi_level)]
+    pub oversold: Option<f64>,
+
+    /// RSI level above which a token is overbought (overrides rsi.overbought)
+    #[arg(long, value_name = "RSI", value_parser = parse_rsi_level)]
+    pub overbought: Option<f64>,
+}
+
+impl Cli {
+    /// The selected subcommand, defaulting to `run`
+    pub fn command(&mut self) -> Command {
+        self.command
+            .take()
+            .unwrap_or_else(|| Command::Run(RunArgs::default()))
+    }
+}
+
+impl RunArgs {
+    /// Apply command-line overrides on top of the loaded config
+    pub fn apply(&self, config: &mut Config) {
+        self.kafka.apply(config);
+        self.rsi.apply(config);
+    }
+}
+
+impl BackfillArgs {
+    /// Apply command-line overrides on top of the loaded config
+    pub fn apply(&self, config: &mut Config) {
+        self.kafka.apply(config);
+        self.rsi.apply(config);
+    }
+}
+
+impl BacktestArgs {
+    /// Apply command-line overrides on top of the loaded config
+    pub fn apply(&self, config: &mut Config) {
+        self.kafka.apply(config);
+        self.rsi.apply(config);
+    }
+}
+
+impl GenerateArgs {
+    /// Apply command-line overrides on top of the loaded config
+    pub fn apply(&self, config: &mut Config) {
+        self.kafka.apply(config);
+        override_with(&mut config.generator.rate_per_sec, &self.rate);
+        override_with(&mut config.generator.tokens, &self.tokens);
+        if self.seed.is_some() {
+            config.generator.seed = self.seed;
+        }
+    }
+}
+
+impl KafkaArgs {
+    pub fn apply(&self, config: &mut Config) {
+        override_with(&mut config.kafka.brokers, &self.brokers);
+        override_with(&mut config.kafka.group_id, &self.group_id);
+        override_with(&mut config.kafka.input_topic, &self.topic_in);
+        override_with(&mut config.kafka.output_topic, &self.topic_out);
+    }
+}
+
+impl RsiArgs {
+    pub fn apply(&self, config: &mut Config) {
+        if !self.periods.is_empty() {
+            config.rsi.periods = self.periods.clone();
+        }
+        override_with(&mut config.rsi.smoothing, &self.smoothing);
+        override_with(&mut config.rsi.oversold, &self.oversold);
+        override_with(&mut config.rsi.overbought, &self.overbought);
+    }
+}
+
+fn override_with<T: Clone>(target: &mut T, value: &Option<T>) {
+    if let Some(value) = value {
+        *target = value.clone();
+    }
+}
+
+fn parse_non_empty(s: &str) -> Result<String, String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err("value must not be empty".to_string());
+    }
+    Ok(s.to_string())
+}
+
+fn parse_period(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(0) => Err("period must be at least 1".to_string()),
+        Ok(period) => Ok(period),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn parse_rate(s: &str) -> Result<f64, String> {
+    let rate: f64 = s.parse().map_err(|e: std::num::ParseFloatError| e.to_string())?;
+    if rate <= 0.0 {
+        return Err("rate must be greater than 0".to_string());
+    }
+    Ok(rate)
+}
+
+fn parse_time(s: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| e.to_string())
+}
+
+fn parse_rsi_level(s: &str) -> Result<f64, String> {
+    let level: f64 = s.parse().map_err(|e: std::num::ParseFloatError| e.to_string())?;
+    if !(0.0..=100.0).contains(&level) {
+        return Err("RSI levels must be between 0 and 100".to_string());
+    }
+    Ok(level)
+}
@@ -0,0 +1,213 @@
+use anyhow::{bail, Context, Result};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::config::{InputSchema, LenientConfig};
+use crate::TradeMessage;
+
+/// How lenient parsing made a field usable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Fallback {
+    /// Sent with another JSON type and converted
+    Coerced,
+    /// Absent or null, so defaulted
+    Missing,
+    /// Not convertible, so defaulted
+    Invalid,
+}
+
+impl fmt::Display for Fallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Fallback::Coerced => "coerced",
+            Fallback::Missing => "missing",
+            Fallback::Invalid => "invalid",
+        })
+    }
+}
+
+/// Maps each input topic's JSON field names onto `TradeMessage`, filling in
+/// what lenient parsing allows
+#[derive(Default)]
+pub struct SchemaAdapters {
+    topics: HashMap<String, TopicSchema>,
+    lenient: LenientConfig,
+    // Fields filled in for each trade of the last decoded payload
+    filled: Vec<Vec<(&'static str, Fallback)>>,
+}
+
+struct TopicSchema {
+    // (TradeMessage field, path of the topic field) renames
+    renames: Vec<(&'static str, Vec<String>)>,
+    coerce: bool,
+}
+
+impl SchemaAdapters {
+    pub fn new(schemas: &BTreeMap<String, InputSchema>, lenient: &LenientConfig) -> Self {
+        let topics = schemas
+            .iter()
+            .map(|(topic, schema)| {
+                let renames = schema
+                    .renames()
+                    .into_iter()
+                    .map(|(field, name)| (field, name.split('.').map(str::to_string).collect()))
+                    .collect();
+                (topic.clone(), TopicSchema { renames, coerce: schema.coerce })
+            })
+            .collect();
+        Self {
+            topics,
+            lenient: lenient.clone(),
+            filled: Vec::new(),
+        }
+    }
+
+    /// Decode the JSON trades of a message consumed from `topic`, renaming
+    /// their fields first when the topic has a schema
+    ///
+    /// A payload holds one trade object, a JSON array of them, or several
+    /// separated by newlines (NDJSON). A batch is decoded whole or not at all.
+    pub fn decode(&mut self, topic: &str, payload: &[u8]) -> Result<Vec<TradeMessage>> {
+        let values: Vec<Value> = if payload.trim_ascii_start().starts_with(b"[") {
+            serde_json::from_slice(payload).context("Invalid JSON trade batch")?
+        } else {
+            serde_json::Deserializer::from_slice(payload)
+                .into_iter()
+                .collect::<Result<_, _>>()
+                .context("Invalid JSON trade")?
+        };
+        if values.is_empty() {
+            bail!("No trade in JSON payload");
+        }
+
+        self.filled.clear();
+        let batched = values.len() > 1;
+        let decoded = values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| {
+                self.decode_value(topic, value).with_context(|| {
+                    if batched {
+                        format!("Invalid trade #{} of a batch", i + 1)
+                    } else {
+                        "Invalid JSON trade".to_string()
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>>>();
+        let (trades, filled) = decoded?.into_iter().unzip();
+        self.filled = filled;
+        Ok(trades)
+    }
+
+    fn decode_value(
+        &self,
+        topic: &str,
+        mut value: Value,
+    ) -> Result<(TradeMessage, Vec<(&'static str, Fallback)>)> {
+        let schema = self.topics.get(topic);
+        let mut filled = Vec::new();
+        if let Some(fields) = value.as_object_mut() {
+            if let Some(schema) = schema {
+                // Take every mapped field out before inserting any, so two
+                // fields swapping names don't overwrite each other
+                let mapped: Vec<_> = schema
+                    .renames
+                    .iter()
+                    .filter_map(|(field, path)| Some((*field, take(fields, path)?)))
+                    .collect();
+                fields.extend(mapped.into_iter().map(|(field, v)| (field.to_string(), v)));
+            }
+            if self.lenient.enabled || schema.is_some_and(|schema| schema.coerce) {
+                filled.extend(coerce(fields).into_iter().map(|field| (field, Fallback::Coerced)));
+            }
+            if self.lenient.enabled {
+                filled.extend(fill_defaults(fields, self.lenient.default_is_buy));
+            }
+        }
+
+        let trade = match schema {
+            Some(_) => {
+                serde_json::from_value(value).with_context(|| format!("Does not match the '{}' input schema", topic))?
+            }
+            None => serde_json::from_value(value)?,
+        };
+        Ok((trade, filled))
+    }
+
+    /// Fields lenient parsing filled in or converted for trade `index` of
+    /// the last decoded payload
+    pub fn fallbacks(&self, index: usize) -> &[(&'static str, Fallback)] {
+        self.filled.get(index).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Remove the value at a dotted path, leaving its parent objects in place
+fn take(fields: &mut Map<String, Value>, path: &[String]) -> Option<Value> {
+    match path {
+        [] => None,
+        [name] => fields.remove(name),
+        [name, rest @ ..] => take(fields.get_mut(name)?.as_object_mut()?, rest),
+    }
+}
+
+/// Convert `TradeMessage` fields sent with another JSON type, returning the
+/// fields converted; values that can't be converted are left as they are
+fn coerce(fields: &mut Map<String, Value>) -> Vec<&'static str> {
+    let mut coerced = Vec::new();
+    for field in ["token_address", "price_in_sol", "block_time", "transaction_signature", "is_buy", "amount_in_sol"] {
+        let Some(v) = fields.get_mut(field) else {
+            continue;
+        };
+        let converted = match (field, &*v) {
+            ("price_in_sol" | "amount_in_sol", Value::String(s)) => {
+                s.trim().parse().ok().and_then(serde_json::Number::from_f64).map(Value::Number)
+            }
+            ("token_address" | "block_time" | "transaction_signature", Value::Number(number)) => {
+                Some(Value::String(number.to_string()))
+            }
+            ("is_buy", Value::String(s)) => match s.trim().to_ascii_lowercase().as_str() {
+                "buy" | "true" | "1" => Some(Value::Bool(true)),
+                "sell" | "false" | "0" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            ("is_buy", Value::Number(number)) => number.as_f64().map(|n| Value::Bool(n != 0.0)),
+            _ => None,
+        };
+        if let Some(converted) = converted {
+            *v = converted;
+            coerced.push(field);
+        }
+    }
+    coerced
+}
+
+/// A defaulted field, whether a value sent for it is usable, and its default
+type DefaultRule = (&'static str, fn(&Value) -> bool, Value);
+
+/// Default the optional fields that are absent or still mistyped after
+/// coercion
+///
+/// `token_address`, `price_in_sol`, `block_time` and `amount_in_sol` have no
+/// sensible default: a made-up trade time would reorder candles, and a zero
+/// amount fails validation anyway, so trades without them are still rejected.
+fn fill_defaults(fields: &mut Map<String, Value>, default_is_buy: bool) -> Vec<(&'static str, Fallback)> {
+    let defaults: [DefaultRule; 2] = [
+        // Trades without a signature are never treated as duplicates
+        ("transaction_signature", Value::is_string, Value::String(String::new())),
+        ("is_buy", Value::is_boolean, Value::Bool(default_is_buy)),
+    ];
+
+    let mut filled = Vec::new();
+    for (field, usable, default) in defaults {
+        let fallback = match fields.get(field) {
+            None | Some(Value::Null) => Fallback::Missing,
+            Some(v) if usable(v) => continue,
+            Some(_) => Fallback::Invalid,
+        };
+        fields.insert(field.to_string(), default);
+        filled.push((field, fallback));
+    }
+    filled
+}
@@ -0,0 +1,167 @@
+use anyhow::{bail, Context, Result};
+use apache_avro::Schema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::config::SchemaRegistryConfig;
+use crate::{RsiMessage, TradeMessage};
+
+/// First byte of every Confluent-framed message, followed by a 4-byte schema id
+const MAGIC_BYTE: u8 = 0;
+
+const TRADE_SCHEMA: &str = include_str!("../../schemas/trade.avsc");
+const RSI_SCHEMA: &str = include_str!("../../schemas/rsi.avsc");
+
+const REGISTRY_CONTENT_TYPE: &str = "application/vnd.schemaregistry.v1+json";
+
+/// Avro encoding with schemas resolved through Schema Registry
+pub struct AvroCodec {
+    registry: SchemaRegistry,
+    /// Reader schema trades are resolved into
+    trade_schema: Schema,
+    rsi_schema: Schema,
+    /// Writer schemas of consumed trades, by registry id
+    writer_schemas: HashMap<u32, Schema>,
+    /// Registered RSI schema id, by subject
+    rsi_schema_ids: HashMap<String, u32>,
+}
+
+impl AvroCodec {
+    pub fn new(config: &SchemaRegistryConfig) -> Result<Self> {
+        Ok(Self {
+            registry: SchemaRegistry::new(config)?,
+            trade_schema: Schema::parse_str(TRADE_SCHEMA).context("Invalid trade Avro schema")?,
+            rsi_schema: Schema::parse_str(RSI_SCHEMA).context("Invalid RSI Avro schema")?,
+            writer_schemas: HashMap::new(),
+            rsi_schema_ids: HashMap::new(),
+        })
+    }
+
+    pub async fn decode_trade(&mut self, payload: &[u8]) -> Result<TradeMessage> {
+        let (id, mut body) = split_wire_format(payload)?;
+
+        if !self.writer_schemas.contains_key(&id) {
+            let schema = self.registry.schema_by_id(id).await?;
+            let schema = Schema::parse_str(&schema)
+                .with_context(|| format!("Invalid Avro schema {} in registry", id))?;
+            self.writer_schemas.insert(id, schema);
+        }
+
+        let value = apache_avro::from_avro_datum(&self.writer_schemas[&id], &mut body, Some(&self.trade_schema))
+            .with_context(|| format!("Invalid Avro trade for schema {}", id))?;
+
+        apache_avro::from_value(&value).context("Avro trade does not match the trade schema")
+    }
+
+    /// Encode RSI, registering its schema under `<topic>-value` on first use
+    pub async fn encode_rsi(&mut self, msg: &RsiMessage, topic: &str) -> Result<Vec<u8>> {
+        let subject = format!("{}-value", topic);
+        let id = match self.rsi_schema_ids.get(&subject) {
+            Some(&id) => id,
+            None => {
+                let id = self.registry.register(&subject, RSI_SCHEMA).await?;
+                log::info!("📜 Registered RSI Avro schema under '{}' (id {})", subject, id);
+                self.rsi_schema_ids.insert(subject, id);
+                id
+            }
+        };
+
+        let value = apache_avro::to_value(msg).context("Failed to convert RSI message to Avro")?;
+        let datum = apache_avro::to_avro_datum(&self.rsi_schema, value).context("Failed to encode RSI message as Avro")?;
+
+        let mut payload = Vec::with_capacity(5 + datum.len());
+        payload.push(MAGIC_BYTE);
+        payload.extend_from_slice(&id.to_be_bytes());
+        payload.extend(datum);
+
+        Ok(payload)
+    }
+}
+
+/// Split a Confluent-framed payload into its schema id and Avro body
+fn split_wire_format(payload: &[u8]) -> Result<(u32, &[u8])> {
+    match payload {
+        [MAGIC_BYTE, a, b, c, d, body @ ..] => Ok((u32::from_be_bytes([*a, *b, *c, *d]), body)),
+        _ => bail!("Not in Confluent Avro wire format (missing magic byte or schema id)"),
+    }
+}
+
+/// Minimal Confluent Schema Registry REST client
+struct SchemaRegistry {
+    client: reqwest::Client,
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SchemaResponse {
+    schema: String,
+}
+
+#[derive(Serialize)]
+struct RegisterRequest<'a> {
+    schema: &'a str,
+}
+
+#[derive(Deserialize)]
+struct RegisterResponse {
+    id: u32,
+}
+
+impl SchemaRegistry {
+    fn new(config: &SchemaRegistryConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .context("Failed to create Schema Registry client")?;
+
+        Ok(Self {
+            client,
+            url: config.url.trim_end_matches('/').to_string(),
+            username: config.username.clone(),
+            password: config.password.clone(),
+        })
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.username {
+            Some(username) => request.basic_auth(username, self.password.as_ref()),
+            None => request,
+        }
+    }
+
+    async fn schema_by_id(&self, id: u32) -> Result<String> {
+        let url = format!("{}/schemas/ids/{}", self.url, id);
+        let response: SchemaResponse = self
+            .authorize(self.client.get(&url))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .with_context(|| format!("Failed to fetch schema {} from {}", id, self.url))?
+            .json()
+            .await
+            .context("Invalid Schema Registry response")?;
+
+        Ok(response.schema)
+    }
+
+    /// Register `schema` under `subject`, returning its id (idempotent)
+    async fn register(&self, subject: &str, schema: &str) -> Result<u32> {
+        let url = format!("{}/subjects/{}/versions", self.url, subject);
+        let response: RegisterResponse = self
+            .authorize(self.client.post(&url))
+            .header("Content-Type", REGISTRY_CONTENT_TYPE)
+            .json(&RegisterRequest { schema })
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .with_context(|| format!("Failed to register schema under '{}' at {}", subject, self.url))?
+            .json()
+            .await
+            .context("Invalid Schema Registry response")?;
+
+        Ok(response.id)
+    }
+}
@@ -0,0 +1,75 @@
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::config::CsvInputConfig;
+use crate::TradeMessage;
+
+/// Decodes trades sent as raw CSV lines, by column position
+pub struct CsvDecoder {
+    delimiter: u8,
+    token_address: usize,
+    price_in_sol: usize,
+    block_time: usize,
+    transaction_signature: Option<usize>,
+    is_buy: usize,
+    amount_in_sol: usize,
+    quote_currency: Option<usize>,
+}
+
+impl CsvDecoder {
+    pub fn new(config: &CsvInputConfig) -> Result<Self> {
+        let column = |name: &str| config.columns.iter().position(|column| column == name);
+        let required = |name: &str| column(name).ok_or_else(|| anyhow!("csv_input.columns has no '{}' column", name));
+        Ok(Self {
+            delimiter: config.delimiter_byte()?,
+            token_address: required("token_address")?,
+            price_in_sol: required("price_in_sol")?,
+            block_time: required("block_time")?,
+            transaction_signature: column("transaction_signature"),
+            is_buy: required("is_buy")?,
+            amount_in_sol: required("amount_in_sol")?,
+            quote_currency: column("quote_currency"),
+        })
+    }
+
+    /// Decode every line of a payload; a header line repeating the column
+    /// names is skipped
+    pub fn decode(&self, payload: &[u8]) -> Result<Vec<TradeMessage>> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .delimiter(self.delimiter)
+            .flexible(true)
+            .from_reader(payload);
+
+        let mut trades = Vec::new();
+        for (line, record) in reader.records().enumerate() {
+            let record = record.with_context(|| format!("Invalid CSV on line {}", line + 1))?;
+            let field = |index: usize| record.get(index).map(str::trim).unwrap_or_default();
+            if field(self.token_address) == "token_address" {
+                continue;
+            }
+            let number = |index: usize, name: &str| -> Result<f64> {
+                match field(index) {
+                    "" => Ok(0.0),
+                    raw => raw
+                        .parse()
+                        .with_context(|| format!("Invalid {} '{}' on CSV line {}", name, raw, line + 1)),
+                }
+            };
+
+            trades.push(TradeMessage {
+                token_address: field(self.token_address).to_string(),
+                price_in_sol: number(self.price_in_sol, "price_in_sol")?,
+                block_time: field(self.block_time).to_string(),
+                transaction_signature: self.transaction_signature.map(field).unwrap_or_default().to_string(),
+                is_buy: field(self.is_buy).eq_ignore_ascii_case("true"),
+                amount_in_sol: number(self.amount_in_sol, "amount_in_sol")?,
+                processed_timestamp: String::new(),
+                quote_currency: self.quote_currency.map(field).unwrap_or_default().to_string(),
+            });
+        }
+        if trades.is_empty() {
+            bail!("No trade in CSV payload");
+        }
+        Ok(trades)
+    }
+}
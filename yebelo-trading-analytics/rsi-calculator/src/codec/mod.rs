@@ -0,0 +1,78 @@
+pub mod adapter;
+pub mod avro;
+pub mod delimited;
+pub mod protobuf;
+
+use anyhow::{Context, Result};
+
+use crate::config::{Config, MessageFormat};
+use crate::indicators::IndicatorOutput;
+use crate::TradeMessage;
+use adapter::{Fallback, SchemaAdapters};
+use avro::AvroCodec;
+use delimited::CsvDecoder;
+
+/// Wire format for consumed trades and published indicator messages
+pub enum Codec {
+    Json(SchemaAdapters),
+    Avro(Box<AvroCodec>),
+    Protobuf,
+    MsgPack,
+    Csv(CsvDecoder),
+}
+
+impl Codec {
+    pub fn new(config: &Config) -> Result<Self> {
+        match config.kafka.format {
+            MessageFormat::Json => Ok(Codec::Json(SchemaAdapters::new(&config.input_schemas, &config.lenient))),
+            MessageFormat::Avro => Ok(Codec::Avro(Box::new(AvroCodec::new(&config.schema_registry)?))),
+            MessageFormat::Protobuf => Ok(Codec::Protobuf),
+            MessageFormat::MsgPack => Ok(Codec::MsgPack),
+            MessageFormat::Csv => Ok(Codec::Csv(CsvDecoder::new(&config.csv_input)?)),
+        }
+    }
+
+    /// Decode the trades of a message consumed from `topic`
+    ///
+    /// JSON and CSV messages may batch several trades (see
+    /// [`SchemaAdapters::decode`] and [`CsvDecoder::decode`]); Avro, Protobuf
+    /// and MessagePack messages always hold one.
+    pub async fn decode_trades(&mut self, topic: &str, payload: &[u8]) -> Result<Vec<TradeMessage>> {
+        match self {
+            Codec::Json(adapters) => adapters.decode(topic, payload),
+            Codec::Avro(avro) => avro.decode_trade(payload).await.map(|trade| vec![trade]),
+            Codec::Protobuf => protobuf::decode_trade(payload).map(|trade| vec![trade]),
+            Codec::MsgPack => rmp_serde::from_slice(payload)
+                .map(|trade| vec![trade])
+                .context("Invalid MessagePack trade"),
+            Codec::Csv(csv) => csv.decode(payload),
+        }
+    }
+
+    /// Fields lenient parsing filled in or converted for trade `index` of
+    /// the last decoded payload; always empty for every format but JSON
+    pub fn fallbacks(&self, index: usize) -> &[(&'static str, Fallback)] {
+        match self {
+            Codec::Json(adapters) => adapters.fallbacks(index),
+            _ => &[],
+        }
+    }
+
+    /// Encode an indicator message for `topic`
+    ///
+    /// Only RSI has Avro and Protobuf schemas; the other indicator topics
+    /// are JSON then. MessagePack covers every topic.
+    pub async fn encode(&mut self, output: &IndicatorOutput, topic: &str) -> Result<Vec<u8>> {
+        match (self, output) {
+            (Codec::Avro(avro), IndicatorOutput::Rsi(msg)) => avro.encode_rsi(msg, topic).await,
+            (Codec::Protobuf, IndicatorOutput::Rsi(msg)) => Ok(protobuf::encode_rsi(msg)),
+            (Codec::MsgPack, _) => output
+                .to_msgpack()
+                .with_context(|| format!("Failed to serialize {} message", output.kind())),
+            _ => output
+                .to_json()
+                .map(String::into_bytes)
+                .with_context(|| format!("Failed to serialize {} message", output.kind())),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Protobuf message types for `proto/trading.proto`.
+//!
+//! Written in the shape `prost-build` generates so no `protoc` is needed at
+//! build time; keep the field tags in sync with the .proto file.
+
+use anyhow::{Context, Result};
+use prost::Message;
+
+use crate::{RsiMessage, TradeMessage};
+
+/// `yebelo.trading.Trade`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Trade {
+    #[prost(string, tag = "1")]
+    pub token_address: String,
+    #[prost(double, tag = "2")]
+    pub price_in_sol: f64,
+    #[prost(string, tag = "3")]
+    pub block_time: String,
+    #[prost(string, tag = "4")]
+    pub transaction_signature: String,
+    #[prost(bool, tag = "5")]
+    pub is_buy: bool,
+    #[prost(double, tag = "6")]
+    pub amount_in_sol: f64,
+    #[prost(string, tag = "7")]
+    pub processed_timestamp: String,
+    #[prost(string, tag = "8")]
+    pub quote_currency: String,
+}
+
+/// `yebelo.trading.Rsi`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Rsi {
+    #[prost(string, tag = "1")]
+    pub token_address: String,
+    #[prost(double, tag = "2")]
+    pub rsi_value: f64,
+    #[prost(double, tag = "3")]
+    pub current_price: f64,
+    #[prost(string, tag = "4")]
+    pub timestamp: String,
+    #[prost(uint32, tag = "5")]
+    pub period: u32,
+    #[prost(string, tag = "6")]
+    pub signal: String,
+    #[prost(string, tag = "7")]
+    pub timeframe: String,
+    #[prost(string, tag = "8")]
+    pub processed_at: String,
+    #[prost(uint32, tag = "9")]
+    pub schema_version: u32,
+    #[prost(double, optional, tag = "10")]
+    pub avg_gain: Option<f64>,
+    #[prost(double, optional, tag = "11")]
+    pub avg_loss: Option<f64>,
+    #[prost(string, tag = "12")]
+    pub quote_currency: String,
+}
+
+impl From<Trade> for TradeMessage {
+    fn from(trade: Trade) -> Self {
+        Self {
+            token_address: trade.token_address,
+            price_in_sol: trade.price_in_sol,
+            block_time: trade.block_time,
+            transaction_signature: trade.transaction_signature,
+            is_buy: trade.is_buy,
+            amount_in_sol: trade.amount_in_sol,
+            processed_timestamp: trade.processed_timestamp,
+            quote_currency: trade.quote_currency,
+        }
+    }
+}
+
+impl From<&TradeMessage> for Trade {
+    fn from(trade: &TradeMessage) -> Self {
+        Self {
+            token_address: trade.token_address.clone(),
+            price_in_sol: trade.price_in_sol,
+            block_time: trade.block_time.clone(),
+            transaction_signature: trade.transaction_signature.clone(),
+            is_buy: trade.is_buy,
+            amount_in_sol: trade.amount_in_sol,
+            processed_timestamp: trade.processed_timestamp.clone(),
+            quote_currency: trade.quote_currency.clone(),
+        }
+    }
+}
+
+impl From<&RsiMessage> for Rsi {
+    fn from(msg: &RsiMessage) -> Self {
+        Self {
+            token_address: msg.token_address.clone(),
+            rsi_value: msg.rsi_value,
+            current_price: msg.current_price,
+            timestamp: msg.timestamp.clone(),
+            period: msg.period as u32,
+            signal: msg.signal.clone(),
+            timeframe: msg.timeframe.clone(),
+            processed_at: msg.processed_at.clone(),
+            schema_version: msg.schema_version,
+            quote_currency: msg.quote_currency.clone(),
+            avg_gain: msg.avg_gain,
+            avg_loss: msg.avg_loss,
+        }
+    }
+}
+
+pub fn decode_trade(payload: &[u8]) -> Result<TradeMessage> {
+    Trade::decode(payload)
+        .map(TradeMessage::from)
+        .context("Invalid Protobuf trade")
+}
+
+pub fn encode_rsi(msg: &RsiMessage) -> Vec<u8> {
+    Rsi::from(msg).encode_to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade() -> TradeMessage {
+        TradeMessage {
+            token_address: "FCuk4XWLR6fAJFTcQoMrm3KeywSt2X6wK4Ufh4Xjpump".to_string(),
+            price_in_sol: 0.000_000_042_5,
+            block_time: "1718000000".to_string(),
+            transaction_signature: "5xSig".to_string(),
+            is_buy: true,
+            amount_in_sol: 1.25,
+            processed_timestamp: "2024-06-10T06:13:20+00:00".to_string(),
+            quote_currency: "USDC".to_string(),
+        }
+    }
+
+    fn rsi(avg_gain: Option<f64>, avg_loss: Option<f64>) -> RsiMessage {
+        RsiMessage {
+            schema_version: 4,
+            token_address: "FCuk4XWLR6fAJFTcQoMrm3KeywSt2X6wK4Ufh4Xjpump".to_string(),
+            quote_currency: "SOL".to_string(),
+            rsi_value: 71.5,
+            current_price: 0.000_000_042_5,
+            timestamp: "2024-06-10T06:13:20+00:00".to_string(),
+            processed_at: "2024-06-10T06:13:21+00:00".to_string(),
+            period: 14,
+            signal: "overbought".to_string(),
+            timeframe: "1m".to_string(),
+            avg_gain,
+            avg_loss,
+        }
+    }
+
+    #[test]
+    fn trade_round_trips() {
+        let original = trade();
+        let decoded = decode_trade(&Trade::from(&original).encode_to_vec()).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&original).unwrap());
+    }
+
+    #[test]
+    fn unset_trade_fields_decode_as_defaults() {
+        let trade = Trade {
+            token_address: "token".to_string(),
+            price_in_sol: 1.0,
+            block_time: "1718000000".to_string(),
+            ..Trade::default()
+        };
+        let decoded = decode_trade(&trade.encode_to_vec()).unwrap();
+        assert_eq!(decoded.transaction_signature, "");
+        assert!(!decoded.is_buy);
+        assert_eq!(decoded.amount_in_sol, 0.0);
+        assert_eq!(decoded.processed_timestamp, "");
+        assert_eq!(decoded.quote(), "SOL");
+    }
+
+    #[test]
+    fn json_trade_survives_protobuf() {
+        let json = serde_json::json!({
+            "token_address": "FCuk4XWLR6fAJFTcQoMrm3KeywSt2X6wK4Ufh4Xjpump",
+            "price_in_sol": 0.0000000425,
+            "block_time": "2024-06-10T06:13:20Z",
+            "transaction_signature": "5xSig",
+            "is_buy": false,
+            "amount_in_sol": 0.1,
+            "processed_timestamp": "",
+            "quote_currency": "",
+        });
+        let trade: TradeMessage = serde_json::from_value(json.clone()).unwrap();
+        let decoded = decode_trade(&Trade::from(&trade).encode_to_vec()).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), json);
+    }
+
+    #[test]
+    fn rsi_round_trips_with_and_without_averages() {
+        for msg in [rsi(Some(0.0012), Some(0.0004)), rsi(None, None)] {
+            let decoded = Rsi::decode(encode_rsi(&msg).as_slice()).unwrap();
+            assert_eq!(decoded, Rsi::from(&msg));
+            assert_eq!(decoded.avg_gain, msg.avg_gain);
+            assert_eq!(decoded.avg_loss, msg.avg_loss);
+            assert_eq!(decoded.period as usize, msg.period);
+            assert_eq!(decoded.quote_currency, msg.quote_currency);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use log::{debug, warn};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+use crate::config::CorrelationConfig;
+use crate::TradeMessage;
+
+/// Key of every matrix message, so they all land on one partition in order
+const KEY: &str = "correlation";
+
+/// Pairwise return correlations, published every `publish_interval_secs`
+#[derive(Debug, Serialize)]
+pub struct CorrelationMessage {
+    pub timestamp: String,
+    pub sample_secs: u64,
+    pub window: usize,
+    pub tokens: Vec<String>,
+    /// Pearson correlation of token i's and token j's returns at
+    /// `matrix[i][j]`; None while the pair shares too few returns
+    pub matrix: Vec<Vec<Option<f64>>>,
+}
+
+/// Latest prices of the correlated tokens, fed by every calculator
+pub struct Correlations {
+    config: CorrelationConfig,
+    prices: Mutex<HashMap<String, f64>>,
+}
+
+impl Correlations {
+    pub fn new(config: &CorrelationConfig) -> Self {
+        Self {
+            config: config.clone(),
+            prices: Mutex::default(),
+        }
+    }
+
+    /// Tokens are matched by series key, so "<token_address>:USDC" in
+    /// `tokens` correlates a token's USDC series
+    pub fn observe_trade(&self, trade: &TradeMessage) {
+        let key = trade.series_key();
+        if !self.config.tokens.iter().any(|token| *token == key) {
+            return;
+        }
+        let mut prices = self.prices.lock().unwrap_or_else(|e| e.into_inner());
+        prices.insert(key.into_owned(), trade.price_in_sol);
+    }
+
+    fn price(&self, token_address: &str) -> Option<f64> {
+        let prices = self.prices.lock().unwrap_or_else(|e| e.into_inner());
+        prices.get(token_address).copied()
+    }
+}
+
+/// Sampled log returns per token, aligned by sample so pairs compare the
+/// same intervals
+struct Returns {
+    // Price at the previous sample, per token in config order
+    last: Vec<Option<f64>>,
+    // Newest last; None where a token had no return for that sample
+    returns: Vec<VecDeque<Option<f64>>>,
+    window: usize,
+}
+
+impl Returns {
+    fn new(tokens: usize, window: usize) -> Self {
+        Self {
+            last: vec![None; tokens],
+            returns: vec![VecDeque::with_capacity(window); tokens],
+            window,
+        }
+    }
+
+    fn sample(&mut self, prices: Vec<Option<f64>>) {
+        for ((last, returns), price) in self.last.iter_mut().zip(&mut self.returns).zip(prices) {
+            let ret = match (*last, price) {
+                (Some(previous), Some(price)) if previous > 0.0 && price > 0.0 => Some((price / previous).ln()),
+                _ => None,
+            };
+            if returns.len() == self.window {
+                returns.pop_front();
+            }
+            returns.push_back(ret);
+            if price.is_some() {
+                *last = price;
+            }
+        }
+    }
+
+    /// Correlation of tokens `a` and `b` over the samples where both have a
+    /// return
+    fn correlation(&self, a: usize, b: usize, min_samples: usize) -> Option<f64> {
+        let pairs: Vec<(f64, f64)> = self.returns[a]
+            .iter()
+            .zip(&self.returns[b])
+            .filter_map(|(&x, &y)| Some((x?, y?)))
+            .collect();
+        if pairs.len() < min_samples {
+            return None;
+        }
+        // A token is perfectly correlated with itself, even when flat
+        if a == b {
+            return Some(1.0);
+        }
+        pearson(&pairs)
+    }
+}
+
+/// Pearson correlation; None when either series is flat
+fn pearson(pairs: &[(f64, f64)]) -> Option<f64> {
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    Some((cov / (var_x * var_y).sqrt()).clamp(-1.0, 1.0))
+}
+
+/// Sample prices every `sample_secs` and publish the matrix every
+/// `publish_interval_secs` until the process exits
+pub async fn publish(correlations: Arc<Correlations>, producer: FutureProducer) {
+    let config = &correlations.config;
+    let mut returns = Returns::new(config.tokens.len(), config.window);
+
+    let mut sampler = tokio::time::interval(Duration::from_secs(config.sample_secs));
+    sampler.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut publisher = tokio::time::interval(Duration::from_secs(config.publish_interval_secs));
+    publisher.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick is immediate, before any return exists
+    publisher.tick().await;
+
+    loop {
+        tokio::select! {
+            _ = sampler.tick() => {
+                let prices = config.tokens.iter().map(|token| correlations.price(token)).collect();
+                returns.sample(prices);
+                continue;
+            }
+            _ = publisher.tick() => {}
+        }
+
+        let n = config.tokens.len();
+        let matrix = (0..n)
+            .map(|a| {
+                (0..n)
+                    .map(|b| returns.correlation(a, b, config.min_samples))
+                    .collect()
+            })
+            .collect();
+        let message = CorrelationMessage {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            sample_secs: config.sample_secs,
+            window: config.window,
+            tokens: config.tokens.clone(),
+            matrix,
+        };
+
+        let payload = match serde_json::to_string(&message) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("⚠️  Failed to serialize correlation matrix: {}", e);
+                continue;
+            }
+        };
+        let record = FutureRecord::to(&config.topic).key(KEY).payload(&payload);
+        match producer.send(record, Duration::from_secs(0)).await {
+            Ok(_) => debug!("🔗 Published {}x{} correlation matrix", n, n),
+            Err((e, _)) => warn!("⚠️  Failed to publish correlation matrix to '{}': {}", config.topic, e),
+        }
+    }
+}
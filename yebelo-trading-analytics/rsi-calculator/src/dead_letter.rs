@@ -0,0 +1,41 @@
+use anyhow::{anyhow, Result};
+use rdkafka::message::{Header, Message, OwnedHeaders};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use std::time::Duration;
+
+/// Forward a trade message that could not be processed to the dead-letter topic.
+///
+/// The original key and payload are kept byte-for-byte so the record can be
+/// replayed onto the input topic as-is; the failure reason and source
+/// coordinates travel in `dlq.*` headers. `payload` replaces the original
+/// one, for a single trade out of a batched message.
+pub async fn forward<M: Message>(
+    producer: &FutureProducer,
+    topic: &str,
+    message: &M,
+    payload: Option<&[u8]>,
+    error: &str,
+) -> Result<()> {
+    let partition = message.partition().to_string();
+    let offset = message.offset().to_string();
+    let failed_at = chrono::Utc::now().to_rfc3339();
+
+    let headers = OwnedHeaders::new()
+        .insert(Header { key: "dlq.error", value: Some(error) })
+        .insert(Header { key: "dlq.source.topic", value: Some(message.topic()) })
+        .insert(Header { key: "dlq.source.partition", value: Some(&partition) })
+        .insert(Header { key: "dlq.source.offset", value: Some(&offset) })
+        .insert(Header { key: "dlq.failed_at", value: Some(&failed_at) });
+
+    let payload = payload.or(message.payload()).unwrap_or_default();
+    let mut record = FutureRecord::to(topic).payload(payload).headers(headers);
+    if let Some(key) = message.key() {
+        record = record.key(key);
+    }
+
+    producer
+        .send(record, Duration::from_secs(0))
+        .await
+        .map(|_| ())
+        .map_err(|(e, _)| anyhow!("Failed to publish to dead-letter topic '{}': {}", topic, e))
+}
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+use crate::config::DedupConfig;
+
+/// Recently seen transaction signatures for one token.
+///
+/// Bounded LRU with a TTL measured in trade time (`block_time`), so it
+/// behaves the same on replays and survives state checkpoints.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DedupCache {
+    capacity: usize,
+    ttl_secs: i64,
+    // Signature -> last time seen
+    seen: HashMap<String, i64>,
+    // Recency order; entries superseded by a later sighting are skipped lazily
+    order: VecDeque<(String, i64)>,
+    latest: i64,
+}
+
+impl DedupCache {
+    pub fn new(config: &DedupConfig) -> Self {
+        Self {
+            capacity: config.capacity,
+            ttl_secs: config.ttl_secs,
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            latest: i64::MIN,
+        }
+    }
+
+    /// Record `signature` at `time`; returns `true` if it was already seen
+    /// within the TTL. Trades without a signature are never duplicates.
+    pub fn is_duplicate(&mut self, signature: &str, time: i64) -> bool {
+        if signature.is_empty() {
+            return false;
+        }
+        self.latest = self.latest.max(time);
+        self.expire();
+
+        let duplicate = match self.seen.get_mut(signature) {
+            // Nothing to refresh for a redelivery that is no newer
+            Some(seen) if *seen >= time => return true,
+            Some(seen) => {
+                *seen = time;
+                true
+            }
+            None => {
+                self.seen.insert(signature.to_string(), time);
+                false
+            }
+        };
+        self.order.push_back((signature.to_string(), time));
+
+        while self.seen.len() > self.capacity {
+            self.pop_oldest();
+        }
+        // Refreshed signatures leave their earlier entries behind; drop
+        // those before they outnumber the live ones
+        if self.order.len() > 2 * self.capacity.max(1) {
+            let seen = &self.seen;
+            self.order.retain(|(signature, time)| seen.get(signature) == Some(time));
+        }
+
+        duplicate
+    }
+
+    /// Drop entries older than the TTL
+    fn expire(&mut self) {
+        let cutoff = self.latest.saturating_sub(self.ttl_secs);
+        while self.order.front().is_some_and(|(_, time)| *time < cutoff) {
+            self.pop_oldest();
+        }
+    }
+
+    fn pop_oldest(&mut self) {
+        if let Some((signature, time)) = self.order.pop_front() {
+            if self.seen.get(&signature) == Some(&time) {
+                self.seen.remove(&signature);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(capacity: usize, ttl_secs: i64) -> DedupCache {
+        DedupCache::new(&DedupConfig {
+            enabled: true,
+            capacity,
+            ttl_secs,
+        })
+    }
+
+    #[test]
+    fn redelivery_within_ttl_is_a_duplicate() {
+        let mut cache = cache(10, 60);
+        assert!(!cache.is_duplicate("a", 100));
+        assert!(cache.is_duplicate("a", 100));
+        assert!(cache.is_duplicate("a", 150));
+        assert!(!cache.is_duplicate("b", 150));
+    }
+
+    #[test]
+    fn signatures_expire_after_the_ttl() {
+        let mut cache = cache(10, 60);
+        assert!(!cache.is_duplicate("a", 100));
+        assert!(!cache.is_duplicate("b", 161));
+        assert!(!cache.is_duplicate("a", 161));
+    }
+
+    #[test]
+    fn oldest_signatures_make_room_at_capacity() {
+        let mut cache = cache(2, 600);
+        for signature in ["a", "b", "c"] {
+            assert!(!cache.is_duplicate(signature, 100));
+        }
+        assert_eq!(cache.seen.len(), 2);
+        assert!(cache.is_duplicate("c", 100));
+        assert!(!cache.is_duplicate("a", 100));
+    }
+
+    #[test]
+    fn redeliveries_do_not_grow_the_cache() {
+        let mut cache = cache(4, 600);
+        for time in 0..100 {
+            assert_eq!(cache.is_duplicate("a", 100 + time % 10), time > 0);
+        }
+        assert_eq!(cache.seen.len(), 1);
+        assert!(cache.order.len() <= 8);
+    }
+
+    #[test]
+    fn unsigned_trades_are_never_duplicates() {
+        let mut cache = cache(10, 60);
+        assert!(!cache.is_duplicate("", 100));
+        assert!(!cache.is_duplicate("", 100));
+        assert!(cache.seen.is_empty());
+    }
+}
@@ -0,0 +1,374 @@
+use log::{error, info, warn};
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::message::{Header, Message, OwnedHeaders, OwnedMessage, ToBytes};
+use rdkafka::producer::{DeliveryFuture, FutureProducer, FutureRecord};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::config::RetryConfig;
+
+/// Pause between send attempts while the producer queue is full and none of
+/// its messages are ours to wait on
+const QUEUE_FULL_BACKOFF: Duration = Duration::from_millis(100);
+
+/// A queued send awaiting its acknowledgement
+struct Pending {
+    kind: &'static str,
+    token_address: String,
+    state: Attempt,
+    // Sends of this output so far, including the first
+    attempts: u32,
+}
+
+/// Where a pending output stands; kept in the entry rather than across an
+/// await so a cancelled `next` picks up where it left off
+enum Attempt {
+    /// Queued on the producer, awaiting the broker's acknowledgement
+    Queued(DeliveryFuture),
+    /// Rejected by the broker, re-sent once `until` passes
+    Backoff { message: OwnedMessage, until: Instant },
+    /// Retries exhausted, queued on the failure topic
+    Parking(DeliveryFuture),
+}
+
+/// Outputs of one consumed trade, resolved together
+struct Batch<T> {
+    source: T,
+    // Queued sends not yet acknowledged, oldest first
+    pending: VecDeque<Pending>,
+    published: u64,
+    // Token of every output that never reached its topic
+    failed: Vec<String>,
+    delivered: bool,
+}
+
+/// A consumed trade whose outputs have all been acknowledged or failed
+pub struct Delivered<T> {
+    pub source: T,
+    pub published: u64,
+    /// Token of every output that never reached its topic, whether parked
+    /// or lost
+    pub failed: Vec<String>,
+    /// Whether every output reached Kafka
+    pub delivered: bool,
+}
+
+/// Publishes outputs without awaiting each broker acknowledgement
+///
+/// Sends are queued on the producer right away and their delivery futures
+/// kept per consumed trade, oldest first. A trade is only handed back once
+/// all of its outputs are acknowledged, so offsets are still stored strictly
+/// after delivery while up to `max_in_flight` messages are pipelined.
+///
+/// A full producer queue never drops an output: the send is held and retried
+/// once queued messages are acknowledged, with the backpressure flag raised
+/// so the consumer can pause in the meantime. Outputs the broker rejects are
+/// re-sent with exponential backoff, then parked on the failure topic.
+pub struct DeliveryPipeline<T> {
+    producer: FutureProducer,
+    retry: RetryConfig,
+    batches: VecDeque<Batch<T>>,
+    // Trades resolved while making room for new sends
+    ready: VecDeque<Delivered<T>>,
+    in_flight: usize,
+    max_in_flight: usize,
+    backpressure: Arc<AtomicBool>,
+}
+
+impl<T> DeliveryPipeline<T> {
+    pub fn new(producer: &FutureProducer, max_in_flight: usize, retry: &RetryConfig) -> Self {
+        Self {
+            producer: producer.clone(),
+            retry: retry.clone(),
+            batches: VecDeque::new(),
+            ready: VecDeque::new(),
+            in_flight: 0,
+            max_in_flight,
+            backpressure: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Set while a send is held because the producer queue is full
+    pub fn backpressure(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.backpressure)
+    }
+
+    /// Whether any trade is still waiting to be handed back
+    pub fn is_empty(&self) -> bool {
+        self.batches.is_empty() && self.ready.is_empty()
+    }
+
+    /// Outputs sent but not yet acknowledged
+    pub fn in_flight(&self) -> usize {
+        self.in_flight
+    }
+
+    /// Start collecting the outputs of a newly consumed trade
+    pub fn start(&mut self, source: T) {
+        self.batches.push_back(Batch {
+            source,
+            pending: VecDeque::new(),
+            published: 0,
+            failed: Vec::new(),
+            delivered: true,
+        });
+    }
+
+    /// Queue a record for the current trade, first waiting for the oldest
+    /// deliveries while `max_in_flight` are outstanding
+    pub async fn send<K, P>(&mut self, record: FutureRecord<'_, K, P>, kind: &'static str, token_address: &str)
+    where
+        K: ToBytes + ?Sized,
+        P: ToBytes + ?Sized,
+    {
+        while self.in_flight >= self.max_in_flight {
+            // The current trade's own sends may be the oldest ones left
+            let resolved = self.resolve_front(self.batches.len() > 1).await;
+            if let Some(resolved) = resolved {
+                self.ready.push_back(resolved);
+            }
+        }
+
+        let mut record = record;
+        let mut held = false;
+        loop {
+            match self.producer.send_result(record) {
+                Ok(delivery) => {
+                    let batch = self.batches.back_mut().expect("send is preceded by start");
+                    batch.pending.push_back(Pending {
+                        kind,
+                        token_address: token_address.to_string(),
+                        state: Attempt::Queued(delivery),
+                        attempts: 1,
+                    });
+                    self.in_flight += 1;
+                    break;
+                }
+                Err((e, returned)) if e.rdkafka_error_code() == Some(RDKafkaErrorCode::QueueFull) => {
+                    if !held {
+                        warn!("⏸️  Producer queue full, holding {} until it drains", kind);
+                        self.backpressure.store(true, Ordering::Relaxed);
+                        held = true;
+                    }
+                    record = returned;
+
+                    // Our own oldest deliveries free queue space soonest
+                    if self.in_flight > 0 {
+                        let resolved = self.resolve_front(self.batches.len() > 1).await;
+                        if let Some(resolved) = resolved {
+                            self.ready.push_back(resolved);
+                        }
+                    } else {
+                        tokio::time::sleep(QUEUE_FULL_BACKOFF).await;
+                    }
+                }
+                Err((e, _)) => {
+                    error!("❌ Failed to queue {}: {}", kind, e);
+                    let batch = self.batches.back_mut().expect("send is preceded by start");
+                    batch.failed.push(token_address.to_string());
+                    batch.delivered = false;
+                    break;
+                }
+            }
+        }
+
+        if held {
+            info!("▶️  Producer queue drained, publishing resumed");
+            self.backpressure.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Mark the current trade as failed, e.g. when an output could not be encoded
+    pub fn fail(&mut self) {
+        if let Some(batch) = self.batches.back_mut() {
+            batch.delivered = false;
+        }
+    }
+
+    /// The oldest trade once all of its outputs are acknowledged; pending
+    /// forever when nothing is in flight
+    ///
+    /// Cancel-safe: acknowledgements, retry deadlines and parked sends are
+    /// kept in the pending entries, so a later call resumes where it left off.
+    pub async fn next(&mut self) -> Delivered<T> {
+        if let Some(resolved) = self.ready.pop_front() {
+            return resolved;
+        }
+        match self.resolve_front(true).await {
+            Some(resolved) => resolved,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Await the oldest trade's deliveries; hands it back when `complete`
+    /// (no more outputs will be added), otherwise only frees its slots
+    async fn resolve_front(&mut self, complete: bool) -> Option<Delivered<T>> {
+        let batch = self.batches.front_mut()?;
+        while let Some(pending) = batch.pending.front_mut() {
+            match &mut pending.state {
+                Attempt::Queued(delivery) => match delivery.await {
+                    Ok(Ok(_)) => batch.published += 1,
+                    Ok(Err((e, message))) if pending.attempts < self.retry.max_attempts => {
+                        let backoff = self.retry.backoff(pending.attempts);
+                        warn!(
+                            "⚠️  Failed to publish {} (attempt {}/{}): {}, retrying in {:?}",
+                            pending.kind,
+                            pending.attempts,
+                            self.retry.max_attempts,
+                            e,
+                            backoff
+                        );
+                        pending.state = Attempt::Backoff {
+                            message,
+                            until: Instant::now() + backoff,
+                        };
+                        continue;
+                    }
+                    Ok(Err((e, message))) => {
+                        error!("❌ Failed to publish {} after {} attempts: {}", pending.kind, pending.attempts, e);
+                        batch.failed.push(pending.token_address.clone());
+                        match park(&self.producer, self.retry.failure_topic.as_deref(), &message, &e) {
+                            Some(delivery) => {
+                                pending.state = Attempt::Parking(delivery);
+                                continue;
+                            }
+                            None => batch.delivered = false,
+                        }
+                    }
+                    Err(_) => {
+                        error!("❌ Delivery of {} was cancelled", pending.kind);
+                        batch.failed.push(pending.token_address.clone());
+                        batch.delivered = false;
+                    }
+                },
+                Attempt::Backoff { message, until } => {
+                    tokio::time::sleep_until(*until).await;
+
+                    pending.attempts += 1;
+                    match resend(&self.producer, message, message.topic(), None) {
+                        // Await the new attempt in the same slot
+                        Ok(delivery) => {
+                            pending.state = Attempt::Queued(delivery);
+                            continue;
+                        }
+                        Err(e) => {
+                            error!("❌ Failed to queue retry of {}: {}", pending.kind, e);
+                            batch.failed.push(pending.token_address.clone());
+                            batch.delivered = false;
+                        }
+                    }
+                }
+                Attempt::Parking(delivery) => {
+                    let failure_topic = self.retry.failure_topic.as_deref().unwrap_or_default();
+                    if matches!(delivery.await, Ok(Ok(_))) {
+                        warn!("📦 Parked undeliverable output on '{}'", failure_topic);
+                    } else {
+                        error!("❌ Failed to park undeliverable output on '{}'", failure_topic);
+                        batch.delivered = false;
+                    }
+                }
+            }
+
+            batch.pending.pop_front();
+            self.in_flight -= 1;
+        }
+
+        if !complete {
+            return None;
+        }
+        let batch = self.batches.pop_front()?;
+        Some(Delivered {
+            source: batch.source,
+            published: batch.published,
+            failed: batch.failed,
+            delivered: batch.delivered,
+        })
+    }
+}
+
+/// Queue a rejected message again on `topic`, keeping its key and headers
+fn resend(
+    producer: &FutureProducer,
+    message: &OwnedMessage,
+    topic: &str,
+    headers: Option<OwnedHeaders>,
+) -> Result<DeliveryFuture, KafkaError> {
+    let mut record: FutureRecord<'_, [u8], [u8]> = FutureRecord::to(topic).payload(message.payload().unwrap_or_default());
+    if let Some(key) = message.key() {
+        record = record.key(key);
+    }
+    if let Some(headers) = headers.or_else(|| message.headers().cloned()) {
+        record = record.headers(headers);
+    }
+    producer.send_result(record).map_err(|(e, _)| e)
+}
+
+/// Queue an output whose retries are exhausted on the failure topic, with
+/// the error and original topic in `failure.*` headers; None when there is
+/// no failure topic or it could not be queued either
+fn park(
+    producer: &FutureProducer,
+    failure_topic: Option<&str>,
+    message: &OwnedMessage,
+    error: &KafkaError,
+) -> Option<DeliveryFuture> {
+    let failure_topic = failure_topic?;
+
+    let reason = error.to_string();
+    let headers = message
+        .headers()
+        .cloned()
+        .unwrap_or_default()
+        .insert(Header { key: "failure.error", value: Some(&reason) })
+        .insert(Header { key: "failure.topic", value: Some(message.topic()) });
+
+    match resend(producer, message, failure_topic, Some(headers)) {
+        Ok(delivery) => Some(delivery),
+        Err(e) => {
+            error!("❌ Failed to park undeliverable output on '{}': {}", failure_topic, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rdkafka::ClientConfig;
+
+    // Creating a producer does not connect, so no broker is needed while
+    // nothing is sent
+    fn pipeline() -> DeliveryPipeline<u64> {
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", "localhost:9092")
+            .create()
+            .expect("producer");
+        DeliveryPipeline::new(&producer, 4, &RetryConfig::default())
+    }
+
+    #[tokio::test]
+    async fn trades_are_handed_back_in_consumed_order() {
+        let mut pipeline = pipeline();
+        pipeline.start(5);
+        pipeline.start(6);
+
+        assert_eq!(pipeline.next().await.source, 5);
+        assert_eq!(pipeline.next().await.source, 6);
+        assert!(pipeline.is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_trades_are_not_delivered() {
+        let mut pipeline = pipeline();
+        pipeline.start(5);
+        pipeline.fail();
+        pipeline.start(6);
+
+        let failed = pipeline.next().await;
+        assert_eq!((failed.source, failed.published, failed.delivered), (5, 0, false));
+        assert!(pipeline.next().await.delivered);
+    }
+}
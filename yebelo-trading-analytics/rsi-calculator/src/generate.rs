@@ -0,0 +1,96 @@
+use anyhow::{Context, Result};
+use log::{info, warn};
+use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
+use crate::generator::TradeGenerator;
+use std::time::{Duration, Instant};
+
+use crate::cli::GenerateArgs;
+use crate::config::{Config, MessageFormat};
+use crate::service::{create_producer, shutdown_signal};
+
+/// Time between batches of generated trades
+const TICK: Duration = Duration::from_millis(100);
+
+/// Longest the producer may take to deliver what is still queued on exit
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Publish synthetic trades to the input topic, keyed by token, until the
+/// trade count or duration is reached or the process is interrupted
+///
+/// Trades are JSON in the shape the ingestion script publishes, stamped with
+/// the current time, so a running calculator and the dashboard treat them as
+/// live. With `--print` they go to stdout as JSON lines instead.
+pub async fn run(config: Config, args: &GenerateArgs) -> Result<()> {
+    if !args.print && config.kafka.format != MessageFormat::Json {
+        anyhow::bail!("generate only publishes JSON trades; set kafka.format = \"json\" or use --print");
+    }
+
+    let mut generator = TradeGenerator::new(&config.generator);
+    let producer = if args.print { None } else { Some(create_producer(&config.kafka)?) };
+    info!(
+        "🎲 Generating {:.0} trades/s for {} tokens into '{}'",
+        config.generator.rate_per_sec,
+        generator.tokens().count(),
+        if args.print { "stdout" } else { &config.kafka.input_topic }
+    );
+
+    let started = Instant::now();
+    let deadline = args.duration.map(|secs| started + Duration::from_secs(secs));
+    let mut ticker = tokio::time::interval(TICK);
+    let mut last = Instant::now();
+    let mut sent = 0u64;
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    'generate: loop {
+        tokio::select! {
+            _ = &mut shutdown => {
+                info!("🛑 Interrupted");
+                break;
+            }
+            _ = ticker.tick() => {}
+        }
+
+        let elapsed = last.elapsed().as_secs_f64();
+        last = Instant::now();
+        let now = chrono::Utc::now().timestamp_millis() as f64 / 1000.0;
+
+        for trade in generator.tick(now, elapsed) {
+            if args.count.is_some_and(|count| sent >= count) {
+                break 'generate;
+            }
+            let payload = serde_json::to_string(&trade).context("Failed to serialize trade")?;
+            match &producer {
+                Some(producer) => publish(producer, &config.kafka.input_topic, &trade.token_address, &payload).await,
+                None => println!("{}", payload),
+            }
+            sent += 1;
+        }
+
+        if args.count.is_some_and(|count| sent >= count) || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            break;
+        }
+    }
+
+    if let Some(producer) = &producer {
+        producer.flush(FLUSH_TIMEOUT).context("Failed to flush generated trades")?;
+    }
+    info!(
+        "✅ Generated {} trades in {:.1}s",
+        sent,
+        started.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+/// Queue a trade without waiting for its acknowledgement; the final flush
+/// delivers whatever is still queued
+async fn publish(producer: &FutureProducer, topic: &str, key: &str, payload: &str) {
+    let record = FutureRecord::to(topic).key(key).payload(payload);
+    if let Err((e, _)) = producer.send_result(record) {
+        warn!("⚠️  Dropped generated trade: {}", e);
+        // Mostly a full queue; give it a moment to drain
+        tokio::time::sleep(TICK).await;
+    }
+}
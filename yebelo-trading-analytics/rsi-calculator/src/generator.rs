@@ -0,0 +1,144 @@
+//! Synthetic pump.fun trades for load and integration testing
+//!
+//! Each token's price follows its own random walk: buys push it up and sells
+//! down by a normally distributed step. Now and then a token bursts, trading
+//! `burst_multiplier` times faster and harder, mostly on the buy side, like a
+//! pump in progress.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::config::GeneratorConfig;
+use crate::TradeMessage;
+
+/// Alphabet of Solana addresses and signatures
+const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Share of trades that are buys, outside and during a burst
+const BUY_SHARE: f64 = 0.5;
+const BURST_BUY_SHARE: f64 = 0.65;
+
+/// One generated token and where its walk is at
+struct Token {
+    address: String,
+    price: f64,
+    /// Fractional trades owed from earlier ticks
+    owed: f64,
+    /// Unix seconds the current burst ends at
+    burst_until: f64,
+}
+
+/// Produces trades for a fixed set of tokens, tick by tick
+pub struct TradeGenerator {
+    config: GeneratorConfig,
+    rng: StdRng,
+    tokens: Vec<Token>,
+}
+
+impl TradeGenerator {
+    /// Tokens come from `token_addresses`, or are made up when it is empty;
+    /// `seed` makes the run reproducible
+    pub fn new(config: &GeneratorConfig) -> Self {
+        let mut rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        let addresses = if config.token_addresses.is_empty() {
+            (0..config.tokens)
+                .map(|_| format!("{}pump", base58(&mut rng, 40)))
+                .collect()
+        } else {
+            config.token_addresses.clone()
+        };
+        let tokens = addresses
+            .into_iter()
+            .map(|address| Token {
+                address,
+                price: config.start_price_sol,
+                owed: 0.0,
+                burst_until: f64::MIN,
+            })
+            .collect();
+
+        Self {
+            config: config.clone(),
+            rng,
+            tokens,
+        }
+    }
+
+    /// Addresses of the tokens being traded
+    pub fn tokens(&self) -> impl Iterator<Item = &str> {
+        self.tokens.iter().map(|token| token.address.as_str())
+    }
+
+    /// Trades that happened in the `elapsed` seconds up to `now` (Unix
+    /// seconds), in time order
+    pub fn tick(&mut self, now: f64, elapsed: f64) -> Vec<TradeMessage> {
+        let base_rate = self.config.rate_per_sec / self.tokens.len() as f64;
+        // Time, token and whether it is bursting, for every trade due
+        let mut due = Vec::new();
+
+        for (index, token) in self.tokens.iter_mut().enumerate() {
+            if token.burst_until < now && self.rng.gen_bool((self.config.burst_probability * elapsed).min(1.0)) {
+                token.burst_until = now + self.config.burst_secs as f64;
+            }
+            let bursting = token.burst_until >= now;
+            let factor = if bursting { self.config.burst_multiplier } else { 1.0 };
+
+            token.owed += base_rate * factor * elapsed;
+            let count = token.owed.floor();
+            token.owed -= count;
+
+            for _ in 0..count as usize {
+                due.push((now - elapsed * self.rng.gen::<f64>(), index, bursting));
+            }
+        }
+
+        // Walk each price in time order, not in the order times were drawn
+        due.sort_by(|a, b| a.0.total_cmp(&b.0));
+        due.into_iter()
+            .map(|(time, index, bursting)| self.trade(index, time, bursting))
+            .collect()
+    }
+
+    /// Step token `index` by one trade at `time`
+    fn trade(&mut self, index: usize, time: f64, bursting: bool) -> TradeMessage {
+        let (buy_share, factor) = if bursting {
+            (BURST_BUY_SHARE, self.config.burst_multiplier)
+        } else {
+            (BUY_SHARE, 1.0)
+        };
+        let is_buy = self.rng.gen_bool(buy_share);
+
+        let step = normal(&mut self.rng).abs() * self.config.volatility_pct / 100.0 * factor;
+        let token = &mut self.tokens[index];
+        token.price *= if is_buy { step.exp() } else { (-step).exp() };
+
+        let amount = self.rng.gen_range(self.config.min_amount_sol..=self.config.max_amount_sol);
+        TradeMessage {
+            token_address: token.address.clone(),
+            price_in_sol: token.price,
+            block_time: (time as i64).to_string(),
+            transaction_signature: base58(&mut self.rng, 88),
+            is_buy,
+            amount_in_sol: amount,
+            processed_timestamp: String::new(),
+            quote_currency: String::new(),
+        }
+    }
+}
+
+/// Standard normal sample (Box-Muller)
+fn normal(rng: &mut StdRng) -> f64 {
+    let u1 = 1.0 - rng.gen::<f64>();
+    let u2 = rng.gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}
+
+fn base58(rng: &mut StdRng, len: usize) -> String {
+    (0..len)
+        .map(|_| BASE58[rng.gen_range(0..BASE58.len())] as char)
+        .collect()
+}
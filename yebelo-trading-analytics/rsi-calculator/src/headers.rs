@@ -0,0 +1,59 @@
+use rdkafka::message::{Header, Headers, Message, OwnedHeaders};
+
+use crate::config::HeadersConfig;
+
+/// Headers taken from a consumed trade, carried along to its outputs
+pub type Carried = Vec<(String, Vec<u8>)>;
+
+/// Builds the Kafka headers of published outputs
+///
+/// Every output is tagged with the topic its trade came from, followed by
+/// the configured headers copied from the trade and the static ones.
+#[derive(Clone)]
+pub struct OutputHeaders {
+    propagate: Vec<String>,
+    propagate_all: bool,
+    fixed: Vec<(String, String)>,
+}
+
+impl OutputHeaders {
+    pub fn new(config: &HeadersConfig) -> Self {
+        Self {
+            propagate: config.propagate.clone(),
+            propagate_all: config.propagate.iter().any(|name| name == "*"),
+            fixed: config.static_headers.clone().into_iter().collect(),
+        }
+    }
+
+    /// Take the headers an incoming trade passes on to its outputs
+    pub fn carry<M: Message>(&self, message: &M) -> Carried {
+        let mut carried = vec![("source_topic".to_string(), message.topic().as_bytes().to_vec())];
+        let Some(headers) = message.headers() else {
+            return carried;
+        };
+
+        for header in headers.iter() {
+            let wanted = self.propagate_all || self.propagate.iter().any(|name| name == header.key);
+            // Static headers replace propagated ones of the same name
+            let overridden = self.fixed.iter().any(|(name, _)| name == header.key);
+            if wanted && !overridden && header.key != "source_topic" {
+                if let Some(value) = header.value {
+                    carried.push((header.key.to_string(), value.to_vec()));
+                }
+            }
+        }
+        carried
+    }
+
+    /// Headers for one output of a trade
+    pub fn build(&self, carried: &Carried) -> OwnedHeaders {
+        let headers = carried
+            .iter()
+            .fold(OwnedHeaders::new_with_capacity(carried.len() + self.fixed.len()), |headers, (key, value)| {
+                headers.insert(Header { key: key.as_str(), value: Some(value.as_slice()) })
+            });
+        self.fixed
+            .iter()
+            .fold(headers, |headers, (key, value)| headers.insert(Header { key: key.as_str(), value: Some(value.as_str()) }))
+    }
+}
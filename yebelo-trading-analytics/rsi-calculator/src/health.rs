@@ -0,0 +1,179 @@
+use anyhow::{Context, Result};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::codec::adapter::Fallback;
+use crate::config::HealthConfig;
+use crate::lag::PartitionLag;
+
+/// Consumer status shared between the processing loop and the HTTP probes
+pub struct Health {
+    started: Instant,
+    stall_timeout: Duration,
+    kafka_connected: AtomicBool,
+    subscribed: AtomicBool,
+    // Milliseconds since `started`; 0 until the loop first polls
+    last_poll_ms: AtomicU64,
+    messages: AtomicU64,
+    // Latest consumer lag measurement, empty until lag monitoring reports
+    lag: Mutex<PartitionLag>,
+    // Fields lenient parsing fixed on trades that passed validation
+    fallbacks: Mutex<BTreeMap<(&'static str, Fallback), u64>>,
+}
+
+/// JSON body returned by both probes
+#[derive(Debug, Serialize)]
+struct HealthReport {
+    status: &'static str,
+    kafka_connected: bool,
+    subscribed: bool,
+    /// Seconds since the consumer loop last polled Kafka
+    last_poll_secs_ago: Option<f64>,
+    messages_processed: u64,
+    /// Messages behind the high watermarks of all assigned partitions
+    consumer_lag: Option<i64>,
+}
+
+impl Health {
+    pub fn new(stall_timeout: Duration) -> Self {
+        Self {
+            started: Instant::now(),
+            stall_timeout,
+            kafka_connected: AtomicBool::new(false),
+            subscribed: AtomicBool::new(false),
+            last_poll_ms: AtomicU64::new(0),
+            messages: AtomicU64::new(0),
+            lag: Mutex::new(PartitionLag::new()),
+            fallbacks: Mutex::default(),
+        }
+    }
+
+    pub fn set_subscribed(&self, subscribed: bool) {
+        self.subscribed.store(subscribed, Ordering::Relaxed);
+    }
+
+    pub fn set_kafka_connected(&self, connected: bool) {
+        self.kafka_connected.store(connected, Ordering::Relaxed);
+    }
+
+    /// Record that the consumer loop completed a poll, with or without a message
+    pub fn record_poll(&self) {
+        let elapsed = self.started.elapsed().as_millis().max(1) as u64;
+        self.last_poll_ms.store(elapsed, Ordering::Relaxed);
+    }
+
+    pub fn record_message(&self) {
+        self.messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count the fields lenient parsing fixed on a trade that is processed
+    pub fn record_fallbacks(&self, fallbacks: &[(&'static str, Fallback)]) {
+        if fallbacks.is_empty() {
+            return;
+        }
+        let mut counts = self.fallbacks.lock().unwrap();
+        for &fallback in fallbacks {
+            *counts.entry(fallback).or_default() += 1;
+        }
+    }
+
+    /// Fields lenient parsing fixed so far, by field
+    pub fn fallbacks(&self) -> BTreeMap<(&'static str, Fallback), u64> {
+        self.fallbacks.lock().unwrap().clone()
+    }
+
+    pub fn set_lag(&self, lag: PartitionLag) {
+        *self.lag.lock().unwrap() = lag;
+    }
+
+    fn total_lag(&self) -> Option<i64> {
+        let lag = self.lag.lock().unwrap();
+        (!lag.is_empty()).then(|| lag.values().sum())
+    }
+
+    fn last_poll_age(&self) -> Option<Duration> {
+        match self.last_poll_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some(self.started.elapsed().saturating_sub(Duration::from_millis(ms))),
+        }
+    }
+
+    /// The loop is alive unless it has polled before and then stopped
+    fn is_live(&self) -> bool {
+        self.last_poll_age().is_none_or(|age| age <= self.stall_timeout)
+    }
+
+    /// Ready once subscribed, connected and actively polling
+    fn is_ready(&self) -> bool {
+        self.subscribed.load(Ordering::Relaxed)
+            && self.kafka_connected.load(Ordering::Relaxed)
+            && self.last_poll_age().is_some_and(|age| age <= self.stall_timeout)
+    }
+
+    fn report(&self, ok: bool) -> (StatusCode, Json<HealthReport>) {
+        let status = if ok { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+
+        let report = HealthReport {
+            status: if ok { "ok" } else { "unavailable" },
+            kafka_connected: self.kafka_connected.load(Ordering::Relaxed),
+            subscribed: self.subscribed.load(Ordering::Relaxed),
+            last_poll_secs_ago: self.last_poll_age().map(|age| age.as_secs_f64()),
+            messages_processed: self.messages.load(Ordering::Relaxed),
+            consumer_lag: self.total_lag(),
+        };
+
+        (status, Json(report))
+    }
+}
+
+/// Liveness probe: fails only if the consumer loop has stalled
+async fn healthz(State(health): State<Arc<Health>>) -> (StatusCode, Json<HealthReport>) {
+    health.report(health.is_live())
+}
+
+/// Readiness probe: fails until the consumer is connected, subscribed and polling
+async fn readyz(State(health): State<Arc<Health>>) -> (StatusCode, Json<HealthReport>) {
+    health.report(health.is_ready())
+}
+
+/// Prometheus metrics: messages processed, per-partition consumer lag and
+/// leniently parsed fields
+async fn metrics(State(health): State<Arc<Health>>) -> String {
+    let mut body = String::new();
+    body.push_str("# TYPE rsi_calculator_messages_processed counter\n");
+    let _ = writeln!(body, "rsi_calculator_messages_processed {}", health.messages.load(Ordering::Relaxed));
+
+    body.push_str("# TYPE rsi_calculator_consumer_lag gauge\n");
+    for ((topic, partition), lag) in health.lag.lock().unwrap().iter() {
+        let _ = writeln!(body, "rsi_calculator_consumer_lag{{topic=\"{}\",partition=\"{}\"}} {}", topic, partition, lag);
+    }
+
+    body.push_str("# TYPE rsi_calculator_lenient_fields counter\n");
+    for ((field, fallback), count) in health.fallbacks.lock().unwrap().iter() {
+        let _ = writeln!(body, "rsi_calculator_lenient_fields{{field=\"{}\",fallback=\"{}\"}} {}", field, fallback, count);
+    }
+    body
+}
+
+/// Serve `/healthz`, `/readyz` and `/metrics` on the configured address
+pub async fn serve(config: &HealthConfig, health: Arc<Health>) -> Result<()> {
+    let app = Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/metrics", get(metrics))
+        .with_state(health);
+
+    let listener = tokio::net::TcpListener::bind(&config.bind_addr)
+        .await
+        .with_context(|| format!("Failed to bind health server to {}", config.bind_addr))?;
+
+    axum::serve(listener, app).await.context("Health server failed")
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Indicator, IndicatorOutput};
+use crate::candles::Candle;
+use crate::config::AdxConfig;
+
+/// ADX and directional indicators published for one token when a candle closes
+#[derive(Debug, Serialize)]
+pub struct AdxMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Trend strength regardless of direction (0-100)
+    pub adx: f64,
+    /// +DI: share of the range made by upward moves
+    pub plus_di: f64,
+    /// −DI: share of the range made by downward moves
+    pub minus_di: f64,
+    /// ADX at or above the trend threshold; RSI signals are less reliable
+    pub trending: bool,
+    pub close: f64,
+    /// Candle close time (RFC 3339)
+    pub timestamp: String,
+    pub period: usize,
+    pub interval_secs: i64,
+}
+
+/// Per-token ADX state using Wilder's smoothing throughout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Adx {
+    period: usize,
+    trend_threshold: f64,
+    // (high, low, close) of the previous candle
+    prev: Option<(f64, f64, f64)>,
+    // Wilder-smoothed sums of true range, +DM and −DM
+    tr: f64,
+    plus_dm: f64,
+    minus_dm: f64,
+    dm_count: usize,
+    // ADX seed: sum of the first `period` DX values
+    dx_sum: f64,
+    dx_count: usize,
+    adx: Option<f64>,
+}
+
+impl Adx {
+    pub fn new(config: &AdxConfig) -> Self {
+        Self {
+            period: config.period,
+            trend_threshold: config.trend_threshold,
+            prev: None,
+            tr: 0.0,
+            plus_dm: 0.0,
+            minus_dm: 0.0,
+            dm_count: 0,
+            dx_sum: 0.0,
+            dx_count: 0,
+            adx: None,
+        }
+    }
+
+    /// Feed a completed candle and build a message once ADX is seeded
+    /// (about two periods of candles)
+    pub fn update(&mut self, token_address: &str, candle: &Candle) -> Option<AdxMessage> {
+        let (prev_high, prev_low, prev_close) = self.prev.replace((candle.high, candle.low, candle.close))?;
+
+        let true_range = (candle.high - candle.low)
+            .max((candle.high - prev_close).abs())
+            .max((candle.low - prev_close).abs());
+        let up_move = candle.high - prev_high;
+        let down_move = prev_low - candle.low;
+        let plus_dm = if up_move > down_move && up_move > 0.0 { up_move } else { 0.0 };
+        let minus_dm = if down_move > up_move && down_move > 0.0 { down_move } else { 0.0 };
+
+        // Sums over the first `period` candles, then sum - sum / period + new
+        let period = self.period as f64;
+        if self.dm_count < self.period {
+            self.tr += true_range;
+            self.plus_dm += plus_dm;
+            self.minus_dm += minus_dm;
+            self.dm_count += 1;
+            if self.dm_count < self.period {
+                return None;
+            }
+        } else {
+            self.tr += true_range - self.tr / period;
+            self.plus_dm += plus_dm - self.plus_dm / period;
+            self.minus_dm += minus_dm - self.minus_dm / period;
+        }
+
+        let (plus_di, minus_di) = if self.tr > 0.0 {
+            (self.plus_dm / self.tr * 100.0, self.minus_dm / self.tr * 100.0)
+        } else {
+            (0.0, 0.0)
+        };
+        let di_sum = plus_di + minus_di;
+        let dx = if di_sum > 0.0 { (plus_di - minus_di).abs() / di_sum * 100.0 } else { 0.0 };
+
+        let adx = match self.adx {
+            Some(prev) => (prev * (period - 1.0) + dx) / period,
+            None => {
+                self.dx_sum += dx;
+                self.dx_count += 1;
+                if self.dx_count < self.period {
+                    return None;
+                }
+                self.dx_sum / period
+            }
+        };
+        self.adx = Some(adx);
+
+        Some(AdxMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            adx,
+            plus_di,
+            minus_di,
+            trending: adx >= self.trend_threshold,
+            close: candle.close,
+            timestamp: crate::format_unix_time(candle.end_time()),
+            period: self.period,
+            interval_secs: candle.interval_secs,
+        })
+    }
+}
+
+impl Indicator for Adx {
+    fn on_bar(&mut self, token_address: &str, candle: &Candle) -> Option<IndicatorOutput> {
+        self.update(token_address, candle).map(IndicatorOutput::Adx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::bars;
+
+    #[test]
+    fn adx_matches_known_values() {
+        let config = AdxConfig {
+            period: 5,
+            ..AdxConfig::default()
+        };
+        let mut adx = Adx::new(&config);
+        let messages: Vec<AdxMessage> = bars().iter().filter_map(|bar| adx.update("token", bar)).collect();
+
+        // 5 moves to seed the DIs and 5 DX values to seed the ADX
+        assert_eq!(messages.len(), 30 - 9);
+        let first = &messages[0];
+        assert!((first.adx - 82.7975).abs() < 1e-3, "ADX {}", first.adx);
+        assert!((first.plus_di - 37.8565).abs() < 1e-3, "+DI {}", first.plus_di);
+        assert!((first.minus_di - 3.8111).abs() < 1e-3, "-DI {}", first.minus_di);
+        assert!(first.trending);
+        let last = messages.last().unwrap();
+        assert!((last.adx - 86.6280).abs() < 1e-3, "ADX {}", last.adx);
+        assert!((last.plus_di - 0.9064).abs() < 1e-3, "+DI {}", last.plus_di);
+        assert!((last.minus_di - 39.4342).abs() < 1e-3, "-DI {}", last.minus_di);
+    }
+}
@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Indicator, IndicatorOutput, TradeInput};
+use crate::config::AnomalyConfig;
+
+/// An abnormal price or volume move, published as an alert event
+#[derive(Debug, Serialize)]
+pub struct AnomalyMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// "price_spike", "price_drop" or "volume_spike", after the more extreme
+    /// of the two scores
+    pub anomaly: String,
+    /// "warning" from `threshold`, "critical" from `critical_threshold`
+    pub severity: String,
+    /// Standard deviations of the trade's log return from its running mean;
+    /// absent for a token's first trade
+    pub price_zscore: Option<f64>,
+    /// Standard deviations of the trade's log size from its running mean
+    pub volume_zscore: Option<f64>,
+    pub previous_price: f64,
+    pub current_price: f64,
+    pub amount_in_sol: f64,
+    pub timestamp: String,
+}
+
+/// Exponentially weighted mean and variance of a series
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Ewma {
+    mean: f64,
+    variance: f64,
+    samples: usize,
+}
+
+impl Ewma {
+    /// How unusual `value` is against the series so far; None until `warmup`
+    /// samples are in or while the series is flat
+    fn zscore(&self, value: f64, warmup: usize) -> Option<f64> {
+        if self.samples < warmup || self.variance <= 0.0 {
+            return None;
+        }
+        Some((value - self.mean) / self.variance.sqrt())
+    }
+
+    fn push(&mut self, value: f64, alpha: f64) {
+        if self.samples == 0 {
+            self.mean = value;
+        } else {
+            let diff = value - self.mean;
+            self.mean += alpha * diff;
+            self.variance = (1.0 - alpha) * (self.variance + alpha * diff * diff);
+        }
+        self.samples += 1;
+    }
+}
+
+/// Per-token anomaly state: running statistics of trade returns and sizes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Anomaly {
+    alpha: f64,
+    warmup: usize,
+    threshold: f64,
+    critical_threshold: f64,
+    cooldown: usize,
+    prev_price: Option<f64>,
+    returns: Ewma,
+    sizes: Ewma,
+    // Trades left before another event may fire
+    quiet: usize,
+}
+
+impl Anomaly {
+    pub fn new(config: &AnomalyConfig) -> Self {
+        Self {
+            alpha: 2.0 / (config.span as f64 + 1.0),
+            warmup: config.warmup,
+            threshold: config.threshold,
+            critical_threshold: config.critical_threshold,
+            cooldown: config.cooldown,
+            prev_price: None,
+            returns: Ewma::default(),
+            sizes: Ewma::default(),
+            quiet: 0,
+        }
+    }
+
+    /// Feed the latest trade; returns a message when its return or size is
+    /// `threshold` or more standard deviations from normal
+    ///
+    /// Each trade is scored before it joins the statistics, so a spike does
+    /// not hide itself.
+    pub fn update(&mut self, token_address: &str, price: f64, amount: f64, timestamp: &str) -> Option<AnomalyMessage> {
+        let prev_price = self.prev_price.replace(price);
+        let ret = prev_price.filter(|&prev| prev > 0.0 && price > 0.0).map(|prev| (price / prev).ln());
+        let size = (amount > 0.0).then(|| amount.ln());
+
+        let price_zscore = ret.and_then(|ret| self.returns.zscore(ret, self.warmup));
+        let volume_zscore = size.and_then(|size| self.sizes.zscore(size, self.warmup));
+        if let Some(ret) = ret {
+            self.returns.push(ret, self.alpha);
+        }
+        if let Some(size) = size {
+            self.sizes.push(size, self.alpha);
+        }
+
+        if self.quiet > 0 {
+            self.quiet -= 1;
+            return None;
+        }
+        // Only unusually large trades matter, not unusually small ones
+        let price_score = price_zscore.map_or(0.0, f64::abs);
+        let volume_score = volume_zscore.unwrap_or(0.0);
+        let score = price_score.max(volume_score);
+        if score < self.threshold {
+            return None;
+        }
+        self.quiet = self.cooldown;
+
+        let anomaly = if volume_score > price_score {
+            "volume_spike"
+        } else if price_zscore.unwrap_or_default() > 0.0 {
+            "price_spike"
+        } else {
+            "price_drop"
+        };
+        let severity = if score >= self.critical_threshold {
+            "critical"
+        } else {
+            "warning"
+        };
+
+        Some(AnomalyMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            anomaly: anomaly.to_string(),
+            severity: severity.to_string(),
+            price_zscore,
+            volume_zscore,
+            previous_price: prev_price.unwrap_or(price),
+            current_price: price,
+            amount_in_sol: amount,
+            timestamp: timestamp.to_string(),
+        })
+    }
+}
+
+impl Indicator for Anomaly {
+    fn on_trade(&mut self, trade: &TradeInput) -> Option<IndicatorOutput> {
+        self.update(trade.token_address, trade.price, trade.amount, trade.timestamp).map(IndicatorOutput::Anomaly)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anomalies(trades: &[(f64, f64)]) -> Vec<AnomalyMessage> {
+        // alpha = 2 / (9 + 1) = 0.2
+        let config = AnomalyConfig {
+            span: 9,
+            warmup: 5,
+            threshold: 3.0,
+            critical_threshold: 6.0,
+            cooldown: 2,
+            ..AnomalyConfig::default()
+        };
+        let mut anomaly = Anomaly::new(&config);
+        trades.iter().filter_map(|&(price, amount)| anomaly.update("token", price, amount, "")).collect()
+    }
+
+    #[test]
+    fn price_spike_matches_known_zscore() {
+        let mut trades: Vec<(f64, f64)> = (0..9).map(|i| (if i % 2 == 0 { 100.0 } else { 101.0 }, 1.0)).collect();
+        // A second jump right after the first falls within the cooldown
+        trades.extend([(110.0, 1.0), (121.0, 1.0)]);
+        let messages = anomalies(&trades);
+
+        // EWMA mean and variance of the alternating ±1% log returns
+        assert_eq!(messages.len(), 1);
+        let message = &messages[0];
+        assert_eq!((message.anomaly.as_str(), message.severity.as_str()), ("price_spike", "critical"));
+        let zscore = message.price_zscore.unwrap();
+        assert!((zscore - 9.5304).abs() < 1e-3, "z-score {}", zscore);
+        // Identical sizes have no spread to score against
+        assert_eq!(message.volume_zscore, None);
+        assert_eq!((message.previous_price, message.current_price), (100.0, 110.0));
+    }
+
+    #[test]
+    fn volume_spike_matches_known_zscore() {
+        let amounts = [1.0, 2.0, 1.0, 2.0, 1.0, 2.0, 1.0, 2.0, 50.0];
+        let trades: Vec<(f64, f64)> = amounts.iter().map(|&amount| (100.0, amount)).collect();
+        let messages = anomalies(&trades);
+
+        // EWMA mean and variance of the log sizes
+        assert_eq!(messages.len(), 1);
+        let message = &messages[0];
+        assert_eq!((message.anomaly.as_str(), message.severity.as_str()), ("volume_spike", "critical"));
+        let zscore = message.volume_zscore.unwrap();
+        assert!((zscore - 10.3925).abs() < 1e-3, "z-score {}", zscore);
+        assert_eq!(message.amount_in_sol, 50.0);
+    }
+}
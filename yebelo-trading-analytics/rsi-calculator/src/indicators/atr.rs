@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Indicator, IndicatorOutput};
+use crate::candles::Candle;
+use crate::config::AtrConfig;
+
+/// Average True Range published for one token when a candle closes
+#[derive(Debug, Serialize)]
+pub struct AtrMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    pub atr: f64,
+    /// ATR as a percentage of the closing price
+    pub atr_percent: f64,
+    pub close: f64,
+    /// Candle close time (RFC 3339)
+    pub timestamp: String,
+    pub period: usize,
+    pub interval_secs: i64,
+}
+
+/// Per-token ATR state using Wilder's smoothing of the true range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Atr {
+    period: usize,
+    prev_close: Option<f64>,
+    seed_sum: f64,
+    seed_count: usize,
+    value: Option<f64>,
+}
+
+impl Atr {
+    pub fn new(config: &AtrConfig) -> Self {
+        Self {
+            period: config.period,
+            prev_close: None,
+            seed_sum: 0.0,
+            seed_count: 0,
+            value: None,
+        }
+    }
+
+    /// Feed a completed candle and return the ATR once seeded
+    pub fn on_candle(&mut self, candle: &Candle) -> Option<f64> {
+        // True range also covers gaps from the previous close
+        let true_range = match self.prev_close.replace(candle.close) {
+            Some(prev_close) => (candle.high - candle.low)
+                .max((candle.high - prev_close).abs())
+                .max((candle.low - prev_close).abs()),
+            None => candle.high - candle.low,
+        };
+
+        let period = self.period as f64;
+        match self.value {
+            Some(prev) => self.value = Some((prev * (period - 1.0) + true_range) / period),
+            None => {
+                self.seed_sum += true_range;
+                self.seed_count += 1;
+                if self.seed_count == self.period {
+                    self.value = Some(self.seed_sum / period);
+                }
+            }
+        }
+
+        self.value
+    }
+
+    /// Feed a completed candle and build a message once seeded
+    pub fn update(&mut self, token_address: &str, candle: &Candle) -> Option<AtrMessage> {
+        let atr = self.on_candle(candle)?;
+
+        Some(AtrMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            atr,
+            atr_percent: if candle.close > 0.0 { atr / candle.close * 100.0 } else { 0.0 },
+            close: candle.close,
+            timestamp: crate::format_unix_time(candle.end_time()),
+            period: self.period,
+            interval_secs: candle.interval_secs,
+        })
+    }
+}
+
+impl Indicator for Atr {
+    fn on_bar(&mut self, token_address: &str, candle: &Candle) -> Option<IndicatorOutput> {
+        self.update(token_address, candle).map(IndicatorOutput::Atr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::bars;
+
+    #[test]
+    fn atr_matches_known_values() {
+        let mut atr = Atr::new(&AtrConfig::default());
+        let messages: Vec<AtrMessage> = bars().iter().filter_map(|bar| atr.update("token", bar)).collect();
+
+        // Seeded with the mean true range of the first 14 bars, then Wilder-smoothed
+        assert_eq!(messages.len(), 30 - 13);
+        assert!((messages[0].atr - 0.9843).abs() < 1e-3, "ATR {}", messages[0].atr);
+        assert!((messages[1].atr - 1.0018).abs() < 1e-3, "ATR {}", messages[1].atr);
+        let last = messages.last().unwrap();
+        assert!((last.atr - 1.1592).abs() < 1e-3, "ATR {}", last.atr);
+        assert!((last.atr_percent - 6.9207).abs() < 1e-3, "ATR% {}", last.atr_percent);
+    }
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Indicator, IndicatorOutput, TradeInput};
+use crate::config::BollingerConfig;
+use crate::PriceHistory;
+
+/// Bollinger Bands published for one token after a trade
+#[derive(Debug, Serialize)]
+pub struct BollingerMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    pub upper: f64,
+    /// Simple moving average of the window
+    pub middle: f64,
+    pub lower: f64,
+    /// Position of the price within the bands (0 = lower, 1 = upper)
+    pub percent_b: f64,
+    pub current_price: f64,
+    pub timestamp: String,
+    pub period: usize,
+    pub std_dev_multiplier: f64,
+}
+
+/// Calculate Bollinger Bands (mean ± k·stddev) from the token's recent prices
+pub fn calculate(
+    history: &PriceHistory,
+    config: &BollingerConfig,
+    token_address: &str,
+    price: f64,
+    timestamp: &str,
+) -> Option<BollingerMessage> {
+    let (mean, std_dev) = history.mean_std_dev(config.period)?;
+
+    let upper = mean + config.std_dev_multiplier * std_dev;
+    let lower = mean - config.std_dev_multiplier * std_dev;
+
+    // Flat prices collapse the bands; treat the price as sitting in the middle
+    let percent_b = if upper > lower {
+        (price - lower) / (upper - lower)
+    } else {
+        0.5
+    };
+
+    Some(BollingerMessage {
+        token_address: token_address.to_string(),
+        quote_currency: String::new(),
+        upper,
+        middle: mean,
+        lower,
+        percent_b,
+        current_price: price,
+        timestamp: timestamp.to_string(),
+        period: config.period,
+        std_dev_multiplier: config.std_dev_multiplier,
+    })
+}
+
+/// Bollinger Bands as a per-token indicator; stateless apart from its settings,
+/// since everything it needs is in the token's price history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bollinger {
+    config: BollingerConfig,
+}
+
+impl Bollinger {
+    pub fn new(config: &BollingerConfig) -> Self {
+        Self { config: config.clone() }
+    }
+}
+
+impl Indicator for Bollinger {
+    fn on_trade(&mut self, trade: &TradeInput) -> Option<IndicatorOutput> {
+        calculate(trade.history, &self.config, trade.token_address, trade.price, trade.timestamp)
+            .map(IndicatorOutput::Bollinger)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::CLOSES;
+    use crate::{Arithmetic, RsiWeighting};
+
+    #[test]
+    fn bollinger_bands_match_known_values() {
+        let config = BollingerConfig::default();
+        let mut history = PriceHistory::new(100, &[], RsiWeighting::Equal, Arithmetic::Float);
+        let mut messages = Vec::new();
+        for close in CLOSES {
+            history.add_price(close, 1.0);
+            messages.extend(calculate(&history, &config, "token", close, ""));
+        }
+
+        // 20-close bands with a population standard deviation
+        assert_eq!(messages.len(), 2);
+        let last = &messages[1];
+        assert!((last.middle - 45.5041).abs() < 1e-3, "middle {}", last.middle);
+        assert!((last.upper - 47.1726).abs() < 1e-3, "upper {}", last.upper);
+        assert!((last.lower - 43.8356).abs() < 1e-3, "lower {}", last.lower);
+        assert!((last.percent_b - 0.7122).abs() < 1e-3, "%B {}", last.percent_b);
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::{Indicator, IndicatorOutput};
+use crate::candles::Candle;
+use crate::config::CciConfig;
+
+/// Commodity Channel Index published for one token when a candle closes
+#[derive(Debug, Serialize)]
+pub struct CciMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Typically within ±100; beyond that the price is stretched from its mean
+    pub cci: f64,
+    /// (high + low + close) / 3 of the closed candle
+    pub typical_price: f64,
+    pub close: f64,
+    /// Candle close time (RFC 3339)
+    pub timestamp: String,
+    pub period: usize,
+    pub interval_secs: i64,
+}
+
+/// Per-token CCI state: typical prices of the last `period` candles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cci {
+    period: usize,
+    typical_prices: VecDeque<f64>,
+}
+
+impl Cci {
+    pub fn new(config: &CciConfig) -> Self {
+        Self {
+            period: config.period,
+            typical_prices: VecDeque::with_capacity(config.period + 1),
+        }
+    }
+
+    /// Feed a completed candle and build a message once the window is full
+    pub fn update(&mut self, token_address: &str, candle: &Candle) -> Option<CciMessage> {
+        let typical_price = (candle.high + candle.low + candle.close) / 3.0;
+        self.typical_prices.push_back(typical_price);
+        if self.typical_prices.len() > self.period {
+            self.typical_prices.pop_front();
+        }
+        if self.typical_prices.len() < self.period {
+            return None;
+        }
+
+        // CCI = (TP - SMA(TP)) / (0.015 * mean absolute deviation)
+        let period = self.period as f64;
+        let mean = self.typical_prices.iter().sum::<f64>() / period;
+        let mean_deviation = self.typical_prices.iter().map(|tp| (tp - mean).abs()).sum::<f64>() / period;
+        let cci = if mean_deviation > 0.0 {
+            (typical_price - mean) / (0.015 * mean_deviation)
+        } else {
+            0.0 // Flat window, no deviation from the mean
+        };
+
+        Some(CciMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            cci,
+            typical_price,
+            close: candle.close,
+            timestamp: crate::format_unix_time(candle.end_time()),
+            period: self.period,
+            interval_secs: candle.interval_secs,
+        })
+    }
+}
+
+impl Indicator for Cci {
+    fn on_bar(&mut self, token_address: &str, candle: &Candle) -> Option<IndicatorOutput> {
+        self.update(token_address, candle).map(IndicatorOutput::Cci)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::bars;
+
+    #[test]
+    fn cci_matches_known_values() {
+        let mut cci = Cci::new(&CciConfig::default());
+        let messages: Vec<CciMessage> = bars().iter().filter_map(|bar| cci.update("token", bar)).collect();
+
+        // (TP - SMA 20 of TP) / (0.015 × mean deviation), from the 20th bar on
+        let expected = [23.1333, -37.1900, -102.6253, -161.6027, -164.4088, -175.4521, -183.7796, -167.1650];
+        assert_eq!(messages.len(), 30 - 19);
+        for (message, cci) in messages.iter().zip(expected) {
+            assert!((message.cci - cci).abs() < 1e-3, "CCI {} instead of {}", message.cci, cci);
+        }
+        assert!((messages[0].typical_price - 23.02).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::{Indicator, IndicatorOutput, TradeInput};
+use crate::config::ConnorsRsiConfig;
+use crate::WilderState;
+
+/// Connors RSI published for one token after a trade
+#[derive(Debug, Serialize)]
+pub struct ConnorsRsiMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Average of the three components (0-100)
+    pub connors_rsi: f64,
+    /// RSI of the price
+    pub price_rsi: f64,
+    /// RSI of the up/down streak length
+    pub streak_rsi: f64,
+    /// Share of recent one-trade returns below the latest one (0-100)
+    pub percent_rank: f64,
+    /// Consecutive higher (positive) or lower (negative) prices
+    pub streak: i64,
+    pub current_price: f64,
+    pub timestamp: String,
+    pub rsi_period: usize,
+    pub streak_period: usize,
+    pub rank_period: usize,
+}
+
+/// Per-token Connors RSI state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnorsRsi {
+    rank_period: usize,
+    price_rsi: WilderState,
+    streak_rsi: WilderState,
+    prev_price: Option<f64>,
+    streak: i64,
+    // The last `rank_period` one-trade returns, oldest first
+    returns: VecDeque<f64>,
+}
+
+impl ConnorsRsi {
+    pub fn new(config: &ConnorsRsiConfig) -> Self {
+        Self {
+            rank_period: config.rank_period,
+            price_rsi: WilderState::new(config.rsi_period),
+            streak_rsi: WilderState::new(config.streak_period),
+            prev_price: None,
+            streak: 0,
+            returns: VecDeque::with_capacity(config.rank_period + 1),
+        }
+    }
+
+    /// Feed the latest price and build a message once every component is ready
+    pub fn update(&mut self, token_address: &str, price: f64, timestamp: &str) -> Option<ConnorsRsiMessage> {
+        self.price_rsi.update(price, 1.0);
+        let Some(prev_price) = self.prev_price.replace(price) else {
+            self.streak_rsi.update(0.0, 1.0);
+            return None;
+        };
+
+        self.streak = if price > prev_price {
+            self.streak.max(0) + 1
+        } else if price < prev_price {
+            self.streak.min(0) - 1
+        } else {
+            0
+        };
+        self.streak_rsi.update(self.streak as f64, 1.0);
+
+        // Rank today's return against the previous `rank_period` ones
+        let change = if prev_price != 0.0 { (price - prev_price) / prev_price } else { 0.0 };
+        let percent_rank = (self.returns.len() == self.rank_period).then(|| {
+            let below = self.returns.iter().filter(|&&r| r < change).count();
+            below as f64 / self.rank_period as f64 * 100.0
+        });
+        self.returns.push_back(change);
+        if self.returns.len() > self.rank_period {
+            self.returns.pop_front();
+        }
+
+        let price_rsi = self.price_rsi.rsi()?;
+        let streak_rsi = self.streak_rsi.rsi()?;
+        let percent_rank = percent_rank?;
+
+        Some(ConnorsRsiMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            connors_rsi: (price_rsi + streak_rsi + percent_rank) / 3.0,
+            price_rsi,
+            streak_rsi,
+            percent_rank,
+            streak: self.streak,
+            current_price: price,
+            timestamp: timestamp.to_string(),
+            rsi_period: self.price_rsi.period(),
+            streak_period: self.streak_rsi.period(),
+            rank_period: self.rank_period,
+        })
+    }
+}
+
+impl Indicator for ConnorsRsi {
+    fn on_trade(&mut self, trade: &TradeInput) -> Option<IndicatorOutput> {
+        self.update(trade.token_address, trade.price, trade.timestamp).map(IndicatorOutput::ConnorsRsi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::CLOSES;
+
+    #[test]
+    fn connors_rsi_matches_known_values() {
+        let config = ConnorsRsiConfig {
+            rank_period: 10,
+            ..ConnorsRsiConfig::default()
+        };
+        let mut connors = ConnorsRsi::new(&config);
+        let messages: Vec<ConnorsRsiMessage> =
+            CLOSES.iter().filter_map(|&close| connors.update("token", close, "")).collect();
+
+        // RSI 3 of price, RSI 2 of the streak and the rank of the latest of
+        // 10 returns, from the 12th close on
+        assert_eq!(messages.len(), CLOSES.len() - 11);
+        let first = &messages[0];
+        assert_eq!(first.streak, 1);
+        assert!((first.price_rsi - 78.0582).abs() < 1e-3, "price RSI {}", first.price_rsi);
+        assert!((first.streak_rsi - 41.6369).abs() < 1e-3, "streak RSI {}", first.streak_rsi);
+        assert!((first.percent_rank - 40.0).abs() < 1e-9);
+        assert!((first.connors_rsi - 53.2317).abs() < 1e-3, "CRSI {}", first.connors_rsi);
+        let last = messages.last().unwrap();
+        assert_eq!(last.streak, 1);
+        assert!((last.percent_rank - 90.0).abs() < 1e-9);
+        assert!((last.connors_rsi - 73.2632).abs() < 1e-3, "CRSI {}", last.connors_rsi);
+    }
+}
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+
+use super::moving_average::{Ema, Sma};
+use super::{Indicator, IndicatorOutput};
+use crate::candles::Candle;
+use crate::config::{CrossoverConfig, MaKind};
+
+/// A fast moving average crossing the slow one, published as an alert event
+#[derive(Debug, Serialize)]
+pub struct CrossoverMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// "golden_cross" (fast crosses above slow) or "death_cross" (below)
+    pub crossover: String,
+    /// "bullish" for a golden cross, "bearish" for a death cross
+    pub direction: String,
+    pub average: MaKind,
+    pub fast_period: usize,
+    pub slow_period: usize,
+    pub fast: f64,
+    pub slow: f64,
+    pub close: f64,
+    pub interval_secs: i64,
+    /// Open and close time of the candle the cross happened on (RFC 3339)
+    pub candle_start: String,
+    pub timestamp: String,
+}
+
+/// A simple or exponential moving average
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Average {
+    Sma(Sma),
+    Ema(Ema),
+}
+
+impl Average {
+    fn new(kind: MaKind, period: usize) -> Self {
+        match kind {
+            MaKind::Sma => Average::Sma(Sma::new(period)),
+            MaKind::Ema => Average::Ema(Ema::new(period)),
+        }
+    }
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        match self {
+            Average::Sma(sma) => sma.update(value),
+            Average::Ema(ema) => ema.update(value),
+        }
+    }
+}
+
+/// Per-token crossover state: both averages over candle closes and which
+/// side of the slow average the fast one was on at the last close
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Crossover {
+    kind: MaKind,
+    fast_period: usize,
+    slow_period: usize,
+    fast: Average,
+    slow: Average,
+    fast_above: Option<bool>,
+}
+
+impl Crossover {
+    pub fn new(config: &CrossoverConfig) -> Self {
+        Self {
+            kind: config.average,
+            fast_period: config.fast_period,
+            slow_period: config.slow_period,
+            fast: Average::new(config.average, config.fast_period),
+            slow: Average::new(config.average, config.slow_period),
+            fast_above: None,
+        }
+    }
+
+    /// Feed a completed candle; returns a message when the fast average
+    /// moved to the other side of the slow one
+    pub fn update(&mut self, token_address: &str, candle: &Candle) -> Option<CrossoverMessage> {
+        let fast = self.fast.update(candle.close);
+        let slow = self.slow.update(candle.close);
+        let (fast, slow) = (fast?, slow?);
+
+        // Touching averages keep the previous side, so a cross needs a
+        // strict move through the slow average
+        let fast_above = match self.fast_above {
+            Some(above) if fast == slow => above,
+            _ => fast > slow,
+        };
+        let previous = self.fast_above.replace(fast_above)?;
+        if previous == fast_above {
+            return None;
+        }
+
+        let (crossover, direction) = if fast_above {
+            ("golden_cross", "bullish")
+        } else {
+            ("death_cross", "bearish")
+        };
+
+        Some(CrossoverMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            crossover: crossover.to_string(),
+            direction: direction.to_string(),
+            average: self.kind,
+            fast_period: self.fast_period,
+            slow_period: self.slow_period,
+            fast,
+            slow,
+            close: candle.close,
+            interval_secs: candle.interval_secs,
+            candle_start: crate::format_unix_time(candle.start_time),
+            timestamp: crate::format_unix_time(candle.end_time()),
+        })
+    }
+}
+
+impl Indicator for Crossover {
+    fn on_bar(&mut self, token_address: &str, candle: &Candle) -> Option<IndicatorOutput> {
+        self.update(token_address, candle).map(IndicatorOutput::Crossover)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::bars;
+
+    fn crossings(average: MaKind) -> Vec<CrossoverMessage> {
+        let config = CrossoverConfig {
+            average,
+            fast_period: 3,
+            slow_period: 8,
+            ..CrossoverConfig::default()
+        };
+        let mut crossover = Crossover::new(&config);
+        bars().iter().filter_map(|bar| crossover.update("token", bar)).collect()
+    }
+
+    #[test]
+    fn sma_death_cross_matches_known_values() {
+        let crossings = crossings(MaKind::Sma);
+
+        // The fast SMA starts above and drops below once the bars turn down
+        assert_eq!(crossings.len(), 1);
+        let cross = &crossings[0];
+        assert_eq!((cross.crossover.as_str(), cross.direction.as_str()), ("death_cross", "bearish"));
+        assert_eq!(cross.close, 23.53);
+        assert!((cross.fast - 23.9633).abs() < 1e-3, "fast {}", cross.fast);
+        assert!((cross.slow - 23.9825).abs() < 1e-3, "slow {}", cross.slow);
+    }
+
+    #[test]
+    fn ema_death_cross_matches_known_values() {
+        let crossings = crossings(MaKind::Ema);
+
+        assert_eq!(crossings.len(), 1);
+        let cross = &crossings[0];
+        assert_eq!(cross.crossover, "death_cross");
+        assert_eq!(cross.close, 23.32);
+        assert!((cross.fast - 23.5770).abs() < 1e-3, "fast {}", cross.fast);
+        assert!((cross.slow - 23.6053).abs() < 1e-3, "slow {}", cross.slow);
+    }
+}
@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::{Indicator, IndicatorOutput, RsiInput};
+use crate::config::DivergenceConfig;
+use crate::{PriceHistory, Smoothing};
+
+/// A divergence between price and RSI swings, published as an alert event
+#[derive(Debug, Serialize)]
+pub struct DivergenceMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// "bullish" (price lower low, RSI higher low) or "bearish"
+    /// (price higher high, RSI lower high)
+    pub divergence: String,
+    pub period: usize,
+    /// Price at the earlier and the latest swing point
+    pub previous_price: f64,
+    pub pivot_price: f64,
+    /// RSI at the earlier and the latest swing point
+    pub previous_rsi: f64,
+    pub pivot_rsi: f64,
+    pub current_price: f64,
+    pub timestamp: String,
+}
+
+/// A swing high or low in the price series, with the RSI at that point
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Pivot {
+    price: f64,
+    rsi: f64,
+    // Sample number, to expire pivots older than the lookback
+    index: u64,
+}
+
+/// Per-token divergence state: recent (price, RSI) samples and the last
+/// confirmed swing high and low
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Divergence {
+    period: usize,
+    pivot_span: usize,
+    lookback: u64,
+    window: VecDeque<(f64, f64)>,
+    samples: u64,
+    last_low: Option<Pivot>,
+    last_high: Option<Pivot>,
+}
+
+impl Divergence {
+    pub fn new(config: &DivergenceConfig) -> Self {
+        Self {
+            period: config.period,
+            pivot_span: config.pivot_span,
+            lookback: config.lookback as u64,
+            window: VecDeque::with_capacity(2 * config.pivot_span + 1),
+            samples: 0,
+            last_low: None,
+            last_high: None,
+        }
+    }
+
+    /// Feed the latest price (already added to `history`); returns a message
+    /// when the newly confirmed swing point diverges from the previous one
+    pub fn update(
+        &mut self,
+        history: &PriceHistory,
+        smoothing: Smoothing,
+        token_address: &str,
+        price: f64,
+        timestamp: &str,
+    ) -> Option<DivergenceMessage> {
+        let rsi = history.rsi(self.period, smoothing)?;
+
+        self.samples += 1;
+        self.window.push_back((price, rsi));
+        if self.window.len() > 2 * self.pivot_span + 1 {
+            self.window.pop_front();
+        }
+        if self.window.len() < 2 * self.pivot_span + 1 {
+            return None;
+        }
+
+        // The middle sample is a swing point once `pivot_span` samples on
+        // each side are strictly higher (low) or lower (high)
+        let (pivot_price, pivot_rsi) = self.window[self.pivot_span];
+        let pivot = Pivot {
+            price: pivot_price,
+            rsi: pivot_rsi,
+            index: self.samples - self.pivot_span as u64,
+        };
+        let others = || {
+            self.window
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i != self.pivot_span)
+                .map(|(_, &(price, _))| price)
+        };
+        let is_low = others().all(|other| other > pivot_price);
+        let is_high = others().all(|other| other < pivot_price);
+
+        let (divergence, previous) = if is_low {
+            let previous = self.last_low.replace(pivot);
+            let diverges = |prev: &Pivot| pivot.price < prev.price && pivot.rsi > prev.rsi;
+            ("bullish", previous.filter(|prev| self.within_lookback(prev, &pivot) && diverges(prev))?)
+        } else if is_high {
+            let previous = self.last_high.replace(pivot);
+            let diverges = |prev: &Pivot| pivot.price > prev.price && pivot.rsi < prev.rsi;
+            ("bearish", previous.filter(|prev| self.within_lookback(prev, &pivot) && diverges(prev))?)
+        } else {
+            return None;
+        };
+
+        Some(DivergenceMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            divergence: divergence.to_string(),
+            period: self.period,
+            previous_price: previous.price,
+            pivot_price: pivot.price,
+            previous_rsi: previous.rsi,
+            pivot_rsi: pivot.rsi,
+            current_price: price,
+            timestamp: timestamp.to_string(),
+        })
+    }
+
+    fn within_lookback(&self, previous: &Pivot, pivot: &Pivot) -> bool {
+        pivot.index - previous.index <= self.lookback
+    }
+}
+
+impl Indicator for Divergence {
+    fn on_rsi(&mut self, input: &RsiInput) -> Option<IndicatorOutput> {
+        self.update(input.history, input.smoothing, input.token_address, input.price, input.timestamp)
+            .map(IndicatorOutput::Divergence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Arithmetic, RsiWeighting};
+
+    fn divergences(prices: &[f64], lookback: usize) -> Vec<DivergenceMessage> {
+        let config = DivergenceConfig {
+            period: 3,
+            pivot_span: 1,
+            lookback,
+            ..DivergenceConfig::default()
+        };
+        let mut divergence = Divergence::new(&config);
+        let mut history = PriceHistory::new(100, &[3], RsiWeighting::Equal, Arithmetic::Float);
+        let mut messages = Vec::new();
+        for &price in prices {
+            history.add_price(price, 1.0);
+            messages.extend(divergence.update(&history, Smoothing::Wilder, "token", price, ""));
+        }
+        messages
+    }
+
+    #[test]
+    fn lower_low_with_higher_rsi_is_bullish() {
+        let prices = [10.0, 11.0, 12.0, 13.0, 11.5, 9.5, 9.0, 10.5, 8.5, 10.5, 11.5, 11.0];
+        let messages = divergences(&prices, 50);
+
+        // Swing lows at 9.0 and 8.5 with Wilder RSI 3 of 26.23 and 30.96
+        assert_eq!(messages.len(), 1);
+        let message = &messages[0];
+        assert_eq!(message.divergence, "bullish");
+        assert_eq!((message.previous_price, message.pivot_price), (9.0, 8.5));
+        assert!((message.previous_rsi - 26.2295).abs() < 1e-3, "RSI {}", message.previous_rsi);
+        assert!((message.pivot_rsi - 30.9589).abs() < 1e-3, "RSI {}", message.pivot_rsi);
+        // Confirmed one sample after the swing low
+        assert_eq!(message.current_price, 10.5);
+    }
+
+    #[test]
+    fn higher_high_with_lower_rsi_is_bearish() {
+        let prices = [10.0, 9.0, 8.0, 9.0, 10.0, 13.0, 12.0, 12.5, 13.2, 12.8, 13.0];
+        let messages = divergences(&prices, 50);
+
+        // Swing highs at 13.0 and 13.2 with Wilder RSI 3 of 82.22 and 76.08
+        assert_eq!(messages.len(), 1);
+        let message = &messages[0];
+        assert_eq!(message.divergence, "bearish");
+        assert_eq!((message.previous_price, message.pivot_price), (13.0, 13.2));
+        assert!((message.previous_rsi - 82.2222).abs() < 1e-3, "RSI {}", message.previous_rsi);
+        assert!((message.pivot_rsi - 76.0812).abs() < 1e-3, "RSI {}", message.pivot_rsi);
+    }
+
+    #[test]
+    fn swing_points_beyond_the_lookback_are_not_compared() {
+        let prices = [10.0, 11.0, 12.0, 13.0, 11.5, 9.5, 9.0, 10.5, 8.5, 10.5, 11.5, 11.0];
+        assert!(divergences(&prices, 1).is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::{Indicator, IndicatorOutput};
+use crate::candles::Candle;
+
+/// Donchian Channel published for one token when a candle closes
+#[derive(Debug, Serialize)]
+pub struct DonchianMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Highest high of the previous `period` candles
+    pub upper: f64,
+    pub middle: f64,
+    /// Lowest low of the previous `period` candles
+    pub lower: f64,
+    pub close: f64,
+    /// "up" when the close broke above the channel, "down" below it
+    pub breakout: Option<String>,
+    /// Candle close time (RFC 3339)
+    pub timestamp: String,
+    pub period: usize,
+    pub interval_secs: i64,
+}
+
+/// Per-token Donchian state: high and low of the most recent candles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Donchian {
+    period: usize,
+    breakouts_only: bool,
+    // (high, low) of the last `period` candles, oldest first
+    candles: VecDeque<(f64, f64)>,
+}
+
+impl Donchian {
+    pub fn new(period: usize, breakouts_only: bool) -> Self {
+        Self {
+            period,
+            breakouts_only,
+            candles: VecDeque::with_capacity(period + 1),
+        }
+    }
+
+    /// Feed a completed candle; the channel comes from the candles before
+    /// it, so a close outside it is a breakout
+    pub fn update(&mut self, token_address: &str, candle: &Candle) -> Option<DonchianMessage> {
+        let channel = (self.candles.len() == self.period).then(|| {
+            let upper = self.candles.iter().map(|&(high, _)| high).fold(f64::MIN, f64::max);
+            let lower = self.candles.iter().map(|&(_, low)| low).fold(f64::MAX, f64::min);
+            (upper, lower)
+        });
+
+        self.candles.push_back((candle.high, candle.low));
+        if self.candles.len() > self.period {
+            self.candles.pop_front();
+        }
+
+        let (upper, lower) = channel?;
+        let breakout = if candle.close > upper {
+            Some("up")
+        } else if candle.close < lower {
+            Some("down")
+        } else {
+            None
+        };
+        if self.breakouts_only && breakout.is_none() {
+            return None;
+        }
+
+        Some(DonchianMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            upper,
+            middle: (upper + lower) / 2.0,
+            lower,
+            close: candle.close,
+            breakout: breakout.map(str::to_string),
+            timestamp: crate::format_unix_time(candle.end_time()),
+            period: self.period,
+            interval_secs: candle.interval_secs,
+        })
+    }
+}
+
+impl Indicator for Donchian {
+    fn on_bar(&mut self, token_address: &str, candle: &Candle) -> Option<IndicatorOutput> {
+        self.update(token_address, candle).map(IndicatorOutput::Donchian)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::bars;
+
+    #[test]
+    fn donchian_channel_matches_known_values() {
+        let mut donchian = Donchian::new(5, false);
+        let messages: Vec<DonchianMessage> = bars().iter().filter_map(|bar| donchian.update("token", bar)).collect();
+
+        // Channel of the 5 bars before each close
+        assert_eq!(messages.len(), 30 - 5);
+        let first = &messages[0];
+        assert_eq!((first.upper, first.lower), (21.69, 19.59));
+        assert!((first.middle - 20.64).abs() < 1e-9);
+        assert_eq!(first.breakout.as_deref(), Some("up"));
+        let last = messages.last().unwrap();
+        assert_eq!((last.upper, last.lower), (20.15, 16.14));
+        assert_eq!(last.breakout, None);
+    }
+
+    #[test]
+    fn breakouts_only_skips_closes_inside_the_channel() {
+        let mut donchian = Donchian::new(5, true);
+        let breakouts: Vec<String> = bars()
+            .iter()
+            .filter_map(|bar| donchian.update("token", bar))
+            .filter_map(|message| message.breakout)
+            .collect();
+
+        let ups = breakouts.iter().filter(|breakout| *breakout == "up").count();
+        assert_eq!((ups, breakouts.len() - ups), (5, 9));
+    }
+}
@@ -0,0 +1,65 @@
+//! Price series shared by the indicator tests
+
+use crate::candles::Candle;
+
+/// Closes from Wilder's worked 14-period RSI example (as tabulated by StockCharts)
+pub const CLOSES: [f64; 21] = [
+    44.3389, 44.0902, 44.1497, 43.6124, 44.3278, 44.8264, 45.0955, 45.4245, 45.8433, 46.0826, 45.8931, 46.0328,
+    45.6140, 46.2820, 46.2820, 46.0028, 46.0328, 46.4116, 46.2222, 45.6439, 46.2122,
+];
+
+/// High, low, close and volume of one-minute bars: 15 rising, then 15 falling
+const BARS: [(f64, f64, f64, f64); 30] = [
+    (20.25, 19.59, 20.12, 8.3),
+    (20.83, 20.04, 20.58, 27.8),
+    (20.87, 20.15, 20.24, 9.1),
+    (21.02, 20.12, 20.52, 15.0),
+    (21.69, 20.15, 21.12, 22.9),
+    (22.36, 20.60, 22.28, 18.0),
+    (22.39, 21.89, 22.11, 41.7),
+    (22.48, 21.60, 22.00, 21.8),
+    (22.56, 21.92, 22.48, 14.3),
+    (23.46, 22.26, 23.17, 31.4),
+    (23.71, 22.68, 23.50, 36.5),
+    (23.87, 23.15, 23.49, 44.4),
+    (24.47, 22.90, 24.26, 10.3),
+    (25.00, 24.13, 24.53, 27.0),
+    (24.95, 23.72, 24.19, 30.8),
+    (24.56, 23.76, 24.34, 31.7),
+    (24.64, 23.51, 24.02, 47.5),
+    (24.44, 23.45, 23.53, 36.6),
+    (24.13, 22.82, 23.32, 17.8),
+    (23.74, 22.63, 22.69, 25.8),
+    (22.80, 21.63, 21.71, 39.6),
+    (21.90, 20.40, 20.67, 44.2),
+    (20.97, 19.20, 19.55, 44.8),
+    (20.14, 19.35, 19.61, 23.7),
+    (20.15, 18.35, 18.93, 11.8),
+    (19.11, 17.78, 17.96, 26.8),
+    (18.15, 17.60, 17.65, 23.9),
+    (18.01, 16.42, 16.99, 36.1),
+    (17.38, 16.14, 16.56, 7.4),
+    (17.23, 16.03, 16.75, 40.9),
+];
+
+/// `BARS` as candles, each opening at the previous close
+pub fn bars() -> Vec<Candle> {
+    let mut open = 20.0;
+    (0..)
+        .zip(BARS)
+        .map(|(i, (high, low, close, volume))| {
+            let candle = Candle {
+                start_time: i * 60,
+                interval_secs: 60,
+                open,
+                high,
+                low,
+                close,
+                volume,
+                trade_count: 1,
+            };
+            open = close;
+            candle
+        })
+        .collect()
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::{Indicator, IndicatorOutput, TradeInput};
+use crate::config::FlowConfig;
+
+/// Buy/sell pressure over a token's recent trades
+#[derive(Debug, Serialize)]
+pub struct FlowMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Trades in the window
+    pub window: usize,
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    /// buy / sell volume; absent while there were no sells
+    pub volume_ratio: Option<f64>,
+    /// (buy - sell) / (buy + sell) volume, from -1 (all sells) to 1 (all buys)
+    pub volume_imbalance: f64,
+    pub buy_count: usize,
+    pub sell_count: usize,
+    /// (buys - sells) / trades, from -1 to 1
+    pub count_imbalance: f64,
+    pub current_price: f64,
+    pub timestamp: String,
+}
+
+/// Per-token rolling window of trade sides and sizes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Flow {
+    window: usize,
+    // (is_buy, amount_in_sol) of the most recent trades, oldest first
+    trades: VecDeque<(bool, f64)>,
+}
+
+impl Flow {
+    pub fn new(config: &FlowConfig) -> Self {
+        Self {
+            window: config.window,
+            trades: VecDeque::with_capacity(config.window + 1),
+        }
+    }
+
+    /// Feed the latest trade and build a message once the window is full
+    pub fn update(
+        &mut self,
+        token_address: &str,
+        is_buy: bool,
+        amount: f64,
+        price: f64,
+        timestamp: &str,
+    ) -> Option<FlowMessage> {
+        self.trades.push_back((is_buy, amount));
+        if self.trades.len() > self.window {
+            self.trades.pop_front();
+        }
+        if self.trades.len() < self.window {
+            return None;
+        }
+
+        let (mut buy_volume, mut sell_volume, mut buy_count) = (0.0, 0.0, 0);
+        for &(is_buy, amount) in &self.trades {
+            if is_buy {
+                buy_volume += amount;
+                buy_count += 1;
+            } else {
+                sell_volume += amount;
+            }
+        }
+        let sell_count = self.window - buy_count;
+
+        let total_volume = buy_volume + sell_volume;
+        let volume_imbalance = if total_volume > 0.0 {
+            (buy_volume - sell_volume) / total_volume
+        } else {
+            0.0
+        };
+
+        Some(FlowMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            window: self.window,
+            buy_volume,
+            sell_volume,
+            volume_ratio: (sell_volume > 0.0).then(|| buy_volume / sell_volume),
+            volume_imbalance,
+            buy_count,
+            sell_count,
+            count_imbalance: (buy_count as f64 - sell_count as f64) / self.window as f64,
+            current_price: price,
+            timestamp: timestamp.to_string(),
+        })
+    }
+}
+
+impl Indicator for Flow {
+    fn on_trade(&mut self, trade: &TradeInput) -> Option<IndicatorOutput> {
+        self.update(trade.token_address, trade.is_buy, trade.amount, trade.price, trade.timestamp).map(IndicatorOutput::Flow)
+    }
+}
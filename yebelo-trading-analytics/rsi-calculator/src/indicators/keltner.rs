@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+use super::atr::Atr;
+use super::moving_average::Ema;
+use super::{Indicator, IndicatorOutput};
+use crate::candles::Candle;
+use crate::config::{AtrConfig, KeltnerConfig};
+
+/// Keltner Channels published for one token when a candle closes
+#[derive(Debug, Serialize)]
+pub struct KeltnerMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    pub upper: f64,
+    /// EMA of candle closes
+    pub middle: f64,
+    pub lower: f64,
+    pub close: f64,
+    /// Close above the upper band
+    pub breakout_up: bool,
+    /// Close below the lower band
+    pub breakout_down: bool,
+    /// Candle close time (RFC 3339)
+    pub timestamp: String,
+    pub ema_period: usize,
+    pub atr_period: usize,
+    pub multiplier: f64,
+    pub interval_secs: i64,
+}
+
+/// Per-token Keltner state: EMA of closes and ATR of the same candles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keltner {
+    multiplier: f64,
+    atr_period: usize,
+    ema: Ema,
+    atr: Atr,
+}
+
+impl Keltner {
+    pub fn new(config: &KeltnerConfig) -> Self {
+        let atr = AtrConfig {
+            period: config.atr_period,
+            ..AtrConfig::default()
+        };
+        Self {
+            multiplier: config.multiplier,
+            atr_period: config.atr_period,
+            ema: Ema::new(config.ema_period),
+            atr: Atr::new(&atr),
+        }
+    }
+
+    /// Feed a completed candle and build a message once both the EMA and
+    /// ATR are seeded
+    pub fn update(&mut self, token_address: &str, candle: &Candle) -> Option<KeltnerMessage> {
+        let middle = self.ema.update(candle.close);
+        let atr = self.atr.on_candle(candle);
+        let (middle, atr) = (middle?, atr?);
+
+        let upper = middle + self.multiplier * atr;
+        let lower = middle - self.multiplier * atr;
+
+        Some(KeltnerMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            upper,
+            middle,
+            lower,
+            close: candle.close,
+            breakout_up: candle.close > upper,
+            breakout_down: candle.close < lower,
+            timestamp: crate::format_unix_time(candle.end_time()),
+            ema_period: self.ema.period(),
+            atr_period: self.atr_period,
+            multiplier: self.multiplier,
+            interval_secs: candle.interval_secs,
+        })
+    }
+}
+
+impl Indicator for Keltner {
+    fn on_bar(&mut self, token_address: &str, candle: &Candle) -> Option<IndicatorOutput> {
+        self.update(token_address, candle).map(IndicatorOutput::Keltner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::bars;
+
+    #[test]
+    fn keltner_channels_match_known_values() {
+        let config = KeltnerConfig {
+            ema_period: 10,
+            atr_period: 5,
+            ..KeltnerConfig::default()
+        };
+        let mut keltner = Keltner::new(&config);
+        let messages: Vec<KeltnerMessage> = bars().iter().filter_map(|bar| keltner.update("token", bar)).collect();
+
+        // EMA 10 of closes ± 2 × ATR 5, from the 10th bar on
+        assert_eq!(messages.len(), 30 - 9);
+        let first = &messages[0];
+        assert!((first.middle - 21.4620).abs() < 1e-3, "middle {}", first.middle);
+        assert!((first.upper - 23.3671).abs() < 1e-3, "upper {}", first.upper);
+        assert!((first.lower - 19.5569).abs() < 1e-3, "lower {}", first.lower);
+        let last = messages.last().unwrap();
+        assert!((last.middle - 18.5388).abs() < 1e-3, "middle {}", last.middle);
+        assert!((last.upper - 21.0171).abs() < 1e-3, "upper {}", last.upper);
+        assert!((last.lower - 16.0605).abs() < 1e-3, "lower {}", last.lower);
+        assert!(!last.breakout_up && !last.breakout_down);
+    }
+}
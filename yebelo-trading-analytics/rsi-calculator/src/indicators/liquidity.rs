@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::{Indicator, IndicatorOutput, RsiInput};
+use crate::candles::Candle;
+use crate::config::LiquidityConfig;
+
+/// Turnover and trading activity of one token when a candle closes, with
+/// its latest RSI for context
+#[derive(Debug, Serialize)]
+pub struct LiquidityMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Average SOL traded per candle over the window, counting candles
+    /// without trades as zero
+    pub turnover_sol: f64,
+    /// SOL traded over the whole window
+    pub volume_sol: f64,
+    pub trade_count: u64,
+    pub average_trade_size_sol: f64,
+    pub trades_per_minute: f64,
+    /// "liquid" from `min_turnover_sol` per candle, otherwise "thin"
+    pub liquidity: String,
+    /// Latest RSI of the token's main series; absent until it has one
+    pub rsi: Option<f64>,
+    pub rsi_period: usize,
+    pub close: f64,
+    /// Candle close time (RFC 3339)
+    pub timestamp: String,
+    /// Candles the window spans
+    pub period: usize,
+    pub interval_secs: i64,
+}
+
+/// Per-token liquidity state: volume and trade count of recent candles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Liquidity {
+    period: usize,
+    rsi_period: usize,
+    min_turnover_sol: f64,
+    // (start time, volume, trades) of candles within the window, oldest first
+    candles: VecDeque<(i64, f64, u64)>,
+    rsi: Option<f64>,
+}
+
+impl Liquidity {
+    pub fn new(config: &LiquidityConfig) -> Self {
+        Self {
+            period: config.period,
+            rsi_period: config.rsi_period,
+            min_turnover_sol: config.min_turnover_sol,
+            candles: VecDeque::with_capacity(config.period + 1),
+            rsi: None,
+        }
+    }
+
+    /// Feed a completed candle; the window is measured in time, since no
+    /// candle is built for an interval without trades
+    pub fn update(&mut self, token_address: &str, candle: &Candle) -> Option<LiquidityMessage> {
+        let window_secs = self.period as i64 * candle.interval_secs;
+        self.candles.push_back((candle.start_time, candle.volume, candle.trade_count));
+        while self
+            .candles
+            .front()
+            .is_some_and(|&(start_time, _, _)| start_time <= candle.start_time - window_secs)
+        {
+            self.candles.pop_front();
+        }
+
+        let volume_sol: f64 = self.candles.iter().map(|&(_, volume, _)| volume).sum();
+        let trade_count: u64 = self.candles.iter().map(|&(_, _, trades)| trades).sum();
+        let turnover_sol = volume_sol / self.period as f64;
+
+        Some(LiquidityMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            turnover_sol,
+            volume_sol,
+            trade_count,
+            average_trade_size_sol: volume_sol / trade_count.max(1) as f64,
+            trades_per_minute: trade_count as f64 / (window_secs as f64 / 60.0),
+            liquidity: if turnover_sol >= self.min_turnover_sol { "liquid" } else { "thin" }.to_string(),
+            rsi: self.rsi,
+            rsi_period: self.rsi_period,
+            close: candle.close,
+            timestamp: crate::format_unix_time(candle.end_time()),
+            period: self.period,
+            interval_secs: candle.interval_secs,
+        })
+    }
+}
+
+impl Indicator for Liquidity {
+    fn on_rsi(&mut self, input: &RsiInput) -> Option<IndicatorOutput> {
+        if let Some(rsi) = input.history.rsi(self.rsi_period, input.smoothing) {
+            self.rsi = Some(rsi);
+        }
+        None
+    }
+
+    fn on_bar(&mut self, token_address: &str, candle: &Candle) -> Option<IndicatorOutput> {
+        self.update(token_address, candle).map(IndicatorOutput::Liquidity)
+    }
+}
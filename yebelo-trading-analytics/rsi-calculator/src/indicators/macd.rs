@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+use super::moving_average::Ema;
+use super::{Indicator, IndicatorOutput, TradeInput};
+use crate::config::MacdConfig;
+
+/// MACD values published for one token after a trade
+#[derive(Debug, Serialize)]
+pub struct MacdMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Fast EMA minus slow EMA
+    pub macd: f64,
+    /// EMA of the MACD line
+    pub signal: f64,
+    /// MACD line minus signal line
+    pub histogram: f64,
+    pub current_price: f64,
+    pub timestamp: String,
+    pub fast_period: usize,
+    pub slow_period: usize,
+    pub signal_period: usize,
+}
+
+/// Per-token MACD state: fast/slow price EMAs plus an EMA of their difference
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+}
+
+impl Macd {
+    pub fn new(config: &MacdConfig) -> Self {
+        Self {
+            fast: Ema::new(config.fast_period),
+            slow: Ema::new(config.slow_period),
+            signal: Ema::new(config.signal_period),
+        }
+    }
+
+    /// Feed a new price and build a message once the signal line is seeded
+    pub fn update(&mut self, token_address: &str, price: f64, timestamp: &str) -> Option<MacdMessage> {
+        let fast = self.fast.update(price);
+        let slow = self.slow.update(price);
+
+        // The MACD line only exists once the slow EMA is seeded
+        let macd = fast? - slow?;
+        let signal = self.signal.update(macd)?;
+
+        Some(MacdMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            macd,
+            signal,
+            histogram: macd - signal,
+            current_price: price,
+            timestamp: timestamp.to_string(),
+            fast_period: self.fast.period(),
+            slow_period: self.slow.period(),
+            signal_period: self.signal.period(),
+        })
+    }
+}
+
+impl Indicator for Macd {
+    fn on_trade(&mut self, trade: &TradeInput) -> Option<IndicatorOutput> {
+        self.update(trade.token_address, trade.price, trade.timestamp).map(IndicatorOutput::Macd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::CLOSES;
+
+    #[test]
+    fn macd_matches_known_values() {
+        let config = MacdConfig {
+            fast_period: 3,
+            slow_period: 6,
+            signal_period: 4,
+            ..MacdConfig::default()
+        };
+        let mut macd = Macd::new(&config);
+        let messages: Vec<MacdMessage> = CLOSES.iter().filter_map(|&close| macd.update("token", close, "")).collect();
+
+        // The signal line needs 6 closes for the slow EMA, then 4 MACD values
+        assert_eq!(messages.len(), CLOSES.len() - 8);
+        let first = &messages[0];
+        assert!((first.macd - 0.4148).abs() < 1e-3, "MACD {}", first.macd);
+        assert!((first.signal - 0.3326).abs() < 1e-3, "signal {}", first.signal);
+        let last = messages.last().unwrap();
+        assert!((last.macd - 0.0140).abs() < 1e-3, "MACD {}", last.macd);
+        assert!((last.signal - 0.0307).abs() < 1e-3, "signal {}", last.signal);
+        assert!((last.histogram - -0.0167).abs() < 1e-3, "histogram {}", last.histogram);
+    }
+}
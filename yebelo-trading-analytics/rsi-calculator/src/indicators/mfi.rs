@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::{Indicator, IndicatorOutput};
+use crate::candles::Candle;
+use crate::config::MfiConfig;
+
+/// Money Flow Index published for one token when a candle closes
+#[derive(Debug, Serialize)]
+pub struct MfiMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// 0-100, like RSI but with each move weighted by the candle's volume
+    pub mfi: f64,
+    /// "oversold", "neutral" or "overbought" under the MFI levels
+    pub signal: String,
+    pub close: f64,
+    /// Candle close time (RFC 3339)
+    pub timestamp: String,
+    pub period: usize,
+    pub interval_secs: i64,
+}
+
+/// Per-token MFI state: signed money flow of the last `period` candles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mfi {
+    period: usize,
+    oversold: f64,
+    overbought: f64,
+    prev_typical: Option<f64>,
+    // Money flow per candle: positive when the typical price rose,
+    // negative when it fell, zero when unchanged
+    flows: VecDeque<f64>,
+}
+
+impl Mfi {
+    pub fn new(config: &MfiConfig) -> Self {
+        Self {
+            period: config.period,
+            oversold: config.oversold,
+            overbought: config.overbought,
+            prev_typical: None,
+            flows: VecDeque::with_capacity(config.period + 1),
+        }
+    }
+
+    /// Feed a completed candle and build a message once `period` flows are known
+    pub fn update(&mut self, token_address: &str, candle: &Candle) -> Option<MfiMessage> {
+        let typical = (candle.high + candle.low + candle.close) / 3.0;
+        let prev_typical = self.prev_typical.replace(typical)?;
+
+        let money_flow = typical * candle.volume;
+        let flow = if typical > prev_typical {
+            money_flow
+        } else if typical < prev_typical {
+            -money_flow
+        } else {
+            0.0
+        };
+        self.flows.push_back(flow);
+        if self.flows.len() > self.period {
+            self.flows.pop_front();
+        }
+        if self.flows.len() < self.period {
+            return None;
+        }
+
+        let positive: f64 = self.flows.iter().filter(|&&flow| flow > 0.0).sum();
+        let negative: f64 = -self.flows.iter().filter(|&&flow| flow < 0.0).sum::<f64>();
+        // MFI = 100 - 100 / (1 + positive flow / negative flow)
+        let mfi = if negative == 0.0 {
+            if positive == 0.0 { 50.0 } else { 100.0 }
+        } else {
+            100.0 - 100.0 / (1.0 + positive / negative)
+        };
+
+        let signal = if mfi < self.oversold {
+            "oversold"
+        } else if mfi > self.overbought {
+            "overbought"
+        } else {
+            "neutral"
+        };
+
+        Some(MfiMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            mfi,
+            signal: signal.to_string(),
+            close: candle.close,
+            timestamp: crate::format_unix_time(candle.end_time()),
+            period: self.period,
+            interval_secs: candle.interval_secs,
+        })
+    }
+}
+
+impl Indicator for Mfi {
+    fn on_bar(&mut self, token_address: &str, candle: &Candle) -> Option<IndicatorOutput> {
+        self.update(token_address, candle).map(IndicatorOutput::Mfi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::bars;
+
+    #[test]
+    fn mfi_matches_known_values() {
+        let mut mfi = Mfi::new(&MfiConfig::default());
+        let messages: Vec<MfiMessage> = bars().iter().filter_map(|bar| mfi.update("token", bar)).collect();
+
+        // Money flow of 14 typical-price changes, from the 15th bar on
+        let expected = [
+            (82.1677, "overbought"),
+            (73.1567, "neutral"),
+            (65.4514, "neutral"),
+            (58.4362, "neutral"),
+            (53.8099, "neutral"),
+            (48.6969, "neutral"),
+            (39.4682, "neutral"),
+            (37.7337, "neutral"),
+            (32.7579, "neutral"),
+            (26.6854, "neutral"),
+            (19.7643, "oversold"),
+            (9.7467, "oversold"),
+            (6.9751, "oversold"),
+            (0.0, "oversold"),
+        ];
+        assert_eq!(messages.len(), 30 - 14);
+        for (message, (mfi, signal)) in messages.iter().zip(expected) {
+            assert!((message.mfi - mfi).abs() < 1e-3, "MFI {} instead of {}", message.mfi, mfi);
+            assert_eq!(message.signal, signal);
+        }
+    }
+}
@@ -0,0 +1,481 @@
+pub mod adx;
+pub mod anomaly;
+pub mod atr;
+pub mod bollinger;
+pub mod cci;
+pub mod connors_rsi;
+pub mod crossover;
+pub mod divergence;
+pub mod donchian;
+pub mod flow;
+pub mod keltner;
+pub mod liquidity;
+pub mod macd;
+pub mod mfi;
+pub mod momentum;
+pub mod moving_average;
+pub mod obv;
+pub mod parabolic_sar;
+pub mod registry;
+pub mod stoch_rsi;
+pub mod stochastic;
+pub mod supertrend;
+pub mod volatility;
+pub mod whale;
+pub mod williams_r;
+pub mod zscore;
+
+#[cfg(test)]
+mod fixtures;
+
+pub use adx::{Adx, AdxMessage};
+pub use anomaly::{Anomaly, AnomalyMessage};
+pub use atr::{Atr, AtrMessage};
+pub use bollinger::{Bollinger, BollingerMessage};
+pub use cci::{Cci, CciMessage};
+pub use connors_rsi::{ConnorsRsi, ConnorsRsiMessage};
+pub use crossover::{Crossover, CrossoverMessage};
+pub use divergence::{Divergence, DivergenceMessage};
+pub use donchian::{Donchian, DonchianMessage};
+pub use flow::{Flow, FlowMessage};
+pub use keltner::{Keltner, KeltnerMessage};
+pub use liquidity::{Liquidity, LiquidityMessage};
+pub use macd::{Macd, MacdMessage};
+pub use mfi::{Mfi, MfiMessage};
+pub use momentum::{Momentum, MomentumMessage};
+pub use moving_average::{MaMessage, MovingAverages};
+pub use obv::{Obv, ObvMessage};
+pub use parabolic_sar::{ParabolicSar, ParabolicSarMessage};
+pub use registry::{IndicatorInstance, IndicatorRegistry};
+pub use stoch_rsi::{StochRsi, StochRsiMessage};
+pub use stochastic::{Stochastic, StochasticMessage};
+pub use supertrend::{SuperTrend, SuperTrendMessage};
+pub use volatility::{Volatility, VolatilityMessage};
+pub use whale::{Whale, WhaleMessage};
+pub use williams_r::{WilliamsR, WilliamsRMessage};
+pub use zscore::{ZScore, ZScoreMessage};
+
+use serde::{Serialize, Serializer};
+use std::borrow::Cow;
+
+use crate::candles::{Candle, CandleMessage};
+use crate::config::Config;
+use crate::outliers::QuarantinedTrade;
+use crate::{PriceHistory, RsiMessage, SignalChangeMessage, Smoothing};
+
+/// An in-order trade, as seen by trade-driven indicators
+pub struct TradeInput<'a> {
+    pub token_address: &'a str,
+    pub price: f64,
+    pub amount: f64,
+    pub is_buy: bool,
+    pub transaction_signature: &'a str,
+    pub timestamp: &'a str,
+    /// The token's raw price history, already including this trade
+    pub history: &'a PriceHistory,
+}
+
+/// A new value of a token's main RSI series (ticks, or candles in candle mode)
+pub struct RsiInput<'a> {
+    pub token_address: &'a str,
+    pub price: f64,
+    pub timestamp: &'a str,
+    /// The series the RSI is calculated over, already including this price
+    pub history: &'a PriceHistory,
+    pub smoothing: Smoothing,
+}
+
+/// A streaming indicator kept per token
+///
+/// Every hook defaults to producing nothing, so an indicator only implements
+/// the inputs it reads. Register new indicators in [`registry`].
+pub trait Indicator {
+    /// Called for each trade once it is in order
+    fn on_trade(&mut self, _trade: &TradeInput) -> Option<IndicatorOutput> {
+        None
+    }
+
+    /// Called for each new value of the token's main RSI series
+    fn on_rsi(&mut self, _input: &RsiInput) -> Option<IndicatorOutput> {
+        None
+    }
+
+    /// Called for each finalized candle of the interval the indicator was registered with
+    fn on_bar(&mut self, _token_address: &str, _candle: &Candle) -> Option<IndicatorOutput> {
+        None
+    }
+}
+
+/// A message produced by one of the indicators, ready to be published
+#[derive(Debug)]
+pub enum IndicatorOutput {
+    Rsi(RsiMessage),
+    MovingAverage(MaMessage),
+    Macd(MacdMessage),
+    Bollinger(BollingerMessage),
+    ZScore(ZScoreMessage),
+    Momentum(MomentumMessage),
+    Stochastic(StochasticMessage),
+    StochRsi(StochRsiMessage),
+    ConnorsRsi(ConnorsRsiMessage),
+    Atr(AtrMessage),
+    Keltner(KeltnerMessage),
+    Donchian(DonchianMessage),
+    Cci(CciMessage),
+    WilliamsR(WilliamsRMessage),
+    ParabolicSar(ParabolicSarMessage),
+    SuperTrend(SuperTrendMessage),
+    Adx(AdxMessage),
+    Mfi(MfiMessage),
+    Obv(ObvMessage),
+    Volatility(VolatilityMessage),
+    Liquidity(LiquidityMessage),
+    Candle(CandleMessage),
+    Divergence(DivergenceMessage),
+    Crossover(CrossoverMessage),
+    Flow(FlowMessage),
+    Anomaly(AnomalyMessage),
+    Whale(WhaleMessage),
+    SignalChange(SignalChangeMessage),
+    Quarantine(QuarantinedTrade),
+}
+
+impl IndicatorOutput {
+    /// Short indicator name used in logs
+    pub fn kind(&self) -> &'static str {
+        match self {
+            IndicatorOutput::Rsi(_) => "RSI",
+            IndicatorOutput::MovingAverage(_) => "MA",
+            IndicatorOutput::Macd(_) => "MACD",
+            IndicatorOutput::Bollinger(_) => "BB",
+            IndicatorOutput::ZScore(_) => "ZSCORE",
+            IndicatorOutput::Momentum(_) => "ROC",
+            IndicatorOutput::Stochastic(_) => "STOCH",
+            IndicatorOutput::StochRsi(_) => "STOCHRSI",
+            IndicatorOutput::ConnorsRsi(_) => "CRSI",
+            IndicatorOutput::Atr(_) => "ATR",
+            IndicatorOutput::Keltner(_) => "KC",
+            IndicatorOutput::Donchian(_) => "DC",
+            IndicatorOutput::Cci(_) => "CCI",
+            IndicatorOutput::WilliamsR(_) => "WILLR",
+            IndicatorOutput::ParabolicSar(_) => "PSAR",
+            IndicatorOutput::SuperTrend(_) => "SUPERTREND",
+            IndicatorOutput::Adx(_) => "ADX",
+            IndicatorOutput::Mfi(_) => "MFI",
+            IndicatorOutput::Obv(_) => "OBV",
+            IndicatorOutput::Volatility(_) => "VOLATILITY",
+            IndicatorOutput::Liquidity(_) => "LIQUIDITY",
+            IndicatorOutput::Candle(_) => "CANDLE",
+            IndicatorOutput::Divergence(_) => "DIVERGENCE",
+            IndicatorOutput::Crossover(_) => "CROSSOVER",
+            IndicatorOutput::Flow(_) => "FLOW",
+            IndicatorOutput::Anomaly(_) => "ANOMALY",
+            IndicatorOutput::Whale(_) => "WHALE",
+            IndicatorOutput::SignalChange(_) => "SIGNAL",
+            IndicatorOutput::Quarantine(_) => "OUTLIER",
+        }
+    }
+
+    /// Topic the message should be published to
+    pub fn topic<'a>(&self, config: &'a Config) -> &'a str {
+        match self {
+            IndicatorOutput::Rsi(_) => &config.kafka.output_topic,
+            IndicatorOutput::MovingAverage(_) => &config.moving_averages.topic,
+            IndicatorOutput::Macd(_) => &config.macd.topic,
+            IndicatorOutput::Bollinger(_) => &config.bollinger.topic,
+            IndicatorOutput::ZScore(_) => &config.zscore.topic,
+            IndicatorOutput::Momentum(_) => &config.momentum.topic,
+            IndicatorOutput::Stochastic(_) => &config.stochastic.topic,
+            IndicatorOutput::StochRsi(_) => &config.stoch_rsi.topic,
+            IndicatorOutput::ConnorsRsi(_) => &config.connors_rsi.topic,
+            IndicatorOutput::Atr(_) => &config.atr.topic,
+            IndicatorOutput::Keltner(_) => &config.keltner.topic,
+            IndicatorOutput::Donchian(_) => &config.donchian.topic,
+            IndicatorOutput::Cci(_) => &config.cci.topic,
+            IndicatorOutput::WilliamsR(_) => &config.williams_r.topic,
+            IndicatorOutput::ParabolicSar(_) => &config.parabolic_sar.topic,
+            IndicatorOutput::SuperTrend(_) => &config.supertrend.topic,
+            IndicatorOutput::Adx(_) => &config.adx.topic,
+            IndicatorOutput::Mfi(_) => &config.mfi.topic,
+            IndicatorOutput::Obv(_) => &config.obv.topic,
+            IndicatorOutput::Volatility(_) => &config.volatility.topic,
+            IndicatorOutput::Liquidity(_) => &config.liquidity.topic,
+            IndicatorOutput::Candle(_) => &config.candles.topic,
+            IndicatorOutput::Divergence(_) => &config.divergence.topic,
+            IndicatorOutput::Crossover(_) => &config.crossover.topic,
+            IndicatorOutput::Flow(_) => &config.flow.topic,
+            IndicatorOutput::Anomaly(_) => &config.anomaly.topic,
+            IndicatorOutput::Whale(_) => &config.whale.topic,
+            IndicatorOutput::SignalChange(_) => &config.signal_events.topic,
+            IndicatorOutput::Quarantine(_) => &config.outliers.quarantine_topic,
+        }
+    }
+
+    /// Every topic the message is published to: its own, then the topics of
+    /// matching `[[routes]]`
+    pub fn topics<'a>(&self, config: &'a Config) -> Vec<&'a str> {
+        let mut topics = vec![self.topic(config)];
+        for route in &config.routes {
+            if route.matches(self.kind(), self.signal()) && !topics.contains(&route.topic.as_str()) {
+                topics.push(&route.topic);
+            }
+        }
+        topics
+    }
+
+    /// Oversold/overbought classification, for outputs that have one
+    pub fn signal(&self) -> Option<&str> {
+        match self {
+            IndicatorOutput::Rsi(msg) => Some(&msg.signal),
+            IndicatorOutput::Mfi(msg) => Some(&msg.signal),
+            IndicatorOutput::SignalChange(msg) => Some(&msg.signal),
+            _ => None,
+        }
+    }
+
+    /// Token the message belongs to
+    pub fn token_address(&self) -> &str {
+        match self {
+            IndicatorOutput::Rsi(msg) => &msg.token_address,
+            IndicatorOutput::MovingAverage(msg) => &msg.token_address,
+            IndicatorOutput::Macd(msg) => &msg.token_address,
+            IndicatorOutput::Bollinger(msg) => &msg.token_address,
+            IndicatorOutput::ZScore(msg) => &msg.token_address,
+            IndicatorOutput::Momentum(msg) => &msg.token_address,
+            IndicatorOutput::Stochastic(msg) => &msg.token_address,
+            IndicatorOutput::StochRsi(msg) => &msg.token_address,
+            IndicatorOutput::ConnorsRsi(msg) => &msg.token_address,
+            IndicatorOutput::Atr(msg) => &msg.token_address,
+            IndicatorOutput::Keltner(msg) => &msg.token_address,
+            IndicatorOutput::Donchian(msg) => &msg.token_address,
+            IndicatorOutput::Cci(msg) => &msg.token_address,
+            IndicatorOutput::WilliamsR(msg) => &msg.token_address,
+            IndicatorOutput::ParabolicSar(msg) => &msg.token_address,
+            IndicatorOutput::SuperTrend(msg) => &msg.token_address,
+            IndicatorOutput::Adx(msg) => &msg.token_address,
+            IndicatorOutput::Mfi(msg) => &msg.token_address,
+            IndicatorOutput::Obv(msg) => &msg.token_address,
+            IndicatorOutput::Volatility(msg) => &msg.token_address,
+            IndicatorOutput::Liquidity(msg) => &msg.token_address,
+            IndicatorOutput::Candle(msg) => &msg.token_address,
+            IndicatorOutput::Divergence(msg) => &msg.token_address,
+            IndicatorOutput::Crossover(msg) => &msg.token_address,
+            IndicatorOutput::Flow(msg) => &msg.token_address,
+            IndicatorOutput::Anomaly(msg) => &msg.token_address,
+            IndicatorOutput::Whale(msg) => &msg.token_address,
+            IndicatorOutput::SignalChange(msg) => &msg.token_address,
+            IndicatorOutput::Quarantine(msg) => &msg.token_address,
+        }
+    }
+
+    /// Currency the message's prices are quoted in
+    pub fn quote_currency(&self) -> &str {
+        match self {
+            IndicatorOutput::Rsi(msg) => &msg.quote_currency,
+            IndicatorOutput::MovingAverage(msg) => &msg.quote_currency,
+            IndicatorOutput::Macd(msg) => &msg.quote_currency,
+            IndicatorOutput::Bollinger(msg) => &msg.quote_currency,
+            IndicatorOutput::ZScore(msg) => &msg.quote_currency,
+            IndicatorOutput::Momentum(msg) => &msg.quote_currency,
+            IndicatorOutput::Stochastic(msg) => &msg.quote_currency,
+            IndicatorOutput::StochRsi(msg) => &msg.quote_currency,
+            IndicatorOutput::ConnorsRsi(msg) => &msg.quote_currency,
+            IndicatorOutput::Atr(msg) => &msg.quote_currency,
+            IndicatorOutput::Keltner(msg) => &msg.quote_currency,
+            IndicatorOutput::Donchian(msg) => &msg.quote_currency,
+            IndicatorOutput::Cci(msg) => &msg.quote_currency,
+            IndicatorOutput::WilliamsR(msg) => &msg.quote_currency,
+            IndicatorOutput::ParabolicSar(msg) => &msg.quote_currency,
+            IndicatorOutput::SuperTrend(msg) => &msg.quote_currency,
+            IndicatorOutput::Adx(msg) => &msg.quote_currency,
+            IndicatorOutput::Mfi(msg) => &msg.quote_currency,
+            IndicatorOutput::Obv(msg) => &msg.quote_currency,
+            IndicatorOutput::Volatility(msg) => &msg.quote_currency,
+            IndicatorOutput::Liquidity(msg) => &msg.quote_currency,
+            IndicatorOutput::Candle(msg) => &msg.quote_currency,
+            IndicatorOutput::Divergence(msg) => &msg.quote_currency,
+            IndicatorOutput::Crossover(msg) => &msg.quote_currency,
+            IndicatorOutput::Flow(msg) => &msg.quote_currency,
+            IndicatorOutput::Anomaly(msg) => &msg.quote_currency,
+            IndicatorOutput::Whale(msg) => &msg.quote_currency,
+            IndicatorOutput::SignalChange(msg) => &msg.quote_currency,
+            IndicatorOutput::Quarantine(msg) => &msg.quote_currency,
+        }
+    }
+
+    /// Key of the series the message belongs to, see [`crate::series_key`]
+    pub fn series_key(&self) -> Cow<'_, str> {
+        crate::series_key(self.token_address(), self.quote_currency())
+    }
+
+    /// Label the message with the token address and quote currency of the
+    /// series `key`; indicators only know the series they are kept for
+    pub fn set_series(&mut self, key: &str) {
+        let (token_address, quote_currency) = crate::split_series_key(key);
+        let (token, quote) = match self {
+            IndicatorOutput::Rsi(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::MovingAverage(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Macd(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Bollinger(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::ZScore(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Momentum(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Stochastic(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::StochRsi(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::ConnorsRsi(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Atr(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Keltner(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Donchian(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Cci(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::WilliamsR(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::ParabolicSar(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::SuperTrend(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Adx(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Mfi(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Obv(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Volatility(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Liquidity(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Candle(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Divergence(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Crossover(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Flow(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Anomaly(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Whale(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::SignalChange(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+            IndicatorOutput::Quarantine(msg) => (&mut msg.token_address, &mut msg.quote_currency),
+        };
+        token_address.clone_into(token);
+        quote_currency.clone_into(quote);
+    }
+
+    /// Period(s) the message's series is calculated over, joined with '-'
+    /// for indicators with several; absent for messages covering many
+    /// periods at once (moving averages, momentum) or none
+    pub fn period(&self) -> Option<String> {
+        let periods: &[usize] = match self {
+            IndicatorOutput::Rsi(msg) => &[msg.period],
+            IndicatorOutput::Macd(msg) => &[msg.fast_period, msg.slow_period, msg.signal_period],
+            IndicatorOutput::Bollinger(msg) => &[msg.period],
+            IndicatorOutput::ZScore(msg) => &[msg.period],
+            IndicatorOutput::Stochastic(msg) => &[msg.k_period, msg.d_period],
+            IndicatorOutput::StochRsi(msg) => &[msg.rsi_period, msg.stoch_period, msg.k_smoothing, msg.d_period],
+            IndicatorOutput::ConnorsRsi(msg) => &[msg.rsi_period, msg.streak_period, msg.rank_period],
+            IndicatorOutput::Atr(msg) => &[msg.period],
+            IndicatorOutput::Keltner(msg) => &[msg.ema_period, msg.atr_period],
+            IndicatorOutput::Donchian(msg) => &[msg.period],
+            IndicatorOutput::Cci(msg) => &[msg.period],
+            IndicatorOutput::WilliamsR(msg) => &[msg.period],
+            IndicatorOutput::SuperTrend(msg) => &[msg.period],
+            IndicatorOutput::Adx(msg) => &[msg.period],
+            IndicatorOutput::Mfi(msg) => &[msg.period],
+            IndicatorOutput::Volatility(msg) => &[msg.period],
+            IndicatorOutput::Liquidity(msg) => &[msg.period],
+            IndicatorOutput::Divergence(msg) => &[msg.period],
+            IndicatorOutput::Crossover(msg) => &[msg.fast_period, msg.slow_period],
+            IndicatorOutput::Flow(msg) => &[msg.window],
+            IndicatorOutput::SignalChange(msg) => &[msg.period],
+            IndicatorOutput::MovingAverage(_)
+            | IndicatorOutput::Momentum(_)
+            | IndicatorOutput::ParabolicSar(_)
+            | IndicatorOutput::Obv(_)
+            | IndicatorOutput::Anomaly(_)
+            | IndicatorOutput::Whale(_)
+            | IndicatorOutput::Candle(_)
+            | IndicatorOutput::Quarantine(_) => &[],
+        };
+        if periods.is_empty() {
+            return None;
+        }
+        let periods: Vec<String> = periods.iter().map(usize::to_string).collect();
+        Some(periods.join("-"))
+    }
+
+    /// Timeframe of the message's series: "tick" or a candle interval such
+    /// as "1m"; absent when the message does not say
+    pub fn timeframe(&self) -> Option<String> {
+        let interval_secs = match self {
+            IndicatorOutput::Rsi(msg) => return Some(msg.timeframe.clone()),
+            IndicatorOutput::SignalChange(msg) => return Some(msg.timeframe.clone()),
+            IndicatorOutput::Atr(msg) => msg.interval_secs,
+            IndicatorOutput::Keltner(msg) => msg.interval_secs,
+            IndicatorOutput::Donchian(msg) => msg.interval_secs,
+            IndicatorOutput::Cci(msg) => msg.interval_secs,
+            IndicatorOutput::WilliamsR(msg) => msg.interval_secs,
+            IndicatorOutput::ParabolicSar(msg) => msg.interval_secs,
+            IndicatorOutput::SuperTrend(msg) => msg.interval_secs,
+            IndicatorOutput::Adx(msg) => msg.interval_secs,
+            IndicatorOutput::Mfi(msg) => msg.interval_secs,
+            IndicatorOutput::Obv(msg) => msg.interval_secs,
+            IndicatorOutput::Volatility(msg) => msg.interval_secs,
+            IndicatorOutput::Liquidity(msg) => msg.interval_secs,
+            IndicatorOutput::Candle(msg) => msg.interval_secs,
+            IndicatorOutput::Crossover(msg) => msg.interval_secs,
+            _ => return None,
+        };
+        Some(crate::candles::format_interval(interval_secs))
+    }
+
+    /// Kafka key from `kafka.key_format`, filling in `{token}`,
+    /// `{indicator}`, `{period}` and `{timeframe}`; parts the message has
+    /// no value for are left empty. `{token}` is the series key, so series
+    /// in quotes other than SOL keep keys of their own.
+    pub fn key(&self, format: &str) -> String {
+        let mut key = format.replace("{token}", &self.series_key());
+        if key.contains("{indicator}") {
+            key = key.replace("{indicator}", self.kind());
+        }
+        if key.contains("{period}") {
+            key = key.replace("{period}", &self.period().unwrap_or_default());
+        }
+        if key.contains("{timeframe}") {
+            key = key.replace("{timeframe}", &self.timeframe().unwrap_or_default());
+        }
+        key
+    }
+
+    /// Serialize the inner message to JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Serialize the inner message to MessagePack, with field names
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        rmp_serde::to_vec_named(self)
+    }
+}
+
+/// Serializes as the inner message, RSI in the shape of its `schema_version`
+impl Serialize for IndicatorOutput {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            IndicatorOutput::Rsi(msg) => crate::schema::serialize_rsi(msg, serializer),
+            IndicatorOutput::MovingAverage(msg) => msg.serialize(serializer),
+            IndicatorOutput::Macd(msg) => msg.serialize(serializer),
+            IndicatorOutput::Bollinger(msg) => msg.serialize(serializer),
+            IndicatorOutput::ZScore(msg) => msg.serialize(serializer),
+            IndicatorOutput::Momentum(msg) => msg.serialize(serializer),
+            IndicatorOutput::Stochastic(msg) => msg.serialize(serializer),
+            IndicatorOutput::StochRsi(msg) => msg.serialize(serializer),
+            IndicatorOutput::ConnorsRsi(msg) => msg.serialize(serializer),
+            IndicatorOutput::Atr(msg) => msg.serialize(serializer),
+            IndicatorOutput::Keltner(msg) => msg.serialize(serializer),
+            IndicatorOutput::Donchian(msg) => msg.serialize(serializer),
+            IndicatorOutput::Cci(msg) => msg.serialize(serializer),
+            IndicatorOutput::WilliamsR(msg) => msg.serialize(serializer),
+            IndicatorOutput::ParabolicSar(msg) => msg.serialize(serializer),
+            IndicatorOutput::SuperTrend(msg) => msg.serialize(serializer),
+            IndicatorOutput::Adx(msg) => msg.serialize(serializer),
+            IndicatorOutput::Mfi(msg) => msg.serialize(serializer),
+            IndicatorOutput::Obv(msg) => msg.serialize(serializer),
+            IndicatorOutput::Volatility(msg) => msg.serialize(serializer),
+            IndicatorOutput::Liquidity(msg) => msg.serialize(serializer),
+            IndicatorOutput::Candle(msg) => msg.serialize(serializer),
+            IndicatorOutput::Divergence(msg) => msg.serialize(serializer),
+            IndicatorOutput::Crossover(msg) => msg.serialize(serializer),
+            IndicatorOutput::Flow(msg) => msg.serialize(serializer),
+            IndicatorOutput::Anomaly(msg) => msg.serialize(serializer),
+            IndicatorOutput::Whale(msg) => msg.serialize(serializer),
+            IndicatorOutput::SignalChange(msg) => msg.serialize(serializer),
+            IndicatorOutput::Quarantine(msg) => msg.serialize(serializer),
+        }
+    }
+}
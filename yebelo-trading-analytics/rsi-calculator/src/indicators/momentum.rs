@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{Indicator, IndicatorOutput, TradeInput};
+use crate::config::MomentumConfig;
+use crate::PriceHistory;
+
+/// Rate of change and momentum published for one token after a trade
+#[derive(Debug, Serialize)]
+pub struct MomentumMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    pub current_price: f64,
+    pub timestamp: String,
+    /// Percentage change over the last N prices, keyed by N
+    pub roc: BTreeMap<usize, f64>,
+    /// Raw price change over the last N prices, keyed by N
+    pub momentum: BTreeMap<usize, f64>,
+}
+
+/// Calculate ROC and momentum for every configured period with enough data
+pub fn calculate(
+    history: &PriceHistory,
+    config: &MomentumConfig,
+    token_address: &str,
+    price: f64,
+    timestamp: &str,
+) -> Option<MomentumMessage> {
+    let mut roc = BTreeMap::new();
+    let mut momentum = BTreeMap::new();
+
+    for &period in &config.periods {
+        let Some(past) = history.price_ago(period) else {
+            continue;
+        };
+        momentum.insert(period, price - past);
+        // ROC is undefined against a zero price
+        if past != 0.0 {
+            roc.insert(period, (price - past) / past * 100.0);
+        }
+    }
+
+    if momentum.is_empty() {
+        return None;
+    }
+
+    Some(MomentumMessage {
+        token_address: token_address.to_string(),
+        quote_currency: String::new(),
+        current_price: price,
+        timestamp: timestamp.to_string(),
+        roc,
+        momentum,
+    })
+}
+
+/// ROC and momentum as a per-token indicator; stateless apart from its settings,
+/// since everything it needs is in the token's price history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Momentum {
+    config: MomentumConfig,
+}
+
+impl Momentum {
+    pub fn new(config: &MomentumConfig) -> Self {
+        Self { config: config.clone() }
+    }
+}
+
+impl Indicator for Momentum {
+    fn on_trade(&mut self, trade: &TradeInput) -> Option<IndicatorOutput> {
+        calculate(trade.history, &self.config, trade.token_address, trade.price, trade.timestamp)
+            .map(IndicatorOutput::Momentum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::CLOSES;
+    use crate::{Arithmetic, RsiWeighting};
+
+    #[test]
+    fn roc_and_momentum_match_known_values() {
+        let config = MomentumConfig {
+            periods: vec![1, 10, 30],
+            ..MomentumConfig::default()
+        };
+        let mut history = PriceHistory::new(100, &[], RsiWeighting::Equal, Arithmetic::Float);
+        for close in CLOSES {
+            history.add_price(close, 1.0);
+        }
+        let price = CLOSES[CLOSES.len() - 1];
+        let message = calculate(&history, &config, "token", price, "").unwrap();
+
+        // 46.2122 against 45.6439 one close back and 45.8931 ten back
+        assert!((message.momentum[&1] - 0.5683).abs() < 1e-3);
+        assert!((message.roc[&1] - 1.2451).abs() < 1e-3);
+        assert!((message.momentum[&10] - 0.3191).abs() < 1e-3);
+        assert!((message.roc[&10] - 0.6953).abs() < 1e-3);
+        // Not enough closes for the 30-period lookback yet
+        assert!(!message.momentum.contains_key(&30));
+    }
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+use super::{Indicator, IndicatorOutput, TradeInput};
+use crate::config::MovingAverageConfig;
+
+/// Moving averages published for one token after a trade
+#[derive(Debug, Serialize)]
+pub struct MaMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    pub current_price: f64,
+    pub timestamp: String,
+    /// SMA values keyed by period (only periods with enough data)
+    pub sma: BTreeMap<usize, f64>,
+    /// EMA values keyed by period (only periods with enough data)
+    pub ema: BTreeMap<usize, f64>,
+}
+
+/// Simple moving average over a fixed window of values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sma {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            window: VecDeque::with_capacity(period + 1),
+            sum: 0.0,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Add a value and return the average once the window is full
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        self.window.push_back(value);
+        self.sum += value;
+
+        if self.window.len() > self.period {
+            if let Some(oldest) = self.window.pop_front() {
+                self.sum -= oldest;
+            }
+        }
+
+        self.value()
+    }
+
+    /// Current average, once `period` values have been seen
+    pub fn value(&self) -> Option<f64> {
+        if self.window.len() < self.period {
+            return None;
+        }
+
+        Some(self.sum / self.period as f64)
+    }
+}
+
+/// Exponential moving average, seeded with the SMA of the first `period` values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ema {
+    period: usize,
+    alpha: f64,
+    seed_sum: f64,
+    seed_count: usize,
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            alpha: 2.0 / (period as f64 + 1.0),
+            seed_sum: 0.0,
+            seed_count: 0,
+            value: None,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Add a value and return the average once it has been seeded
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        match self.value {
+            Some(prev) => {
+                // EMA = alpha * value + (1 - alpha) * previous EMA
+                self.value = Some(self.alpha * value + (1.0 - self.alpha) * prev);
+            }
+            None => {
+                self.seed_sum += value;
+                self.seed_count += 1;
+
+                if self.seed_count == self.period {
+                    self.value = Some(self.seed_sum / self.period as f64);
+                }
+            }
+        }
+
+        self.value
+    }
+}
+
+/// Per-token SMA/EMA state for all configured periods
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MovingAverages {
+    sma: Vec<Sma>,
+    ema: Vec<Ema>,
+}
+
+impl MovingAverages {
+    pub fn new(config: &MovingAverageConfig) -> Self {
+        Self {
+            sma: config.sma_periods.iter().map(|&p| Sma::new(p)).collect(),
+            ema: config.ema_periods.iter().map(|&p| Ema::new(p)).collect(),
+        }
+    }
+
+    /// Feed a new price and build a message if any average is ready
+    pub fn update(&mut self, token_address: &str, price: f64, timestamp: &str) -> Option<MaMessage> {
+        let sma: BTreeMap<usize, f64> = self
+            .sma
+            .iter_mut()
+            .filter_map(|sma| Some((sma.period(), sma.update(price)?)))
+            .collect();
+        let ema: BTreeMap<usize, f64> = self
+            .ema
+            .iter_mut()
+            .filter_map(|ema| Some((ema.period(), ema.update(price)?)))
+            .collect();
+
+        if sma.is_empty() && ema.is_empty() {
+            return None;
+        }
+
+        Some(MaMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            current_price: price,
+            timestamp: timestamp.to_string(),
+            sma,
+            ema,
+        })
+    }
+}
+
+impl Indicator for MovingAverages {
+    fn on_trade(&mut self, trade: &TradeInput) -> Option<IndicatorOutput> {
+        self.update(trade.token_address, trade.price, trade.timestamp).map(IndicatorOutput::MovingAverage)
+    }
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Indicator, IndicatorOutput};
+use crate::candles::Candle;
+use crate::config::ObvConfig;
+
+/// On-Balance Volume published for one token when a candle closes
+#[derive(Debug, Serialize)]
+pub struct ObvMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Running volume: added on up closes, subtracted on down closes
+    pub obv: f64,
+    /// The candle's own volume (sum of `amount_in_sol`)
+    pub volume: f64,
+    pub close: f64,
+    /// Candle close time (RFC 3339)
+    pub timestamp: String,
+    pub interval_secs: i64,
+    /// Start of the session OBV has accumulated over, when sessions are set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_start: Option<String>,
+}
+
+/// Per-token OBV state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Obv {
+    session_secs: Option<i64>,
+    prev_close: Option<f64>,
+    obv: f64,
+    // Start of the current session (Unix seconds), when sessions are set
+    session: Option<i64>,
+}
+
+impl Obv {
+    pub fn new(config: &ObvConfig) -> Self {
+        Self {
+            session_secs: config.session_secs,
+            prev_close: None,
+            obv: 0.0,
+            session: None,
+        }
+    }
+
+    /// Feed a completed candle and build a message once there is a previous
+    /// close to compare against
+    pub fn update(&mut self, token_address: &str, candle: &Candle) -> Option<ObvMessage> {
+        // A new session starts OBV from zero again
+        let session = self.session_secs.map(|secs| candle.start_time.div_euclid(secs) * secs);
+        if session != self.session {
+            self.session = session;
+            self.obv = 0.0;
+        }
+
+        let prev_close = self.prev_close.replace(candle.close)?;
+        if candle.close > prev_close {
+            self.obv += candle.volume;
+        } else if candle.close < prev_close {
+            self.obv -= candle.volume;
+        }
+
+        Some(ObvMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            obv: self.obv,
+            volume: candle.volume,
+            close: candle.close,
+            timestamp: crate::format_unix_time(candle.end_time()),
+            interval_secs: candle.interval_secs,
+            session_start: self.session.map(crate::format_unix_time),
+        })
+    }
+}
+
+impl Indicator for Obv {
+    fn on_bar(&mut self, token_address: &str, candle: &Candle) -> Option<IndicatorOutput> {
+        self.update(token_address, candle).map(IndicatorOutput::Obv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::bars;
+
+    #[test]
+    fn obv_matches_known_values() {
+        let mut obv = Obv::new(&ObvConfig::default());
+        let values: Vec<f64> =
+            bars().iter().filter_map(|bar| obv.update("token", bar)).map(|message| message.obv).collect();
+
+        // Volume added on up closes and subtracted on down closes
+        assert_eq!(values.len(), 30 - 1);
+        for (value, expected) in values.iter().zip([27.8, 18.7, 33.7, 56.6, 74.6, 32.9]) {
+            assert!((value - expected).abs() < 1e-6, "OBV {} instead of {}", value, expected);
+        }
+        assert!((values[28] - -210.6).abs() < 1e-6, "OBV {}", values[28]);
+    }
+
+    #[test]
+    fn sessions_restart_from_zero() {
+        let config = ObvConfig {
+            session_secs: Some(600),
+            ..ObvConfig::default()
+        };
+        let mut obv = Obv::new(&config);
+        let messages: Vec<ObvMessage> = bars().iter().filter_map(|bar| obv.update("token", bar)).collect();
+
+        // The 11th bar opens the second ten-minute session and rises by 36.5
+        assert!((messages[8].obv - 56.8).abs() < 1e-6);
+        assert!((messages[9].obv - 36.5).abs() < 1e-6);
+        assert_eq!(messages[9].session_start.as_deref(), Some(crate::format_unix_time(600).as_str()));
+    }
+}
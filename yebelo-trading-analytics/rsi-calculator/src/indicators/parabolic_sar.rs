@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Indicator, IndicatorOutput};
+use crate::candles::Candle;
+use crate::config::ParabolicSarConfig;
+
+/// Parabolic SAR published for one token when a candle closes
+#[derive(Debug, Serialize)]
+pub struct ParabolicSarMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Stop-and-reverse level for the next candle
+    pub sar: f64,
+    /// "up" (SAR below price) or "down" (SAR above price)
+    pub trend: String,
+    /// The trend flipped on this candle
+    pub reversed: bool,
+    /// Current acceleration factor
+    pub acceleration: f64,
+    pub close: f64,
+    /// Candle close time (RFC 3339)
+    pub timestamp: String,
+    pub interval_secs: i64,
+}
+
+/// Trend being tracked once two candles have been seen
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Trend {
+    up: bool,
+    sar: f64,
+    // Highest high of an uptrend, lowest low of a downtrend
+    extreme: f64,
+    acceleration: f64,
+}
+
+/// Per-token Parabolic SAR state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParabolicSar {
+    start: f64,
+    step: f64,
+    max: f64,
+    // (high, low) of the previous two candles, most recent last
+    previous: Vec<(f64, f64)>,
+    prev_close: Option<f64>,
+    trend: Option<Trend>,
+}
+
+impl ParabolicSar {
+    pub fn new(config: &ParabolicSarConfig) -> Self {
+        Self {
+            start: config.acceleration_start,
+            step: config.acceleration_step,
+            max: config.acceleration_max,
+            previous: Vec::with_capacity(3),
+            prev_close: None,
+            trend: None,
+        }
+    }
+
+    /// Feed a completed candle and build a message from the second candle on
+    pub fn update(&mut self, token_address: &str, candle: &Candle) -> Option<ParabolicSarMessage> {
+        let prev_close = self.prev_close.replace(candle.close);
+        let (trend, reversed) = match (self.trend, prev_close) {
+            (Some(trend), _) => self.advance(trend, candle),
+            // The first move sets the initial direction
+            (None, Some(prev_close)) => {
+                let up = candle.close >= prev_close;
+                let (prev_high, prev_low) = self.previous.last().copied()?;
+                let trend = Trend {
+                    up,
+                    sar: if up { prev_low.min(candle.low) } else { prev_high.max(candle.high) },
+                    extreme: if up { candle.high } else { candle.low },
+                    acceleration: self.start,
+                };
+                (trend, false)
+            }
+            (None, None) => {
+                self.remember(candle);
+                return None;
+            }
+        };
+        self.trend = Some(trend);
+        self.remember(candle);
+
+        Some(ParabolicSarMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            sar: trend.sar,
+            trend: if trend.up { "up" } else { "down" }.to_string(),
+            reversed,
+            acceleration: trend.acceleration,
+            close: candle.close,
+            timestamp: crate::format_unix_time(candle.end_time()),
+            interval_secs: candle.interval_secs,
+        })
+    }
+
+    /// Move the SAR towards the extreme point, reversing if the candle
+    /// crossed it
+    fn advance(&self, trend: Trend, candle: &Candle) -> (Trend, bool) {
+        let mut sar = trend.sar + trend.acceleration * (trend.extreme - trend.sar);
+        // The SAR may never move into the previous two candles' range
+        for &(high, low) in &self.previous {
+            sar = if trend.up { sar.min(low) } else { sar.max(high) };
+        }
+
+        let crossed = if trend.up { candle.low < sar } else { candle.high > sar };
+        if crossed {
+            let reversed = Trend {
+                up: !trend.up,
+                sar: trend.extreme,
+                extreme: if trend.up { candle.low } else { candle.high },
+                acceleration: self.start,
+            };
+            return (reversed, true);
+        }
+
+        let new_extreme = if trend.up { candle.high > trend.extreme } else { candle.low < trend.extreme };
+        let (extreme, acceleration) = if new_extreme {
+            let extreme = if trend.up { candle.high } else { candle.low };
+            (extreme, (trend.acceleration + self.step).min(self.max))
+        } else {
+            (trend.extreme, trend.acceleration)
+        };
+        let trend = Trend {
+            up: trend.up,
+            sar,
+            extreme,
+            acceleration,
+        };
+        (trend, false)
+    }
+
+    fn remember(&mut self, candle: &Candle) {
+        self.previous.push((candle.high, candle.low));
+        if self.previous.len() > 2 {
+            self.previous.remove(0);
+        }
+    }
+}
+
+impl Indicator for ParabolicSar {
+    fn on_bar(&mut self, token_address: &str, candle: &Candle) -> Option<IndicatorOutput> {
+        self.update(token_address, candle).map(IndicatorOutput::ParabolicSar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::bars;
+
+    #[test]
+    fn parabolic_sar_matches_known_values() {
+        let mut psar = ParabolicSar::new(&ParabolicSarConfig::default());
+        let messages: Vec<ParabolicSarMessage> = bars().iter().filter_map(|bar| psar.update("token", bar)).collect();
+
+        assert_eq!(messages.len(), 30 - 1);
+        // The first rise starts an uptrend from the lower of the two lows
+        assert_eq!((messages[0].sar, messages[0].trend.as_str()), (19.59, "up"));
+        assert!((messages[2].sar - 19.6412).abs() < 1e-3, "SAR {}", messages[2].sar);
+        assert!((messages[14].sar - 23.32).abs() < 1e-9, "SAR {}", messages[14].sar);
+        assert!((messages[14].acceleration - 0.2).abs() < 1e-9);
+
+        // The 17th bar breaks the SAR, which restarts from the extreme high
+        let reversal = &messages[15];
+        assert!(reversal.reversed);
+        assert_eq!((reversal.sar, reversal.trend.as_str()), (25.0, "down"));
+        assert!((reversal.acceleration - 0.02).abs() < 1e-9);
+        assert_eq!(messages.iter().filter(|message| message.reversed).count(), 1);
+
+        let last = messages.last().unwrap();
+        assert!((last.sar - 18.9617).abs() < 1e-3, "SAR {}", last.sar);
+    }
+}
@@ -0,0 +1,461 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Adx, Anomaly, Atr, Bollinger, Cci, ConnorsRsi, Crossover, Divergence, Donchian, Flow, Indicator, IndicatorOutput,
+    Keltner, Liquidity, Macd, Mfi, Momentum, MovingAverages, Obv, ParabolicSar, RsiInput, StochRsi, Stochastic,
+    SuperTrend, TradeInput, Volatility, Whale, WilliamsR, ZScore,
+};
+use crate::candles::Candle;
+use crate::config::{
+    AdxConfig, AnomalyConfig, AtrConfig, BollingerConfig, CciConfig, Config, ConnorsRsiConfig, CrossoverConfig,
+    DivergenceConfig, DonchianConfig, FlowConfig, KeltnerConfig, LiquidityConfig, MacdConfig, MfiConfig, MomentumConfig,
+    MovingAverageConfig, ObvConfig, ParabolicSarConfig, StochRsiConfig, StochasticConfig, SuperTrendConfig,
+    VolatilityConfig, WhaleConfig, WilliamsRConfig, ZScoreConfig,
+};
+
+/// Declares every indicator type a token's state can hold, keeping instances
+/// serializable for checkpoints while they are driven through [`Indicator`]
+macro_rules! indicator_kinds {
+    ($($variant:ident($ty:ty)),* $(,)?) => {
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        enum AnyIndicator {
+            $($variant($ty)),*
+        }
+
+        impl AnyIndicator {
+            fn get(&mut self) -> &mut dyn Indicator {
+                match self {
+                    $(AnyIndicator::$variant(indicator) => indicator),*
+                }
+            }
+        }
+
+        $(
+            impl From<$ty> for AnyIndicator {
+                fn from(indicator: $ty) -> Self {
+                    AnyIndicator::$variant(indicator)
+                }
+            }
+        )*
+    };
+}
+
+indicator_kinds! {
+    MovingAverages(MovingAverages),
+    Macd(Macd),
+    Bollinger(Bollinger),
+    ZScore(ZScore),
+    Momentum(Momentum),
+    Stochastic(Stochastic),
+    StochRsi(StochRsi),
+    ConnorsRsi(ConnorsRsi),
+    Atr(Atr),
+    Keltner(Keltner),
+    Donchian(Donchian),
+    Cci(Cci),
+    WilliamsR(WilliamsR),
+    ParabolicSar(ParabolicSar),
+    SuperTrend(SuperTrend),
+    Adx(Adx),
+    Mfi(Mfi),
+    Obv(Obv),
+    Volatility(Volatility),
+    Liquidity(Liquidity),
+    Divergence(Divergence),
+    Crossover(Crossover),
+    Flow(Flow),
+    Anomaly(Anomaly),
+    Whale(Whale),
+}
+
+/// One indicator kept in a token's state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicatorInstance {
+    // Candle interval fed to `on_bar`; None for indicators without candles
+    interval_secs: Option<i64>,
+    indicator: AnyIndicator,
+}
+
+impl IndicatorInstance {
+    /// An indicator fed by trades and/or the RSI series only
+    fn streaming(indicator: impl Into<AnyIndicator>) -> Self {
+        Self {
+            interval_secs: None,
+            indicator: indicator.into(),
+        }
+    }
+
+    /// An indicator fed with finalized candles of one interval
+    fn candles(interval_secs: i64, indicator: impl Into<AnyIndicator>) -> Self {
+        Self {
+            interval_secs: Some(interval_secs),
+            indicator: indicator.into(),
+        }
+    }
+
+    pub fn on_trade(&mut self, trade: &TradeInput) -> Option<IndicatorOutput> {
+        self.indicator.get().on_trade(trade)
+    }
+
+    pub fn on_rsi(&mut self, input: &RsiInput) -> Option<IndicatorOutput> {
+        self.indicator.get().on_rsi(input)
+    }
+
+    /// Feed a finalized candle, if it has the interval this indicator runs on
+    pub fn on_bar(&mut self, token_address: &str, candle: &Candle) -> Option<IndicatorOutput> {
+        if self.interval_secs != Some(candle.interval_secs) {
+            return None;
+        }
+        self.indicator.get().on_bar(token_address, candle)
+    }
+}
+
+/// Indicator settings from config, used to instantiate the indicators of
+/// every newly seen token
+///
+/// Adding an indicator means implementing [`Indicator`], listing it in
+/// `indicator_kinds!` and registering it in [`IndicatorRegistry::instances`].
+#[derive(Debug, Clone, Serialize)]
+pub struct IndicatorRegistry {
+    moving_averages: MovingAverageConfig,
+    macd: MacdConfig,
+    bollinger: BollingerConfig,
+    zscore: ZScoreConfig,
+    momentum: MomentumConfig,
+    stochastic: StochasticConfig,
+    stoch_rsi: StochRsiConfig,
+    connors_rsi: ConnorsRsiConfig,
+    atr: AtrConfig,
+    keltner: KeltnerConfig,
+    donchian: DonchianConfig,
+    cci: CciConfig,
+    williams_r: WilliamsRConfig,
+    parabolic_sar: ParabolicSarConfig,
+    supertrend: SuperTrendConfig,
+    adx: AdxConfig,
+    mfi: MfiConfig,
+    obv: ObvConfig,
+    volatility: VolatilityConfig,
+    liquidity: LiquidityConfig,
+    divergence: DivergenceConfig,
+    crossover: CrossoverConfig,
+    flow: FlowConfig,
+    anomaly: AnomalyConfig,
+    whale: WhaleConfig,
+}
+
+impl IndicatorRegistry {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            moving_averages: config.moving_averages.clone(),
+            macd: config.macd.clone(),
+            bollinger: config.bollinger.clone(),
+            zscore: config.zscore.clone(),
+            momentum: config.momentum.clone(),
+            stochastic: config.stochastic.clone(),
+            stoch_rsi: config.stoch_rsi.clone(),
+            connors_rsi: config.connors_rsi.clone(),
+            atr: config.atr.clone(),
+            keltner: config.keltner.clone(),
+            donchian: config.donchian.clone(),
+            cci: config.cci.clone(),
+            williams_r: config.williams_r.clone(),
+            parabolic_sar: config.parabolic_sar.clone(),
+            supertrend: config.supertrend.clone(),
+            adx: config.adx.clone(),
+            mfi: config.mfi.clone(),
+            obv: config.obv.clone(),
+            volatility: config.volatility.clone(),
+            liquidity: config.liquidity.clone(),
+            divergence: config.divergence.clone(),
+            crossover: config.crossover.clone(),
+            flow: config.flow.clone(),
+            anomaly: config.anomaly.clone(),
+            whale: config.whale.clone(),
+        }
+    }
+
+    /// Every enabled indicator, freshly instantiated for a token
+    pub fn instances(&self, token_address: &str) -> Vec<IndicatorInstance> {
+        [
+            self.divergence.enabled.then(|| IndicatorInstance::streaming(Divergence::new(&self.divergence))),
+            self.stoch_rsi.enabled.then(|| IndicatorInstance::streaming(StochRsi::new(&self.stoch_rsi))),
+            self.moving_averages
+                .enabled
+                .then(|| IndicatorInstance::streaming(MovingAverages::new(&self.moving_averages))),
+            self.macd.enabled.then(|| IndicatorInstance::streaming(Macd::new(&self.macd))),
+            self.bollinger.enabled.then(|| IndicatorInstance::streaming(Bollinger::new(&self.bollinger))),
+            self.zscore.enabled.then(|| IndicatorInstance::streaming(ZScore::new(&self.zscore))),
+            self.momentum.enabled.then(|| IndicatorInstance::streaming(Momentum::new(&self.momentum))),
+            self.stochastic.enabled.then(|| IndicatorInstance::streaming(Stochastic::new(&self.stochastic))),
+            self.connors_rsi.enabled.then(|| IndicatorInstance::streaming(ConnorsRsi::new(&self.connors_rsi))),
+            self.flow.enabled.then(|| IndicatorInstance::streaming(Flow::new(&self.flow))),
+            self.anomaly.enabled.then(|| IndicatorInstance::streaming(Anomaly::new(&self.anomaly))),
+            self.whale.enabled.then(|| IndicatorInstance::streaming(Whale::new(&self.whale, token_address))),
+            self.atr.enabled.then(|| IndicatorInstance::candles(self.atr.interval_secs, Atr::new(&self.atr))),
+            self.keltner
+                .enabled
+                .then(|| IndicatorInstance::candles(self.keltner.interval_secs, Keltner::new(&self.keltner))),
+            self.donchian.enabled.then(|| {
+                let donchian = Donchian::new(self.donchian.period_for(token_address), self.donchian.breakouts_only);
+                IndicatorInstance::candles(self.donchian.interval_secs, donchian)
+            }),
+            self.cci.enabled.then(|| IndicatorInstance::candles(self.cci.interval_secs, Cci::new(&self.cci))),
+            self.williams_r
+                .enabled
+                .then(|| IndicatorInstance::candles(self.williams_r.interval_secs, WilliamsR::new(&self.williams_r))),
+            self.parabolic_sar.enabled.then(|| {
+                IndicatorInstance::candles(self.parabolic_sar.interval_secs, ParabolicSar::new(&self.parabolic_sar))
+            }),
+            self.supertrend
+                .enabled
+                .then(|| IndicatorInstance::candles(self.supertrend.interval_secs, SuperTrend::new(&self.supertrend))),
+            self.adx.enabled.then(|| IndicatorInstance::candles(self.adx.interval_secs, Adx::new(&self.adx))),
+            self.mfi.enabled.then(|| IndicatorInstance::candles(self.mfi.interval_secs, Mfi::new(&self.mfi))),
+            self.obv.enabled.then(|| IndicatorInstance::candles(self.obv.interval_secs, Obv::new(&self.obv))),
+            self.volatility
+                .enabled
+                .then(|| IndicatorInstance::candles(self.volatility.interval_secs, Volatility::new(&self.volatility))),
+            self.liquidity
+                .enabled
+                .then(|| IndicatorInstance::candles(self.liquidity.interval_secs, Liquidity::new(&self.liquidity))),
+            self.crossover
+                .enabled
+                .then(|| IndicatorInstance::candles(self.crossover.interval_secs, Crossover::new(&self.crossover))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Candle intervals the enabled indicators run on
+    pub fn candle_intervals(&self) -> Vec<i64> {
+        // Token-specific settings never change an indicator's interval
+        self.instances("").iter().filter_map(|instance| instance.interval_secs).collect()
+    }
+
+    /// Raw prices a token's history must keep for trade-driven indicators
+    pub fn history_len(&self) -> usize {
+        let mut longest = 0;
+        if self.bollinger.enabled {
+            longest = longest.max(self.bollinger.period);
+        }
+        if self.zscore.enabled {
+            longest = longest.max(self.zscore.period);
+        }
+        if self.stochastic.enabled {
+            longest = longest.max(self.stochastic.k_period);
+        }
+        if self.momentum.enabled {
+            // ROC compares against the price `period` trades back
+            longest = longest.max(self.momentum.periods.iter().copied().max().unwrap_or(0) + 1);
+        }
+        longest
+    }
+
+    /// Log the settings of every enabled indicator at startup
+    pub fn log_enabled(&self) {
+        if self.moving_averages.enabled {
+            info!(
+                "📊 Publishing SMA {:?} / EMA {:?} to '{}'",
+                self.moving_averages.sma_periods,
+                self.moving_averages.ema_periods,
+                self.moving_averages.topic
+            );
+        }
+        if self.macd.enabled {
+            info!(
+                "📊 Publishing MACD({}, {}, {}) to '{}'",
+                self.macd.fast_period,
+                self.macd.slow_period,
+                self.macd.signal_period,
+                self.macd.topic
+            );
+        }
+        if self.bollinger.enabled {
+            info!(
+                "📊 Publishing Bollinger Bands({}, {}) to '{}'",
+                self.bollinger.period,
+                self.bollinger.std_dev_multiplier,
+                self.bollinger.topic
+            );
+        }
+        if self.zscore.enabled {
+            info!(
+                "📊 Publishing price z-score({}, ±{}) to '{}'",
+                self.zscore.period, self.zscore.threshold, self.zscore.topic
+            );
+        }
+        if self.momentum.enabled {
+            info!("📊 Publishing ROC/momentum {:?} to '{}'", self.momentum.periods, self.momentum.topic);
+        }
+        if self.stochastic.enabled {
+            info!(
+                "📊 Publishing Stochastic({}, {}, {}) to '{}'",
+                self.stochastic.k_period,
+                self.stochastic.k_smoothing,
+                self.stochastic.d_period,
+                self.stochastic.topic
+            );
+        }
+        if self.stoch_rsi.enabled {
+            info!(
+                "📊 Publishing StochRSI({}, {}, {}, {}) to '{}'",
+                self.stoch_rsi.rsi_period,
+                self.stoch_rsi.stoch_period,
+                self.stoch_rsi.k_smoothing,
+                self.stoch_rsi.d_period,
+                self.stoch_rsi.topic
+            );
+        }
+        if self.connors_rsi.enabled {
+            info!(
+                "📊 Publishing Connors RSI({}, {}, {}) to '{}'",
+                self.connors_rsi.rsi_period,
+                self.connors_rsi.streak_period,
+                self.connors_rsi.rank_period,
+                self.connors_rsi.topic
+            );
+        }
+        if self.atr.enabled {
+            info!(
+                "📊 Publishing ATR({}) on {}s candles to '{}'",
+                self.atr.period,
+                self.atr.interval_secs,
+                self.atr.topic
+            );
+        }
+        if self.keltner.enabled {
+            info!(
+                "📊 Publishing Keltner Channels(EMA {}, ATR {} × {}) on {}s candles to '{}'",
+                self.keltner.ema_period,
+                self.keltner.atr_period,
+                self.keltner.multiplier,
+                self.keltner.interval_secs,
+                self.keltner.topic
+            );
+        }
+        if self.donchian.enabled {
+            info!(
+                "📊 Publishing Donchian Channels({}){} on {}s candles to '{}'",
+                self.donchian.period,
+                if self.donchian.breakouts_only { " breakouts" } else { "" },
+                self.donchian.interval_secs,
+                self.donchian.topic
+            );
+        }
+        if self.cci.enabled {
+            info!(
+                "📊 Publishing CCI({}) on {}s candles to '{}'",
+                self.cci.period,
+                self.cci.interval_secs,
+                self.cci.topic
+            );
+        }
+        if self.williams_r.enabled {
+            info!(
+                "📊 Publishing Williams %R({}) on {}s candles to '{}'",
+                self.williams_r.period,
+                self.williams_r.interval_secs,
+                self.williams_r.topic
+            );
+        }
+        if self.parabolic_sar.enabled {
+            info!(
+                "📊 Publishing Parabolic SAR({}, {}, {}) on {}s candles to '{}'",
+                self.parabolic_sar.acceleration_start,
+                self.parabolic_sar.acceleration_step,
+                self.parabolic_sar.acceleration_max,
+                self.parabolic_sar.interval_secs,
+                self.parabolic_sar.topic
+            );
+        }
+        if self.supertrend.enabled {
+            info!(
+                "📊 Publishing SuperTrend({}, {}){} on {}s candles to '{}'",
+                self.supertrend.period,
+                self.supertrend.multiplier,
+                if self.supertrend.flips_only { " flips" } else { "" },
+                self.supertrend.interval_secs,
+                self.supertrend.topic
+            );
+        }
+        if self.adx.enabled {
+            info!(
+                "📊 Publishing ADX/DMI({}) on {}s candles to '{}'",
+                self.adx.period,
+                self.adx.interval_secs,
+                self.adx.topic
+            );
+        }
+        if self.mfi.enabled {
+            info!(
+                "📊 Publishing MFI({}) on {}s candles to '{}'",
+                self.mfi.period,
+                self.mfi.interval_secs,
+                self.mfi.topic
+            );
+        }
+        if self.obv.enabled {
+            info!("📊 Publishing OBV on {}s candles to '{}'", self.obv.interval_secs, self.obv.topic);
+        }
+        if self.volatility.enabled {
+            info!(
+                "📊 Publishing realized volatility({}) on {}s candles to '{}'",
+                self.volatility.period,
+                self.volatility.interval_secs,
+                self.volatility.topic
+            );
+        }
+        if self.liquidity.enabled {
+            info!(
+                "📊 Publishing turnover/liquidity over {} x {}s candles to '{}'",
+                self.liquidity.period,
+                self.liquidity.interval_secs,
+                self.liquidity.topic
+            );
+        }
+        if self.divergence.enabled {
+            info!(
+                "📐 Publishing RSI({}) divergences to '{}'",
+                self.divergence.period,
+                self.divergence.topic
+            );
+        }
+        if self.crossover.enabled {
+            info!(
+                "📐 Publishing {:?}({}) / {:?}({}) crossovers on {}s candles to '{}'",
+                self.crossover.average,
+                self.crossover.fast_period,
+                self.crossover.average,
+                self.crossover.slow_period,
+                self.crossover.interval_secs,
+                self.crossover.topic
+            );
+        }
+        if self.flow.enabled {
+            info!(
+                "📊 Publishing buy/sell pressure over {} trades to '{}'",
+                self.flow.window,
+                self.flow.topic
+            );
+        }
+        if self.anomaly.enabled {
+            info!(
+                "📊 Publishing price/volume anomalies beyond {}σ ({}σ critical) to '{}'",
+                self.anomaly.threshold,
+                self.anomaly.critical_threshold,
+                self.anomaly.topic
+            );
+        }
+        if self.whale.enabled {
+            info!(
+                "📊 Publishing whale trades (≥ {} SOL or above p{} of recent sizes) to '{}'",
+                self.whale.min_amount_sol,
+                self.whale.percentile,
+                self.whale.topic
+            );
+        }
+    }
+}
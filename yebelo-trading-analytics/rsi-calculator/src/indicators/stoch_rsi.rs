@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::moving_average::Sma;
+use super::{Indicator, IndicatorOutput, RsiInput};
+use crate::config::StochRsiConfig;
+use crate::{PriceHistory, Smoothing};
+
+/// Stochastic RSI values published for one token after an RSI update
+#[derive(Debug, Serialize)]
+pub struct StochRsiMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Smoothed %K of the RSI (0-100)
+    pub k: f64,
+    /// SMA of %K (0-100)
+    pub d: f64,
+    pub rsi_value: f64,
+    pub current_price: f64,
+    pub timestamp: String,
+    pub rsi_period: usize,
+    pub stoch_period: usize,
+    pub k_smoothing: usize,
+    pub d_period: usize,
+}
+
+/// Per-token StochRSI state: recent RSI values plus %K/%D smoothing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StochRsi {
+    rsi_period: usize,
+    stoch_period: usize,
+    rsi_values: VecDeque<f64>,
+    k_smoothing: Sma,
+    d: Sma,
+}
+
+impl StochRsi {
+    pub fn new(config: &StochRsiConfig) -> Self {
+        Self {
+            rsi_period: config.rsi_period,
+            stoch_period: config.stoch_period,
+            rsi_values: VecDeque::with_capacity(config.stoch_period + 1),
+            k_smoothing: Sma::new(config.k_smoothing),
+            d: Sma::new(config.d_period),
+        }
+    }
+
+    /// Feed the latest price (already added to `history`) and build a
+    /// message once %D is available
+    pub fn update(
+        &mut self,
+        history: &PriceHistory,
+        smoothing: Smoothing,
+        token_address: &str,
+        price: f64,
+        timestamp: &str,
+    ) -> Option<StochRsiMessage> {
+        let rsi = history.rsi(self.rsi_period, smoothing)?;
+
+        self.rsi_values.push_back(rsi);
+        if self.rsi_values.len() > self.stoch_period {
+            self.rsi_values.pop_front();
+        }
+        if self.rsi_values.len() < self.stoch_period {
+            return None;
+        }
+
+        // Where the RSI sits within its own recent range
+        let high = self.rsi_values.iter().copied().fold(f64::MIN, f64::max);
+        let low = self.rsi_values.iter().copied().fold(f64::MAX, f64::min);
+        let raw_k = if high > low {
+            (rsi - low) / (high - low) * 100.0
+        } else {
+            50.0 // Flat RSI, no direction
+        };
+
+        let k = self.k_smoothing.update(raw_k)?;
+        let d = self.d.update(k)?;
+
+        Some(StochRsiMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            k,
+            d,
+            rsi_value: rsi,
+            current_price: price,
+            timestamp: timestamp.to_string(),
+            rsi_period: self.rsi_period,
+            stoch_period: self.stoch_period,
+            k_smoothing: self.k_smoothing.period(),
+            d_period: self.d.period(),
+        })
+    }
+}
+
+impl Indicator for StochRsi {
+    fn on_rsi(&mut self, input: &RsiInput) -> Option<IndicatorOutput> {
+        self.update(input.history, input.smoothing, input.token_address, input.price, input.timestamp)
+            .map(IndicatorOutput::StochRsi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::CLOSES;
+    use crate::{Arithmetic, RsiWeighting};
+
+    #[test]
+    fn stoch_rsi_matches_known_values() {
+        let config = StochRsiConfig {
+            stoch_period: 3,
+            k_smoothing: 1,
+            ..StochRsiConfig::default()
+        };
+        let mut stoch_rsi = StochRsi::new(&config);
+        let mut history = PriceHistory::new(100, &[14], RsiWeighting::Equal, Arithmetic::Float);
+        let mut messages = Vec::new();
+        for close in CLOSES {
+            history.add_price(close, 1.0);
+            messages.extend(stoch_rsi.update(&history, Smoothing::Wilder, "token", close, ""));
+        }
+
+        // %K places each Wilder RSI (70.53, 66.32, 66.55, 69.41, 66.36, 57.97,
+        // 62.93) within the last 3; %D averages the last 3 %K values
+        let expected = [(0.0, 35.1626), (0.0, 33.3333), (59.1237, 19.7079)];
+        assert_eq!(messages.len(), expected.len());
+        for (message, (k, d)) in messages.iter().zip(expected) {
+            assert!((message.k - k).abs() < 1e-3, "%K {} instead of {}", message.k, k);
+            assert!((message.d - d).abs() < 1e-3, "%D {} instead of {}", message.d, d);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+use super::moving_average::Sma;
+use super::{Indicator, IndicatorOutput, TradeInput};
+use crate::config::StochasticConfig;
+use crate::PriceHistory;
+
+/// Stochastic oscillator values published for one token after a trade
+#[derive(Debug, Serialize)]
+pub struct StochasticMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Smoothed %K (0-100)
+    pub k: f64,
+    /// SMA of %K (0-100)
+    pub d: f64,
+    pub current_price: f64,
+    pub timestamp: String,
+    pub k_period: usize,
+    pub k_smoothing: usize,
+    pub d_period: usize,
+}
+
+/// Per-token stochastic state: smoothing averages for %K and %D
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stochastic {
+    k_period: usize,
+    k_smoothing: Sma,
+    d: Sma,
+}
+
+impl Stochastic {
+    pub fn new(config: &StochasticConfig) -> Self {
+        Self {
+            k_period: config.k_period,
+            k_smoothing: Sma::new(config.k_smoothing),
+            d: Sma::new(config.d_period),
+        }
+    }
+
+    /// Feed the latest price (already added to `history`) and build a
+    /// message once %D is available
+    pub fn update(
+        &mut self,
+        history: &PriceHistory,
+        token_address: &str,
+        price: f64,
+        timestamp: &str,
+    ) -> Option<StochasticMessage> {
+        let (high, low) = history.high_low(self.k_period)?;
+
+        // Raw %K: where the price sits within the lookback range
+        let raw_k = if high > low {
+            (price - low) / (high - low) * 100.0
+        } else {
+            50.0 // Flat range, no direction
+        };
+
+        let k = self.k_smoothing.update(raw_k)?;
+        let d = self.d.update(k)?;
+
+        Some(StochasticMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            k,
+            d,
+            current_price: price,
+            timestamp: timestamp.to_string(),
+            k_period: self.k_period,
+            k_smoothing: self.k_smoothing.period(),
+            d_period: self.d.period(),
+        })
+    }
+}
+
+impl Indicator for Stochastic {
+    fn on_trade(&mut self, trade: &TradeInput) -> Option<IndicatorOutput> {
+        self.update(trade.history, trade.token_address, trade.price, trade.timestamp).map(IndicatorOutput::Stochastic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::CLOSES;
+    use crate::{Arithmetic, RsiWeighting};
+
+    #[test]
+    fn slow_stochastic_matches_known_values() {
+        let mut stochastic = Stochastic::new(&StochasticConfig::default());
+        let mut history = PriceHistory::new(100, &[], RsiWeighting::Equal, Arithmetic::Float);
+        let mut messages = Vec::new();
+        for close in CLOSES {
+            history.add_price(close, 1.0);
+            messages.extend(stochastic.update(&history, "token", close, ""));
+        }
+
+        // 14-close range, %K smoothed over 3 and %D over 3 more
+        let expected = [(93.4023, 94.4394), (92.9057, 93.2368), (76.5735, 87.6272), (69.8400, 79.7731)];
+        assert_eq!(messages.len(), expected.len());
+        for (message, (k, d)) in messages.iter().zip(expected) {
+            assert!((message.k - k).abs() < 1e-3, "%K {} instead of {}", message.k, k);
+            assert!((message.d - d).abs() < 1e-3, "%D {} instead of {}", message.d, d);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+
+use super::atr::Atr;
+use super::{Indicator, IndicatorOutput};
+use crate::candles::Candle;
+use crate::config::{AtrConfig, SuperTrendConfig};
+
+/// SuperTrend published for one token when a candle closes
+#[derive(Debug, Serialize)]
+pub struct SuperTrendMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Trailing stop: the lower band in an uptrend, the upper band in a downtrend
+    pub supertrend: f64,
+    /// "up" or "down"
+    pub trend: String,
+    /// The trend flipped on this candle
+    pub flipped: bool,
+    pub upper_band: f64,
+    pub lower_band: f64,
+    pub close: f64,
+    /// Candle close time (RFC 3339)
+    pub timestamp: String,
+    pub period: usize,
+    pub multiplier: f64,
+    pub interval_secs: i64,
+}
+
+/// Per-token SuperTrend state: ATR plus the trailing bands of the last candle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuperTrend {
+    period: usize,
+    multiplier: f64,
+    flips_only: bool,
+    atr: Atr,
+    // (upper, lower, close, trend up) after the previous candle
+    previous: Option<(f64, f64, f64, bool)>,
+}
+
+impl SuperTrend {
+    pub fn new(config: &SuperTrendConfig) -> Self {
+        let atr = AtrConfig {
+            period: config.period,
+            ..AtrConfig::default()
+        };
+        Self {
+            period: config.period,
+            multiplier: config.multiplier,
+            flips_only: config.flips_only,
+            atr: Atr::new(&atr),
+            previous: None,
+        }
+    }
+
+    /// Feed a completed candle and build a message once the ATR is seeded
+    pub fn update(&mut self, token_address: &str, candle: &Candle) -> Option<SuperTrendMessage> {
+        let atr = self.atr.on_candle(candle)?;
+        let mid = (candle.high + candle.low) / 2.0;
+        let mut upper = mid + self.multiplier * atr;
+        let mut lower = mid - self.multiplier * atr;
+
+        let (up, flipped) = match self.previous {
+            Some((prev_upper, prev_lower, prev_close, prev_up)) => {
+                // Bands only ratchet towards price until the price crosses them
+                if upper > prev_upper && prev_close <= prev_upper {
+                    upper = prev_upper;
+                }
+                if lower < prev_lower && prev_close >= prev_lower {
+                    lower = prev_lower;
+                }
+                let up = if prev_up { candle.close >= lower } else { candle.close > upper };
+                (up, up != prev_up)
+            }
+            None => (candle.close >= mid, false),
+        };
+        self.previous = Some((upper, lower, candle.close, up));
+
+        if self.flips_only && !flipped {
+            return None;
+        }
+
+        Some(SuperTrendMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            supertrend: if up { lower } else { upper },
+            trend: if up { "up" } else { "down" }.to_string(),
+            flipped,
+            upper_band: upper,
+            lower_band: lower,
+            close: candle.close,
+            timestamp: crate::format_unix_time(candle.end_time()),
+            period: self.period,
+            multiplier: self.multiplier,
+            interval_secs: candle.interval_secs,
+        })
+    }
+}
+
+impl Indicator for SuperTrend {
+    fn on_bar(&mut self, token_address: &str, candle: &Candle) -> Option<IndicatorOutput> {
+        self.update(token_address, candle).map(IndicatorOutput::SuperTrend)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::bars;
+
+    fn supertrend(flips_only: bool) -> Vec<SuperTrendMessage> {
+        let config = SuperTrendConfig {
+            period: 5,
+            multiplier: 2.0,
+            flips_only,
+            ..SuperTrendConfig::default()
+        };
+        let mut supertrend = SuperTrend::new(&config);
+        bars().iter().filter_map(|bar| supertrend.update("token", bar)).collect()
+    }
+
+    #[test]
+    fn supertrend_matches_known_values() {
+        let messages = supertrend(false);
+
+        // Midpoint ± 2 × ATR 5, from the 5th bar on
+        assert_eq!(messages.len(), 30 - 4);
+        assert!((messages[0].supertrend - 19.0760).abs() < 1e-3, "SuperTrend {}", messages[0].supertrend);
+        assert_eq!(messages[0].trend, "up");
+        // The lower band only ratchets up while the trend holds
+        assert!((messages[15].supertrend - 22.5390).abs() < 1e-3, "SuperTrend {}", messages[15].supertrend);
+        let last = messages.last().unwrap();
+        assert!((last.supertrend - 19.1083).abs() < 1e-3, "SuperTrend {}", last.supertrend);
+        assert_eq!(last.trend, "down");
+    }
+
+    #[test]
+    fn flips_only_publishes_the_trend_change() {
+        let flips = supertrend(true);
+
+        assert_eq!(flips.len(), 1);
+        let flip = &flips[0];
+        assert!(flip.flipped);
+        assert_eq!((flip.trend.as_str(), flip.close), ("down", 21.71));
+        assert!((flip.supertrend - 24.4202).abs() < 1e-3, "SuperTrend {}", flip.supertrend);
+    }
+}
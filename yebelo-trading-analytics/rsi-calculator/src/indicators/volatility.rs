@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::{Indicator, IndicatorOutput};
+use crate::candles::Candle;
+use crate::config::VolatilityConfig;
+
+/// Seconds in a year; tokens trade around the clock
+const YEAR_SECS: f64 = 365.0 * 86_400.0;
+
+/// Realized volatility published for one token when a candle closes
+#[derive(Debug, Serialize)]
+pub struct VolatilityMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Sample standard deviation of the last `period` log returns between
+    /// candle closes, e.g. 0.02 for 2% per candle
+    pub volatility: f64,
+    /// `volatility` scaled to a year of candles
+    pub annualized_volatility: f64,
+    pub close: f64,
+    /// Candle close time (RFC 3339)
+    pub timestamp: String,
+    pub period: usize,
+    pub interval_secs: i64,
+}
+
+/// Per-token realized volatility state: log returns of the last `period` candles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Volatility {
+    period: usize,
+    prev_close: Option<f64>,
+    // Oldest first
+    returns: VecDeque<f64>,
+}
+
+impl Volatility {
+    pub fn new(config: &VolatilityConfig) -> Self {
+        Self {
+            period: config.period,
+            prev_close: None,
+            returns: VecDeque::with_capacity(config.period + 1),
+        }
+    }
+
+    /// Feed a completed candle and build a message once `period` returns are in
+    pub fn update(&mut self, token_address: &str, candle: &Candle) -> Option<VolatilityMessage> {
+        let prev_close = self.prev_close.replace(candle.close)?;
+        if prev_close <= 0.0 || candle.close <= 0.0 {
+            return None;
+        }
+        self.returns.push_back((candle.close / prev_close).ln());
+        if self.returns.len() > self.period {
+            self.returns.pop_front();
+        }
+        if self.returns.len() < self.period {
+            return None;
+        }
+
+        let n = self.returns.len() as f64;
+        let mean = self.returns.iter().sum::<f64>() / n;
+        let variance = self.returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        let volatility = variance.sqrt();
+
+        Some(VolatilityMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            volatility,
+            annualized_volatility: volatility * (YEAR_SECS / candle.interval_secs as f64).sqrt(),
+            close: candle.close,
+            timestamp: crate::format_unix_time(candle.end_time()),
+            period: self.period,
+            interval_secs: candle.interval_secs,
+        })
+    }
+}
+
+impl Indicator for Volatility {
+    fn on_bar(&mut self, token_address: &str, candle: &Candle) -> Option<IndicatorOutput> {
+        self.update(token_address, candle).map(IndicatorOutput::Volatility)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::bars;
+
+    #[test]
+    fn realized_volatility_matches_known_values() {
+        let config = VolatilityConfig {
+            period: 10,
+            ..VolatilityConfig::default()
+        };
+        let mut volatility = Volatility::new(&config);
+        let messages: Vec<VolatilityMessage> =
+            bars().iter().filter_map(|bar| volatility.update("token", bar)).collect();
+
+        // Sample standard deviation of 10 log returns, from the 11th bar on
+        assert_eq!(messages.len(), 30 - 10);
+        assert!((messages[0].volatility - 0.020867).abs() < 1e-5, "volatility {}", messages[0].volatility);
+        let last = messages.last().unwrap();
+        assert!((last.volatility - 0.023128).abs() < 1e-5, "volatility {}", last.volatility);
+        // Scaled by the square root of one-minute bars per year
+        assert!((last.annualized_volatility - 16.7677).abs() < 1e-3, "annualized {}", last.annualized_volatility);
+    }
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::{Indicator, IndicatorOutput, TradeInput};
+use crate::config::WhaleConfig;
+
+/// A single unusually large trade, published as an alert event
+#[derive(Debug, Serialize)]
+pub struct WhaleMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// "buy" or "sell"
+    pub direction: String,
+    pub amount_in_sol: f64,
+    pub price_in_sol: f64,
+    pub transaction_signature: String,
+    /// "absolute" when the trade reached `min_amount_sol`, otherwise
+    /// "percentile"
+    pub trigger: String,
+    /// Size the trade had to reach
+    pub threshold_sol: f64,
+    /// The token's `percentile` trade size over the window before this trade;
+    /// absent until enough trades were seen
+    pub percentile_sol: Option<f64>,
+    pub timestamp: String,
+}
+
+/// Per-token whale state: sizes of the token's recent trades
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Whale {
+    min_amount_sol: f64,
+    percentile: f64,
+    window: usize,
+    min_samples: usize,
+    // Oldest first
+    sizes: VecDeque<f64>,
+}
+
+impl Whale {
+    pub fn new(config: &WhaleConfig, token_address: &str) -> Self {
+        Self {
+            min_amount_sol: config.min_amount_sol_for(token_address),
+            percentile: config.percentile,
+            window: config.window,
+            min_samples: config.min_samples,
+            sizes: VecDeque::with_capacity(config.window + 1),
+        }
+    }
+
+    /// Size at `percentile` of the recent trades; None while disabled or
+    /// fewer than `min_samples` trades are in
+    fn percentile_size(&self) -> Option<f64> {
+        if self.percentile <= 0.0 || self.sizes.len() < self.min_samples {
+            return None;
+        }
+        let mut sizes: Vec<f64> = self.sizes.iter().copied().collect();
+        let rank = ((self.percentile / 100.0) * (sizes.len() - 1) as f64).round() as usize;
+        let (_, size, _) = sizes.select_nth_unstable_by(rank, f64::total_cmp);
+        Some(*size)
+    }
+
+    /// Feed the latest trade; returns a message when it reaches the absolute
+    /// threshold or the token's percentile of recent sizes
+    pub fn update(&mut self, trade: &TradeInput) -> Option<WhaleMessage> {
+        let percentile_sol = self.percentile_size();
+        self.sizes.push_back(trade.amount);
+        if self.sizes.len() > self.window {
+            self.sizes.pop_front();
+        }
+
+        let (trigger, threshold_sol) = if self.min_amount_sol > 0.0 && trade.amount >= self.min_amount_sol {
+            ("absolute", self.min_amount_sol)
+        } else {
+            // Strictly above, so a token trading in identical sizes never fires
+            let percentile = percentile_sol.filter(|&size| size > 0.0 && trade.amount > size)?;
+            ("percentile", percentile)
+        };
+
+        Some(WhaleMessage {
+            token_address: trade.token_address.to_string(),
+            quote_currency: String::new(),
+            direction: if trade.is_buy { "buy" } else { "sell" }.to_string(),
+            amount_in_sol: trade.amount,
+            price_in_sol: trade.price,
+            transaction_signature: trade.transaction_signature.to_string(),
+            trigger: trigger.to_string(),
+            threshold_sol,
+            percentile_sol,
+            timestamp: trade.timestamp.to_string(),
+        })
+    }
+}
+
+impl Indicator for Whale {
+    fn on_trade(&mut self, trade: &TradeInput) -> Option<IndicatorOutput> {
+        self.update(trade).map(IndicatorOutput::Whale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Arithmetic, PriceHistory, RsiWeighting};
+
+    fn whales(config: &WhaleConfig, amounts: &[f64]) -> Vec<WhaleMessage> {
+        let history = PriceHistory::new(1, &[], RsiWeighting::Equal, Arithmetic::Float);
+        let mut whale = Whale::new(config, "token");
+        amounts
+            .iter()
+            .filter_map(|&amount| {
+                whale.update(&TradeInput {
+                    token_address: "token",
+                    price: 1.0,
+                    amount,
+                    is_buy: true,
+                    transaction_signature: "",
+                    timestamp: "",
+                    history: &history,
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn trades_above_the_percentile_are_whales() {
+        let config = WhaleConfig {
+            percentile: 90.0,
+            window: 10,
+            min_samples: 10,
+            ..WhaleConfig::default()
+        };
+        let mut amounts: Vec<f64> = (1..=10).map(f64::from).collect();
+        amounts.extend([9.0, 9.5]);
+        let messages = whales(&config, &amounts);
+
+        // The 90th percentile (nearest rank) of the last 10 sizes stays 9, so
+        // only 9.5 is above it
+        assert_eq!(messages.len(), 1);
+        let message = &messages[0];
+        assert_eq!((message.trigger.as_str(), message.amount_in_sol), ("percentile", 9.5));
+        assert_eq!((message.threshold_sol, message.percentile_sol), (9.0, Some(9.0)));
+    }
+
+    #[test]
+    fn trades_at_the_absolute_threshold_are_whales() {
+        let config = WhaleConfig {
+            min_amount_sol: 100.0,
+            token_min_amount_sol: [("token".to_string(), 50.0)].into(),
+            percentile: 0.0,
+            ..WhaleConfig::default()
+        };
+        let messages = whales(&config, &[10.0, 49.9, 50.0, 100.0]);
+
+        // The token's own threshold replaces the global one
+        let amounts: Vec<f64> = messages.iter().map(|message| message.amount_in_sol).collect();
+        assert_eq!(amounts, [50.0, 100.0]);
+        assert!(messages.iter().all(|message| message.trigger == "absolute" && message.threshold_sol == 50.0));
+    }
+}
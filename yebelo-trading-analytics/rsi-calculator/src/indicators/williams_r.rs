@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::{Indicator, IndicatorOutput};
+use crate::candles::Candle;
+use crate::config::WilliamsRConfig;
+
+/// Williams %R published for one token when a candle closes
+#[derive(Debug, Serialize)]
+pub struct WilliamsRMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// -100 (close at the lookback low) to 0 (close at the lookback high)
+    pub williams_r: f64,
+    pub highest_high: f64,
+    pub lowest_low: f64,
+    pub close: f64,
+    /// Candle close time (RFC 3339)
+    pub timestamp: String,
+    pub period: usize,
+    pub interval_secs: i64,
+}
+
+/// Per-token Williams %R state: highs and lows of the last `period` candles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WilliamsR {
+    period: usize,
+    // (high, low) of each candle, oldest first
+    candles: VecDeque<(f64, f64)>,
+}
+
+impl WilliamsR {
+    pub fn new(config: &WilliamsRConfig) -> Self {
+        Self {
+            period: config.period,
+            candles: VecDeque::with_capacity(config.period + 1),
+        }
+    }
+
+    /// Feed a completed candle and build a message once the lookback is full
+    pub fn update(&mut self, token_address: &str, candle: &Candle) -> Option<WilliamsRMessage> {
+        self.candles.push_back((candle.high, candle.low));
+        if self.candles.len() > self.period {
+            self.candles.pop_front();
+        }
+        if self.candles.len() < self.period {
+            return None;
+        }
+
+        let highest_high = self.candles.iter().map(|&(high, _)| high).fold(f64::MIN, f64::max);
+        let lowest_low = self.candles.iter().map(|&(_, low)| low).fold(f64::MAX, f64::min);
+        // %R = (highest high - close) / (highest high - lowest low) × -100
+        let williams_r = if highest_high > lowest_low {
+            (highest_high - candle.close) / (highest_high - lowest_low) * -100.0
+        } else {
+            -50.0 // Flat range, no direction
+        };
+
+        Some(WilliamsRMessage {
+            token_address: token_address.to_string(),
+            quote_currency: String::new(),
+            williams_r,
+            highest_high,
+            lowest_low,
+            close: candle.close,
+            timestamp: crate::format_unix_time(candle.end_time()),
+            period: self.period,
+            interval_secs: candle.interval_secs,
+        })
+    }
+}
+
+impl Indicator for WilliamsR {
+    fn on_bar(&mut self, token_address: &str, candle: &Candle) -> Option<IndicatorOutput> {
+        self.update(token_address, candle).map(IndicatorOutput::WilliamsR)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::bars;
+
+    #[test]
+    fn williams_r_matches_known_values() {
+        let mut williams_r = WilliamsR::new(&WilliamsRConfig::default());
+        let messages: Vec<WilliamsRMessage> =
+            bars().iter().filter_map(|bar| williams_r.update("token", bar)).collect();
+
+        // Range of the last 14 bars, from the 14th bar on
+        assert_eq!(messages.len(), 30 - 13);
+        let first = &messages[0];
+        assert_eq!((first.highest_high, first.lowest_low), (25.0, 19.59));
+        assert!((first.williams_r - -8.6876).abs() < 1e-3, "%R {}", first.williams_r);
+        let last = messages.last().unwrap();
+        assert_eq!((last.highest_high, last.lowest_low), (24.64, 16.03));
+        assert!((last.williams_r - -91.6376).abs() < 1e-3, "%R {}", last.williams_r);
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Indicator, IndicatorOutput, TradeInput};
+use crate::config::ZScoreConfig;
+use crate::PriceHistory;
+
+/// Price z-score published for one token after a trade
+#[derive(Debug, Serialize)]
+pub struct ZScoreMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Standard deviations the price sits above (positive) or below
+    /// (negative) the mean of the window
+    pub zscore: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub current_price: f64,
+    /// "stretched_high" or "stretched_low" beyond ±threshold, else "neutral"
+    pub signal: String,
+    pub timestamp: String,
+    pub period: usize,
+    pub threshold: f64,
+}
+
+/// Calculate how far the price is from the mean of the token's recent prices;
+/// None until the window is full or while the prices are flat
+pub fn calculate(
+    history: &PriceHistory,
+    config: &ZScoreConfig,
+    token_address: &str,
+    price: f64,
+    timestamp: &str,
+) -> Option<ZScoreMessage> {
+    let (mean, std_dev) = history.mean_std_dev(config.period)?;
+    if std_dev <= 0.0 {
+        return None;
+    }
+
+    let zscore = (price - mean) / std_dev;
+    let signal = if zscore >= config.threshold {
+        "stretched_high"
+    } else if zscore <= -config.threshold {
+        "stretched_low"
+    } else {
+        "neutral"
+    };
+
+    Some(ZScoreMessage {
+        token_address: token_address.to_string(),
+        quote_currency: String::new(),
+        zscore,
+        mean,
+        std_dev,
+        current_price: price,
+        signal: signal.to_string(),
+        timestamp: timestamp.to_string(),
+        period: config.period,
+        threshold: config.threshold,
+    })
+}
+
+/// Price z-score as a per-token indicator; stateless apart from its settings,
+/// since everything it needs is in the token's price history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZScore {
+    config: ZScoreConfig,
+}
+
+impl ZScore {
+    pub fn new(config: &ZScoreConfig) -> Self {
+        Self { config: config.clone() }
+    }
+}
+
+impl Indicator for ZScore {
+    fn on_trade(&mut self, trade: &TradeInput) -> Option<IndicatorOutput> {
+        calculate(trade.history, &self.config, trade.token_address, trade.price, trade.timestamp)
+            .map(IndicatorOutput::ZScore)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::CLOSES;
+    use crate::{Arithmetic, RsiWeighting};
+
+    #[test]
+    fn zscore_matches_known_values() {
+        let config = ZScoreConfig {
+            threshold: 0.5,
+            ..ZScoreConfig::default()
+        };
+        let mut history = PriceHistory::new(100, &[], RsiWeighting::Equal, Arithmetic::Float);
+        let mut messages = Vec::new();
+        for close in CLOSES {
+            history.add_price(close, 1.0);
+            messages.extend(calculate(&history, &config, "token", close, ""));
+        }
+
+        // 20-close mean and population standard deviation
+        let expected = [(0.2733, "neutral"), (0.8488, "stretched_high")];
+        assert_eq!(messages.len(), expected.len());
+        for (message, (zscore, signal)) in messages.iter().zip(expected) {
+            assert!((message.zscore - zscore).abs() < 1e-3, "z-score {} instead of {}", message.zscore, zscore);
+            assert_eq!(message.signal, signal);
+        }
+        assert!((messages[1].mean - 45.5041).abs() < 1e-3);
+        assert!((messages[1].std_dev - 0.8343).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::Result;
+use log::{debug, warn};
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::Offset;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+use crate::config::LagConfig;
+use crate::health::Health;
+
+/// Upper bound for each broker query made while measuring lag
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Messages behind the high watermark, keyed by (topic, partition)
+pub type PartitionLag = BTreeMap<(String, i32), i64>;
+
+/// Periodically compare the high watermark of every assigned partition with
+/// the group's committed offset, publish the lag on the health server and
+/// warn about partitions that fall too far behind
+pub async fn monitor(config: LagConfig, consumer: Arc<StreamConsumer>, health: Arc<Health>) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(config.interval_secs));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+
+        // The offset and watermark queries block on the broker
+        let consumer = Arc::clone(&consumer);
+        let lag = match tokio::task::spawn_blocking(move || measure(&consumer)).await {
+            Ok(Ok(lag)) => lag,
+            Ok(Err(e)) => {
+                warn!("Failed to measure consumer lag: {:#}", e);
+                continue;
+            }
+            Err(e) => {
+                warn!("Consumer lag query panicked: {}", e);
+                continue;
+            }
+        };
+
+        for ((topic, partition), &behind) in &lag {
+            if behind > config.warn_threshold {
+                warn!("🐢 {}[{}] is {} messages behind (threshold {})", topic, partition, behind, config.warn_threshold);
+            }
+        }
+        debug!("Consumer lag: {} messages across {} partitions", lag.values().sum::<i64>(), lag.len());
+        health.set_lag(lag);
+    }
+}
+
+/// Lag of every partition currently assigned to this instance
+fn measure(consumer: &StreamConsumer) -> Result<PartitionLag> {
+    let assignment = consumer.assignment()?;
+    let committed = consumer.committed_offsets(assignment, QUERY_TIMEOUT)?;
+
+    let mut lag = PartitionLag::new();
+    for partition in committed.elements() {
+        let (low, high) = consumer.fetch_watermarks(partition.topic(), partition.partition(), QUERY_TIMEOUT)?;
+        // Without a committed offset the whole retained log is still ahead
+        let position = match partition.offset() {
+            Offset::Offset(offset) => offset,
+            _ => low,
+        };
+        lag.insert((partition.topic().to_string(), partition.partition()), (high - position).max(0));
+    }
+    Ok(lag)
+}
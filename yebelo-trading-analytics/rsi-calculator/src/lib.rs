@@ -0,0 +1,1419 @@
+//! Streaming RSI and technical indicators over token trades
+//!
+//! [`RsiCalculator`] turns [`TradeMessage`]s into [`IndicatorOutput`]s with no
+//! Kafka involved; [`service::run`] wires it to Redpanda for the
+//! `rsi-calculator` binary.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+mod alerts;
+mod api;
+pub mod arithmetic;
+pub mod backfill;
+pub mod backtest;
+pub mod candles;
+pub mod cli;
+mod codec;
+pub mod config;
+mod correlation;
+mod dead_letter;
+pub mod dedup;
+mod delivery;
+pub mod generate;
+pub mod generator;
+mod headers;
+mod health;
+pub mod indicators;
+mod lag;
+mod market_index;
+pub mod outliers;
+mod rankings;
+mod reconnect;
+pub mod reference;
+pub mod reload;
+pub mod reorder;
+pub mod schema;
+mod seed;
+pub mod service;
+mod sinks;
+mod sol_usd;
+mod state_store;
+pub mod strategy;
+mod telemetry;
+mod transactions;
+pub mod validation;
+pub mod verify;
+mod volume;
+mod warmup;
+mod workers;
+
+use arithmetic::Real;
+use candles::{CandleAggregator, CandleMessage};
+use config::{
+    Arithmetic, CandleConfig, Config, DedupConfig, EvictionConfig, FilterConfig, OutlierConfig, ReorderConfig, RsiConfig, RsiMode,
+    RsiWeighting, SignalEventsConfig,
+};
+use dedup::DedupCache;
+use outliers::{OutlierStreak, QuarantinedTrade};
+use indicators::{IndicatorInstance, IndicatorOutput, IndicatorRegistry, RsiInput, TradeInput};
+use reorder::{PendingTrade, ReorderBuffer};
+
+/// Evicted token addresses kept for `take_evicted`; older ones are dropped
+/// when nobody collects them
+const EVICTED_BUFFER: usize = 1024;
+
+/// Quote currency of trades that don't name one
+pub const DEFAULT_QUOTE_CURRENCY: &str = "SOL";
+
+/// Trade message structure matching the CSV data
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TradeMessage {
+    pub token_address: String,
+    pub price_in_sol: f64,
+    /// Unix seconds or RFC 3339
+    pub block_time: String,
+    pub transaction_signature: String,
+    pub is_buy: bool,
+    pub amount_in_sol: f64,
+    
+    #[serde(default)]
+    pub processed_timestamp: String,
+    /// Currency `price_in_sol` and `amount_in_sol` are actually quoted in,
+    /// e.g. "USDC"; empty means SOL
+    #[serde(default)]
+    pub quote_currency: String,
+}
+
+impl TradeMessage {
+    /// Trade time as Unix seconds, parsed from `block_time`
+    ///
+    /// Accepts Unix seconds (as produced by the ingestion script) or RFC 3339.
+    pub fn block_time_secs(&self) -> Option<i64> {
+        let raw = self.block_time.trim();
+        raw.parse::<i64>()
+            .ok()
+            .or_else(|| chrono::DateTime::parse_from_rfc3339(raw).ok().map(|t| t.timestamp()))
+    }
+    
+    /// Upper-case quote currency, SOL when unset
+    pub fn quote(&self) -> Cow<'_, str> {
+        match self.quote_currency.trim() {
+            "" => Cow::Borrowed(DEFAULT_QUOTE_CURRENCY),
+            quote if quote.eq_ignore_ascii_case(DEFAULT_QUOTE_CURRENCY) => Cow::Borrowed(DEFAULT_QUOTE_CURRENCY),
+            quote => Cow::Owned(quote.to_ascii_uppercase()),
+        }
+    }
+    
+    /// Key of the series this trade belongs to, see [`series_key`]
+    pub fn series_key(&self) -> Cow<'_, str> {
+        series_key(&self.token_address, &self.quote())
+    }
+}
+
+/// Key a token's series in one quote currency is tracked under: the token
+/// address for SOL (or no quote), "<token_address>:<QUOTE>" otherwise
+///
+/// Prices in different quotes never share state this way. Outputs still
+/// carry the token address and quote currency as separate fields.
+pub fn series_key<'a>(token_address: &'a str, quote_currency: &str) -> Cow<'a, str> {
+    match quote_currency {
+        "" | DEFAULT_QUOTE_CURRENCY => Cow::Borrowed(token_address),
+        quote => Cow::Owned(format!("{}:{}", token_address, quote)),
+    }
+}
+
+/// Split a series key into token address and quote currency
+pub fn split_series_key(key: &str) -> (&str, &str) {
+    key.split_once(':').unwrap_or((key, DEFAULT_QUOTE_CURRENCY))
+}
+
+/// Format Unix seconds as an RFC 3339 timestamp
+fn format_unix_time(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .unwrap_or_default()
+        .to_rfc3339()
+}
+
+/// RSI calculation result to be published
+#[derive(Debug, Serialize)]
+pub struct RsiMessage {
+    /// Shape this message is published in, see [`schema`]
+    pub schema_version: u32,
+    pub token_address: String,
+    /// Currency `current_price` is quoted in
+    pub quote_currency: String,
+    pub rsi_value: f64,
+    pub current_price: f64,
+    /// Event time: the trade's `block_time`, or the candle's close (RFC 3339)
+    pub timestamp: String,
+    /// When the value was calculated (RFC 3339)
+    pub processed_at: String,
+    pub period: usize,
+    pub signal: String, // one of config::SIGNALS, e.g. "oversold"
+    pub timeframe: String, // "tick" or the candle interval, e.g. "1m"
+    /// Wilder-smoothed average gain and loss behind the value, so a
+    /// restarted calculator can continue the series (absent for simple RSI)
+    pub avg_gain: Option<f64>,
+    pub avg_loss: Option<f64>,
+}
+
+/// Last published RSI of a series, read back from the output topic to seed
+/// a calculator that starts without state
+#[derive(Debug, Deserialize)]
+pub struct RsiSeed {
+    pub token_address: String,
+    /// Absent before schema version 4, meaning SOL
+    #[serde(default)]
+    pub quote_currency: String,
+    pub current_price: f64,
+    pub period: usize,
+    pub signal: String,
+    pub timeframe: String,
+    pub avg_gain: Option<f64>,
+    pub avg_loss: Option<f64>,
+}
+
+/// Published when an RSI series moves from one signal to another
+#[derive(Debug, Serialize)]
+pub struct SignalChangeMessage {
+    pub event: &'static str, // always "signal_changed"
+    pub token_address: String,
+    pub quote_currency: String,
+    pub period: usize,
+    pub timeframe: String,
+    pub previous_signal: String,
+    pub signal: String,
+    pub rsi_value: f64,
+    pub current_price: f64,
+    pub timestamp: String,
+    pub processed_at: String,
+}
+
+/// How average gains and losses are smoothed when calculating RSI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Smoothing {
+    /// Simple average of the last `period` changes, recomputed on every trade
+    Simple,
+    /// Wilder's smoothing: seeded with an SMA, then updated incrementally
+    /// (matches TradingView / TA-Lib RSI)
+    Wilder,
+}
+
+impl std::str::FromStr for Smoothing {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "simple" => Ok(Smoothing::Simple),
+            "wilder" => Ok(Smoothing::Wilder),
+            other => Err(format!("unknown smoothing mode '{}' (expected simple or wilder)", other)),
+        }
+    }
+}
+
+/// Running Wilder-smoothed averages of gains and losses for one token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WilderState<T = f64> {
+    period: usize,
+    prev_price: Option<T>,
+    // Sums of the first `period` changes, used to seed the averages
+    seed_gain: T,
+    seed_loss: T,
+    seed_count: usize,
+    avg_gain: T,
+    avg_loss: T,
+}
+
+impl<T: Real> WilderState<T> {
+    fn new(period: usize) -> Self {
+        Self {
+            period,
+            prev_price: None,
+            seed_gain: T::zero(),
+            seed_loss: T::zero(),
+            seed_count: 0,
+            avg_gain: T::zero(),
+            avg_loss: T::zero(),
+        }
+    }
+    
+    /// Feed the next price into the smoothed averages, scaling its change
+    /// by `weight` (1.0 for classic RSI)
+    fn update(&mut self, price: f64, weight: f64) {
+        let price = T::from_f64(price);
+        let prev = match self.prev_price.replace(price) {
+            Some(prev) => prev,
+            None => return, // First price, no change to record yet
+        };
+        
+        let (gain, loss) = gain_loss((price - prev) * T::from_f64(weight));
+        
+        if self.seed_count < self.period {
+            // Still collecting the initial window: accumulate for the SMA seed
+            self.seed_gain = self.seed_gain + gain;
+            self.seed_loss = self.seed_loss + loss;
+            self.seed_count += 1;
+            
+            if self.seed_count == self.period {
+                self.avg_gain = self.seed_gain / T::from_usize(self.period);
+                self.avg_loss = self.seed_loss / T::from_usize(self.period);
+            }
+        } else {
+            // Wilder's smoothing: avg = (prev_avg * (period - 1) + current) / period
+            let period = T::from_usize(self.period);
+            let rest = T::from_usize(self.period - 1);
+            self.avg_gain = (self.avg_gain * rest + gain) / period;
+            self.avg_loss = (self.avg_loss * rest + loss) / period;
+        }
+    }
+    
+    fn period(&self) -> usize {
+        self.period
+    }
+    
+    /// Continue from published averages as if the seed window were complete
+    fn seed(&mut self, price: f64, avg_gain: f64, avg_loss: f64) {
+        self.prev_price = Some(T::from_f64(price));
+        self.seed_count = self.period;
+        self.avg_gain = T::from_f64(avg_gain);
+        self.avg_loss = T::from_f64(avg_loss);
+    }
+    
+    /// Average gain and loss, once the seed window is complete
+    fn averages(&self) -> Option<(f64, f64)> {
+        (self.seed_count >= self.period).then(|| (self.avg_gain.to_f64(), self.avg_loss.to_f64()))
+    }
+    
+    /// Current RSI, once the seed window is complete
+    fn rsi(&self) -> Option<f64> {
+        if self.seed_count < self.period {
+            return None;
+        }
+        
+        Some(rsi_from_averages(self.avg_gain, self.avg_loss))
+    }
+}
+
+/// A price change split into its gain and loss parts, one of them zero
+fn gain_loss<T: Real>(change: T) -> (T, T) {
+    if change > T::zero() {
+        (change, T::zero())
+    } else {
+        (T::zero(), T::zero() - change)
+    }
+}
+
+/// RSI = 100 - (100 / (1 + RS)), where RS = Average Gain / Average Loss
+fn rsi_from_averages<T: Real>(avg_gain: T, avg_loss: T) -> f64 {
+    // Avoid division by zero
+    if avg_loss == T::zero() {
+        return 100.0; // If no losses, RSI is 100
+    }
+    
+    let hundred = T::from_usize(100);
+    let rs = avg_gain / avg_loss;
+    (hundred - (hundred / (T::from_usize(1) + rs))).to_f64()
+}
+
+/// Stores price history for RSI calculation per token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceHistory {
+    // Ring buffer of the most recent prices, oldest first
+    prices: VecDeque<f64>,
+    max_size: usize,
+    // Wilder smoothing state, one per configured RSI period
+    wilder: HashMap<usize, WilderState>,
+    // Prices added so far, including those no longer kept
+    #[serde(default)]
+    samples: u64,
+    // Change weights when RSI is volume-weighted, aligned with `prices`
+    #[serde(default)]
+    weighting: RsiWeighting,
+    #[serde(default)]
+    volumes: VecDeque<f64>,
+    // Last RSI signal per period, for hysteresis and change events
+    #[serde(default)]
+    signals: BTreeMap<usize, String>,
+    // Number type RSI is calculated in; `wilder_decimal` replaces `wilder`
+    // when it is decimal
+    #[serde(default)]
+    arithmetic: Arithmetic,
+    #[serde(default)]
+    wilder_decimal: HashMap<usize, WilderState<Decimal>>,
+}
+
+impl PriceHistory {
+    /// Empty history keeping up to `max_size` prices, with Wilder state for
+    /// each RSI period
+    pub fn new(max_size: usize, rsi_periods: &[usize], weighting: RsiWeighting, arithmetic: Arithmetic) -> Self {
+        let decimal = arithmetic == Arithmetic::Decimal;
+        Self {
+            prices: VecDeque::with_capacity(max_size + 1),
+            max_size,
+            wilder: rsi_periods
+                .iter()
+                .filter(|_| !decimal)
+                .map(|&period| (period, WilderState::new(period)))
+                .collect(),
+            samples: 0,
+            weighting,
+            volumes: VecDeque::new(),
+            signals: BTreeMap::new(),
+            arithmetic,
+            wilder_decimal: rsi_periods
+                .iter()
+                .filter(|_| decimal)
+                .map(|&period| (period, WilderState::new(period)))
+                .collect(),
+        }
+    }
+    
+    /// Add new price (with the volume traded at it) and maintain maximum size
+    pub fn add_price(&mut self, price: f64, volume: f64) {
+        let weight = match self.weighting {
+            RsiWeighting::Equal => 1.0,
+            RsiWeighting::Volume => volume.max(0.0),
+        };
+        
+        self.prices.push_back(price);
+        self.samples += 1;
+        for state in self.wilder.values_mut() {
+            state.update(price, weight);
+        }
+        for state in self.wilder_decimal.values_mut() {
+            state.update(price, weight);
+        }
+        if self.weighting == RsiWeighting::Volume {
+            self.volumes.push_back(weight);
+        }
+        
+        // Keep only the most recent prices
+        if self.prices.len() > self.max_size {
+            self.prices.pop_front();
+            self.volumes.pop_front();
+        }
+    }
+    
+    /// Number of prices currently kept
+    pub fn len(&self) -> usize {
+        self.prices.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+    
+    /// Number of prices added so far
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+    
+    /// The last `window` prices, oldest first; all of them when fewer are kept
+    pub fn recent(&self, window: usize) -> impl Iterator<Item = f64> + Clone + '_ {
+        self.prices.range(self.prices.len().saturating_sub(window)..).copied()
+    }
+    
+    /// Mean and population standard deviation of the last `window` prices
+    pub fn mean_std_dev(&self, window: usize) -> Option<(f64, f64)> {
+        if window == 0 || self.prices.len() < window {
+            return None;
+        }
+        
+        let recent = self.recent(window);
+        let mean = recent.clone().sum::<f64>() / window as f64;
+        let variance = recent.map(|p| (p - mean).powi(2)).sum::<f64>() / window as f64;
+        
+        Some((mean, variance.sqrt()))
+    }
+    
+    /// The price `n` prices before the latest one
+    pub fn price_ago(&self, n: usize) -> Option<f64> {
+        let index = self.prices.len().checked_sub(n + 1)?;
+        self.prices.get(index).copied()
+    }
+    
+    /// Highest and lowest of the last `window` prices
+    pub fn high_low(&self, window: usize) -> Option<(f64, f64)> {
+        if window == 0 || self.prices.len() < window {
+            return None;
+        }
+        
+        let high = self.recent(window).fold(f64::MIN, f64::max);
+        let low = self.recent(window).fold(f64::MAX, f64::min);
+        
+        Some((high, low))
+    }
+    
+    /// Calculate RSI with the requested smoothing mode
+    pub fn rsi(&self, period: usize, smoothing: Smoothing) -> Option<f64> {
+        match (smoothing, self.arithmetic) {
+            (Smoothing::Simple, Arithmetic::Float) => self.calculate_rsi::<f64>(period),
+            (Smoothing::Simple, Arithmetic::Decimal) => self.calculate_rsi::<Decimal>(period),
+            (Smoothing::Wilder, Arithmetic::Float) => self.wilder.get(&period).and_then(WilderState::rsi),
+            (Smoothing::Wilder, Arithmetic::Decimal) => self.wilder_decimal.get(&period).and_then(WilderState::rsi),
+        }
+    }
+    
+    /// Wilder average gain and loss for `period`, once seeded
+    pub fn wilder_averages(&self, period: usize) -> Option<(f64, f64)> {
+        match self.arithmetic {
+            Arithmetic::Float => self.wilder.get(&period)?.averages(),
+            Arithmetic::Decimal => self.wilder_decimal.get(&period)?.averages(),
+        }
+    }
+    
+    /// Resume a Wilder RSI series from its last published averages and
+    /// signal; false for periods this history does not track
+    pub fn seed(&mut self, period: usize, price: f64, avg_gain: f64, avg_loss: f64, signal: &str) -> bool {
+        let seeded = match self.arithmetic {
+            Arithmetic::Float => self.wilder.get_mut(&period).map(|state| state.seed(price, avg_gain, avg_loss)),
+            Arithmetic::Decimal => self
+                .wilder_decimal
+                .get_mut(&period)
+                .map(|state| state.seed(price, avg_gain, avg_loss)),
+        };
+        if seeded.is_some() {
+            self.signals.insert(period, signal.to_string());
+        }
+        seeded.is_some()
+    }
+    
+    /// The same prices replayed into a history laid out like `layout`
+    /// (size, periods, weighting and arithmetic), keeping the last signals
+    ///
+    /// Wilder averages restart from the prices still held, so a changed
+    /// period resumes with a shorter warm-up instead of from nothing.
+    fn rebuilt_like(&self, layout: &PriceHistory) -> PriceHistory {
+        let periods: Vec<usize> = layout.wilder.keys().chain(layout.wilder_decimal.keys()).copied().collect();
+        let mut history = PriceHistory::new(layout.max_size, &periods, layout.weighting, layout.arithmetic);
+        // Volumes are only kept for volume weighting; count the rest equally
+        let volumes = self.volumes.iter().copied().chain(std::iter::repeat(1.0));
+        for (&price, volume) in self.prices.iter().zip(volumes) {
+            history.add_price(price, volume);
+        }
+        history.samples = self.samples;
+        history.signals = self
+            .signals
+            .iter()
+            .filter(|(period, _)| periods.contains(period))
+            .map(|(&period, signal)| (period, signal.clone()))
+            .collect();
+        history
+    }
+    
+    /// Calculate RSI from a simple average over the last `period` changes
+    /// RSI = 100 - (100 / (1 + RS))
+    /// where RS = Average Gain / Average Loss
+    fn calculate_rsi<T: Real>(&self, period: usize) -> Option<f64> {
+        // Need at least period + 1 prices to calculate changes
+        if self.prices.len() < period + 1 {
+            return None;
+        }
+        
+        // Sum gains and losses over the last `period` price changes
+        let mut total_gain = T::zero();
+        let mut total_loss = T::zero();
+        
+        let recent = self.recent(period + 1).map(T::from_f64);
+        // Each change is weighted by the volume of the trade that made it
+        let weights = self.volumes.range(self.volumes.len().saturating_sub(period)..).copied();
+        let weights = weights.chain(std::iter::repeat(1.0)).map(T::from_f64);
+        for ((previous, current), weight) in recent.clone().zip(recent.skip(1)).zip(weights) {
+            let (gain, loss) = gain_loss((current - previous) * weight);
+            total_gain = total_gain + gain;
+            total_loss = total_loss + loss;
+        }
+        
+        // Calculate average gain and average loss
+        let avg_gain = total_gain / T::from_usize(period);
+        let avg_loss = total_loss / T::from_usize(period);
+        
+        Some(rsi_from_averages(avg_gain, avg_loss))
+    }
+}
+
+/// Snapshot of calculator state restored on startup
+pub struct RestoredState {
+    pub tokens: HashMap<String, TokenState>,
+    pub watermark: Option<i64>,
+}
+
+/// All indicator state tracked for a single token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenState {
+    history: PriceHistory,
+    // RSI fed with candle closes, for tokens in candle mode
+    candle_rsi: Option<PriceHistory>,
+    // RSI fed with candle closes of each extra timeframe, by interval
+    #[serde(default)]
+    timeframe_rsi: BTreeMap<i64, PriceHistory>,
+    // Candles for publishing and candle-based indicators
+    candles: Option<CandleAggregator>,
+    // Every enabled indicator besides RSI
+    #[serde(default)]
+    indicators: Vec<IndicatorInstance>,
+    // Recent transaction signatures, when deduplication is enabled
+    dedup: Option<DedupCache>,
+    // Trades held back until their block_time is safely in order
+    reorder: Option<ReorderBuffer>,
+    // Latest trade time for this token, for idle eviction
+    #[serde(default)]
+    last_trade: i64,
+    // Outliers in a row, to tell a repricing from bad prints
+    #[serde(default)]
+    outlier_streak: OutlierStreak,
+}
+
+/// Main RSI calculator engine
+pub struct RsiCalculator {
+    // Store price history and indicator state for each token
+    token_histories: HashMap<String, TokenState>,
+    rsi: RsiConfig,
+    // Effective RSI settings for tokens with overrides
+    token_rsi: BTreeMap<String, RsiConfig>,
+    indicators: IndicatorRegistry,
+    signal_events: SignalEventsConfig,
+    candles: CandleConfig,
+    // Every candle interval any consumer needs
+    candle_intervals: Vec<i64>,
+    // Latest trade time seen across all tokens (Unix seconds)
+    watermark: i64,
+    // Trades dropped because their candle was already finalized or they
+    // arrived after the reorder buffer had moved past them
+    late_trades: u64,
+    filter: FilterConfig,
+    // Trades skipped by the token allow/deny lists
+    filtered_trades: u64,
+    dedup: DedupConfig,
+    // Trades dropped because their signature was already seen
+    duplicate_trades: u64,
+    outliers: OutlierConfig,
+    // Trades rejected for a price far outside the token's recent range
+    outlier_trades: u64,
+    reorder: ReorderConfig,
+    eviction: EvictionConfig,
+    // Trade time of the last idle-token sweep
+    last_sweep: i64,
+    // Tokens forgotten for being idle, and for exceeding max_tokens
+    evicted_idle: u64,
+    evicted_lru: u64,
+    // Addresses evicted since the last take_evicted
+    evicted: VecDeque<String>,
+    rsi_schema_version: u32,
+}
+
+impl RsiCalculator {
+    /// Calculator with no token state yet, using the indicator settings in `config`
+    pub fn new(config: &Config) -> Self {
+        Self {
+            token_histories: HashMap::new(),
+            rsi: config.rsi.clone(),
+            token_rsi: config.token_rsi_configs(),
+            indicators: IndicatorRegistry::new(config),
+            signal_events: config.signal_events.clone(),
+            candles: config.candles.clone(),
+            candle_intervals: candle_intervals(config),
+            watermark: i64::MIN,
+            late_trades: 0,
+            filter: config.filter.clone(),
+            filtered_trades: 0,
+            dedup: config.dedup.clone(),
+            duplicate_trades: 0,
+            outliers: config.outliers.clone(),
+            outlier_trades: 0,
+            reorder: config.reorder.clone(),
+            eviction: config.eviction.clone(),
+            last_sweep: i64::MIN,
+            evicted_idle: 0,
+            evicted_lru: 0,
+            evicted: VecDeque::new(),
+            rsi_schema_version: config.kafka.rsi_schema_version,
+        }
+    }
+    
+    /// Identifies the indicator configuration that shaped the current state,
+    /// so checkpoints are never restored into an incompatible layout
+    pub fn state_fingerprint(&self) -> String {
+        serde_json::json!({
+            "rsi": self.rsi,
+            "tokens": self.token_rsi,
+            "indicators": self.indicators,
+            "candle_intervals": self.candle_intervals,
+            "dedup": self.dedup.enabled,
+            "reorder": self.reorder.enabled,
+        })
+        .to_string()
+    }
+    
+    /// Apply new indicator settings, thresholds, token filters and
+    /// overrides while keeping every token's state
+    ///
+    /// Thresholds and filters simply take effect on the next trade. RSI
+    /// series whose layout changed (periods, weighting, arithmetic, mode or
+    /// timeframes) are rebuilt from the prices still held; other indicators
+    /// and candles start over only if their own settings changed. Tokens
+    /// the new filter rejects are forgotten.
+    pub fn reconfigure(&mut self, config: &Config) {
+        let old_rsi = std::mem::replace(&mut self.rsi, config.rsi.clone());
+        let old_token_rsi = std::mem::replace(&mut self.token_rsi, config.token_rsi_configs());
+        let old_indicators = std::mem::replace(&mut self.indicators, IndicatorRegistry::new(config));
+        let indicators_changed =
+            serde_json::to_value(&old_indicators).ok() != serde_json::to_value(&self.indicators).ok();
+        let old_candle_intervals = std::mem::replace(&mut self.candle_intervals, candle_intervals(config));
+        let old_dedup = std::mem::replace(&mut self.dedup, config.dedup.clone());
+        let dedup_changed = serde_json::to_value(&old_dedup).ok() != serde_json::to_value(&self.dedup).ok();
+        self.signal_events = config.signal_events.clone();
+        self.candles = config.candles.clone();
+        self.filter = config.filter.clone();
+        self.outliers = config.outliers.clone();
+        self.eviction = config.eviction.clone();
+        
+        for (token_address, state) in std::mem::take(&mut self.token_histories) {
+            let (token, _) = split_series_key(&token_address);
+            if !self.filter.accepts(token) {
+                continue;
+            }
+            let mut state = state;
+            let fresh = self.new_token_state(&token_address);
+            
+            let old_layout = series_layout(old_token_rsi.get(token).unwrap_or(&old_rsi), token);
+            let new_layout = series_layout(self.token_rsi.get(token).unwrap_or(&self.rsi), token);
+            if old_layout != new_layout || state.history.max_size != fresh.history.max_size {
+                state.history = state.history.rebuilt_like(&fresh.history);
+                state.candle_rsi = match (state.candle_rsi, fresh.candle_rsi) {
+                    (Some(old), Some(new)) => Some(old.rebuilt_like(&new)),
+                    (_, new) => new,
+                };
+                let mut old_timeframes = std::mem::take(&mut state.timeframe_rsi);
+                state.timeframe_rsi = fresh
+                    .timeframe_rsi
+                    .into_iter()
+                    .map(|(secs, new)| match old_timeframes.remove(&secs) {
+                        Some(old) => (secs, old.rebuilt_like(&new)),
+                        None => (secs, new),
+                    })
+                    .collect();
+            }
+            if indicators_changed {
+                state.indicators = fresh.indicators;
+            }
+            if old_candle_intervals != self.candle_intervals {
+                state.candles = fresh.candles;
+            }
+            if dedup_changed {
+                state.dedup = fresh.dedup;
+            }
+            self.token_histories.insert(token_address, state);
+        }
+    }
+    
+    /// Replace in-memory state with a restored checkpoint
+    ///
+    /// A checkpoint holding more tokens than `max_tokens` (e.g. written
+    /// before the cap was lowered) keeps only the most recently traded ones.
+    pub fn restore(&mut self, restored: RestoredState) {
+        self.token_histories = restored.tokens;
+        if let Some(watermark) = restored.watermark {
+            self.watermark = watermark;
+        }
+        if self.eviction.enabled && self.eviction.max_tokens > 0 {
+            let excess = self.token_histories.len().saturating_sub(self.eviction.max_tokens);
+            let mut by_last_trade: Vec<(i64, String)> = self
+                .token_histories
+                .iter()
+                .map(|(token_address, state)| (state.last_trade, token_address.clone()))
+                .collect();
+            by_last_trade.sort_unstable();
+            for (_, oldest) in by_last_trade.into_iter().take(excess) {
+                self.token_histories.remove(&oldest);
+                self.evicted_lru += 1;
+                self.record_eviction(oldest);
+            }
+        }
+    }
+    
+    /// Move all token state out, e.g. to divide it between calculators
+    pub fn take_state(&mut self) -> RestoredState {
+        RestoredState {
+            tokens: std::mem::take(&mut self.token_histories),
+            watermark: Some(self.watermark),
+        }
+    }
+    
+    /// Forget one token's state, in every quote currency, so its next trade
+    /// starts every series over; false if the token was not tracked
+    pub fn reset_token(&mut self, token_address: &str) -> bool {
+        let tracked = self.token_histories.len();
+        self.token_histories.retain(|key, _| split_series_key(key).0 != token_address);
+        self.token_histories.len() < tracked
+    }
+    
+    /// Resume a token's RSI series from its last published message
+    ///
+    /// Only Wilder-smoothed series carry the averages needed; returns false
+    /// when the message cannot seed any series tracked for the token.
+    pub fn seed(&mut self, seed: &RsiSeed) -> bool {
+        let (Some(avg_gain), Some(avg_loss)) = (seed.avg_gain, seed.avg_loss) else {
+            return false;
+        };
+        if !self.filter.accepts(&seed.token_address) {
+            return false;
+        }
+        let key = series_key(&seed.token_address, &seed.quote_currency).into_owned();
+        if !self.token_histories.contains_key(&key) {
+            self.make_room();
+            let state = self.new_token_state(&key);
+            self.token_histories.insert(key.clone(), state);
+        }
+        let state = self
+            .token_histories
+            .get_mut(&key)
+            .expect("token state was just inserted");
+        
+        let candle_timeframe = candles::format_interval(self.rsi.candle_interval_secs);
+        let history = match (&mut state.candle_rsi, seed.timeframe.as_str()) {
+            (None, "tick") => Some(&mut state.history),
+            (Some(history), timeframe) if timeframe == candle_timeframe => Some(history),
+            (_, timeframe) => state
+                .timeframe_rsi
+                .iter_mut()
+                .find(|(&secs, _)| candles::format_interval(secs) == timeframe)
+                .map(|(_, history)| history),
+        };
+        history.is_some_and(|history| history.seed(seed.period, seed.current_price, avg_gain, avg_loss, &seed.signal))
+    }
+    
+    /// State of every token seen so far, keyed by token address
+    pub fn tokens(&self) -> &HashMap<String, TokenState> {
+        &self.token_histories
+    }
+    
+    /// Latest trade time seen across all tokens (Unix seconds)
+    pub fn watermark(&self) -> i64 {
+        self.watermark
+    }
+    
+    /// Settings of the indicators instantiated for each token
+    pub fn indicators(&self) -> &IndicatorRegistry {
+        &self.indicators
+    }
+    
+    /// Trades dropped so far because they arrived too late to be processed in order
+    pub fn late_trades(&self) -> u64 {
+        self.late_trades
+    }
+    
+    /// Trades skipped so far by the token allow/deny lists
+    pub fn filtered_trades(&self) -> u64 {
+        self.filtered_trades
+    }
+    
+    /// Trades dropped so far as duplicates of an already processed signature
+    pub fn duplicate_trades(&self) -> u64 {
+        self.duplicate_trades
+    }
+    
+    /// Trades rejected so far as price outliers
+    pub fn outlier_trades(&self) -> u64 {
+        self.outlier_trades
+    }
+    
+    /// Prices fed into a token's RSI for the given timeframe ("tick" or a
+    /// candle interval); 0 for unknown tokens
+    pub fn samples(&self, token_address: &str, timeframe: &str) -> u64 {
+        let Some(state) = self.token_histories.get(token_address) else {
+            return 0;
+        };
+        if timeframe == "tick" {
+            return state.history.samples();
+        }
+        state
+            .timeframe_rsi
+            .iter()
+            .find(|&(&secs, _)| candles::format_interval(secs) == timeframe)
+            .map(|(_, history)| history)
+            .or(state.candle_rsi.as_ref())
+            .map_or(state.history.samples(), PriceHistory::samples)
+    }
+    
+    /// RSI periods of a token that have no value yet; None for tokens
+    /// without state (never seen, filtered out or evicted)
+    pub fn warming_up(&self, token_address: &str) -> Option<Vec<usize>> {
+        let state = self.token_histories.get(token_address)?;
+        let rsi = self.token_rsi.get(split_series_key(token_address).0).unwrap_or(&self.rsi);
+        let history = state.candle_rsi.as_ref().unwrap_or(&state.history);
+        Some(
+            rsi.periods
+                .iter()
+                .copied()
+                .filter(|&period| history.rsi(period, rsi.smoothing).is_none())
+                .collect(),
+        )
+    }
+    
+    /// Tokens evicted so far for being idle longer than the TTL
+    pub fn evicted_idle(&self) -> u64 {
+        self.evicted_idle
+    }
+    
+    /// Tokens evicted so far to stay under `max_tokens`
+    pub fn evicted_lru(&self) -> u64 {
+        self.evicted_lru
+    }
+    
+    /// Forget tokens that have not traded within the idle TTL
+    ///
+    /// Sweeps at most once a minute of trade time, since it visits every token.
+    fn evict_idle(&mut self, now: i64) {
+        const SWEEP_INTERVAL_SECS: i64 = 60;
+        
+        if !self.eviction.enabled || now < self.last_sweep.saturating_add(SWEEP_INTERVAL_SECS) {
+            return;
+        }
+        self.last_sweep = now;
+        
+        let cutoff = now.saturating_sub(self.eviction.idle_ttl_secs);
+        let idle: Vec<String> = self
+            .token_histories
+            .iter()
+            .filter(|(_, state)| state.last_trade < cutoff)
+            .map(|(token_address, _)| token_address.clone())
+            .collect();
+        for token_address in idle {
+            self.token_histories.remove(&token_address);
+            self.evicted_idle += 1;
+            self.record_eviction(token_address);
+        }
+    }
+    
+    /// Drop the least recently traded tokens until a new one fits under `max_tokens`
+    fn make_room(&mut self) {
+        if !self.eviction.enabled || self.eviction.max_tokens == 0 {
+            return;
+        }
+        
+        while self.token_histories.len() >= self.eviction.max_tokens {
+            let oldest = self
+                .token_histories
+                .iter()
+                .min_by_key(|(_, state)| state.last_trade)
+                .map(|(token_address, _)| token_address.clone());
+            let Some(oldest) = oldest else {
+                break;
+            };
+            self.token_histories.remove(&oldest);
+            self.evicted_lru += 1;
+            self.record_eviction(oldest);
+        }
+    }
+    
+    fn record_eviction(&mut self, token_address: String) {
+        if self.evicted.len() >= EVICTED_BUFFER {
+            self.evicted.pop_front();
+        }
+        self.evicted.push_back(token_address);
+    }
+    
+    /// Addresses of tokens evicted since the last call, oldest first
+    pub fn take_evicted(&mut self) -> Vec<String> {
+        self.evicted.drain(..).collect()
+    }
+    
+    /// Create fresh state for a series seen for the first time; per-token
+    /// settings apply to every quote of the token
+    fn new_token_state(&self, key: &str) -> TokenState {
+        let (token_address, _) = split_series_key(key);
+        // Keep enough raw prices for the longest window any indicator reads
+        let rsi = self.token_rsi.get(token_address).unwrap_or(&self.rsi);
+        let rsi_longest = rsi.periods.iter().copied().max().unwrap_or(0);
+        let mut longest = rsi_longest.max(self.indicators.history_len());
+        if self.outliers.enabled {
+            longest = longest.max(self.outliers.window);
+        }
+        
+        let candle_mode = rsi.mode_for(token_address) == RsiMode::Candle;
+        
+        TokenState {
+            history: PriceHistory::new(longest + 10, &rsi.periods, rsi.weighting, rsi.arithmetic),
+            candle_rsi: candle_mode.then(|| PriceHistory::new(rsi_longest + 10, &rsi.periods, rsi.weighting, rsi.arithmetic)),
+            // The main candle series already covers its own interval
+            timeframe_rsi: rsi
+                .timeframes_secs
+                .iter()
+                .filter(|&&secs| !(candle_mode && secs == rsi.candle_interval_secs))
+                .map(|&secs| (secs, PriceHistory::new(rsi_longest + 10, &rsi.periods, rsi.weighting, rsi.arithmetic)))
+                .collect(),
+            candles: (!self.candle_intervals.is_empty())
+                .then(|| CandleAggregator::new(&self.candle_intervals)),
+            indicators: self.indicators.instances(token_address),
+            dedup: self.dedup.enabled.then(|| DedupCache::new(&self.dedup)),
+            reorder: self
+                .reorder
+                .enabled
+                .then(|| ReorderBuffer::new(self.reorder.delay_secs)),
+            last_trade: i64::MIN,
+            outlier_streak: OutlierStreak::default(),
+        }
+    }
+    
+    /// Process incoming trade and calculate every enabled indicator
+    ///
+    /// Returns one RSI message per period that has enough data, plus a
+    /// message for each other indicator that produced a value (may be empty).
+    pub fn process_trade(&mut self, trade: TradeMessage) -> Vec<IndicatorOutput> {
+        if !self.filter.accepts(&trade.token_address) {
+            self.filtered_trades += 1;
+            return Vec::new();
+        }
+        
+        let time = trade.block_time_secs().unwrap_or_else(|| chrono::Utc::now().timestamp());
+        self.evict_idle(time);
+        
+        // Each quote currency of a token is a separate series
+        let key = trade.series_key().into_owned();
+        if !self.token_histories.contains_key(&key) {
+            self.make_room();
+            let state = self.new_token_state(&key);
+            self.token_histories.insert(key.clone(), state);
+        }
+        let state = self
+            .token_histories
+            .get_mut(&key)
+            .expect("token state was just inserted");
+        state.last_trade = state.last_trade.max(time);
+        
+        // A re-delivered trade must not be counted twice
+        if let Some(dedup) = &mut state.dedup {
+            if dedup.is_duplicate(&trade.transaction_signature, time) {
+                self.duplicate_trades += 1;
+                return Vec::new();
+            }
+        }
+        
+        // One bad print must not skew the next `period` RSI values, but a
+        // run of them on one side is the token repricing
+        if self.outliers.enabled {
+            match outliers::check(&self.outliers, &state.history, trade.price_in_sol) {
+                Some(outlier) if !state.outlier_streak.repriced(&self.outliers, &outlier) => {
+                    self.outlier_trades += 1;
+                    if !self.outliers.quarantine {
+                        return Vec::new();
+                    }
+                    let timestamp = format_unix_time(time);
+                    return vec![IndicatorOutput::Quarantine(QuarantinedTrade::new(&trade, &outlier, timestamp))];
+                }
+                Some(_) => {}
+                None => state.outlier_streak.reset(),
+            }
+        }
+        
+        let pending = PendingTrade {
+            time,
+            price_in_sol: trade.price_in_sol,
+            amount_in_sol: trade.amount_in_sol,
+            is_buy: trade.is_buy,
+            transaction_signature: trade.transaction_signature.clone(),
+        };
+        
+        // With reordering, indicators only see trades the token's watermark
+        // has passed, in block_time order
+        let ready = match &mut state.reorder {
+            Some(buffer) => {
+                if !buffer.push(pending) {
+                    self.late_trades += 1;
+                    return Vec::new();
+                }
+                buffer.release()
+            }
+            None => vec![pending],
+        };
+        
+        ready
+            .into_iter()
+            .flat_map(|pending| self.apply_trade(&key, pending))
+            .collect()
+    }
+    
+    /// Feed one in-order trade to every enabled indicator
+    fn apply_trade(&mut self, token_address: &str, trade: PendingTrade) -> Vec<IndicatorOutput> {
+        let state = self
+            .token_histories
+            .get_mut(token_address)
+            .expect("token state exists for buffered trades");
+        
+        // Add new price to history
+        state.history.add_price(trade.price_in_sol, trade.amount_in_sol);
+        
+        // Outputs carry the trade time, so replays and lagging consumers
+        // publish the same timestamps as live processing. Candles are
+        // bucketed by it too; candle-based indicators only update when a
+        // bar is finalized
+        let time = trade.time;
+        let timestamp = format_unix_time(time);
+        let mut outputs = Vec::new();
+        
+        // Tick-mode tokens get RSI on every trade
+        if state.candle_rsi.is_none() {
+            let rsi = self.token_rsi.get(split_series_key(token_address).0).unwrap_or(&self.rsi);
+            let (rsi_msgs, changes) = rsi_outputs(
+                rsi,
+                self.rsi_schema_version,
+                &mut state.history,
+                token_address,
+                trade.price_in_sol,
+                &timestamp,
+                "tick",
+            );
+            outputs.extend(rsi_msgs);
+            if self.signal_events.enabled {
+                outputs.extend(changes.into_iter().map(IndicatorOutput::SignalChange));
+            }
+            
+            let input = RsiInput {
+                token_address,
+                price: trade.price_in_sol,
+                timestamp: &timestamp,
+                history: &state.history,
+                smoothing: rsi.smoothing,
+            };
+            outputs.extend(state.indicators.iter_mut().filter_map(|indicator| indicator.on_rsi(&input)));
+        }
+        
+        let input = TradeInput {
+            token_address,
+            price: trade.price_in_sol,
+            amount: trade.amount_in_sol,
+            is_buy: trade.is_buy,
+            transaction_signature: &trade.transaction_signature,
+            timestamp: &timestamp,
+            history: &state.history,
+        };
+        outputs.extend(state.indicators.iter_mut().filter_map(|indicator| indicator.on_trade(&input)));
+        for output in &mut outputs {
+            output.set_series(token_address);
+        }
+        
+        if let Some(candles) = &mut state.candles {
+            if !candles.add_trade(time, trade.price_in_sol, trade.amount_in_sol) {
+                self.late_trades += 1;
+            }
+        }
+        
+        // Advancing event time may complete bars for any token
+        if time > self.watermark {
+            self.watermark = time;
+            outputs.extend(self.finalize_candles());
+        }
+        
+        outputs
+    }
+    
+    /// Close every bar that is still open, e.g. at the end of a replay
+    pub fn finalize_all(&mut self) -> Vec<IndicatorOutput> {
+        // Release held-back trades first, across all tokens in time order
+        let mut pending: Vec<(String, PendingTrade)> = Vec::new();
+        for (token_address, state) in &mut self.token_histories {
+            if let Some(buffer) = &mut state.reorder {
+                pending.extend(buffer.drain().into_iter().map(|trade| (token_address.clone(), trade)));
+            }
+        }
+        pending.sort_by_key(|(_, trade)| trade.time);
+        
+        let mut outputs: Vec<IndicatorOutput> = pending
+            .into_iter()
+            .flat_map(|(token_address, trade)| self.apply_trade(&token_address, trade))
+            .collect();
+        
+        self.watermark = i64::MAX - self.candles.allowed_lateness_secs;
+        outputs.extend(self.finalize_candles());
+        outputs
+    }
+    
+    /// Close every bar the watermark has passed and run candle-based indicators
+    fn finalize_candles(&mut self) -> Vec<IndicatorOutput> {
+        let mut outputs = Vec::new();
+        
+        for (token_address, state) in &mut self.token_histories {
+            let Some(candles) = &mut state.candles else {
+                continue;
+            };
+            let first = outputs.len();
+            
+            for candle in candles.finalize(self.watermark, self.candles.allowed_lateness_secs) {
+                if self.candles.enabled && self.candles.intervals_secs.contains(&candle.interval_secs) {
+                    outputs.push(IndicatorOutput::Candle(CandleMessage::new(token_address, &candle)));
+                }
+                
+                if candle.interval_secs == self.rsi.candle_interval_secs {
+                    if let Some(history) = &mut state.candle_rsi {
+                        let rsi = self.token_rsi.get(split_series_key(token_address).0).unwrap_or(&self.rsi);
+                        let timestamp = format_unix_time(candle.end_time());
+                        history.add_price(candle.close, candle.volume);
+                        let (rsi_msgs, changes) = rsi_outputs(
+                            rsi,
+                            self.rsi_schema_version,
+                            history,
+                            token_address,
+                            candle.close,
+                            &timestamp,
+                            &candles::format_interval(candle.interval_secs),
+                        );
+                        outputs.extend(rsi_msgs);
+                        if self.signal_events.enabled {
+                            outputs.extend(changes.into_iter().map(IndicatorOutput::SignalChange));
+                        }
+                        
+                        let input = RsiInput {
+                            token_address,
+                            price: candle.close,
+                            timestamp: &timestamp,
+                            history,
+                            smoothing: rsi.smoothing,
+                        };
+                        outputs.extend(state.indicators.iter_mut().filter_map(|indicator| indicator.on_rsi(&input)));
+                    }
+                }
+                
+                if let Some(history) = state.timeframe_rsi.get_mut(&candle.interval_secs) {
+                    let rsi = self.token_rsi.get(split_series_key(token_address).0).unwrap_or(&self.rsi);
+                    history.add_price(candle.close, candle.volume);
+                    let (rsi_msgs, changes) = rsi_outputs(
+                        rsi,
+                        self.rsi_schema_version,
+                        history,
+                        token_address,
+                        candle.close,
+                        &format_unix_time(candle.end_time()),
+                        &candles::format_interval(candle.interval_secs),
+                    );
+                    outputs.extend(rsi_msgs);
+                    if self.signal_events.enabled {
+                        outputs.extend(changes.into_iter().map(IndicatorOutput::SignalChange));
+                    }
+                }
+                
+                outputs.extend(
+                    state
+                        .indicators
+                        .iter_mut()
+                        .filter_map(|indicator| indicator.on_bar(token_address, &candle)),
+                );
+            }
+            for output in &mut outputs[first..] {
+                output.set_series(token_address);
+            }
+        }
+        
+        outputs
+    }
+}
+
+/// Build RSI messages for every configured period that has enough data,
+/// plus a change event for each period whose signal moved
+fn rsi_outputs(
+    config: &RsiConfig,
+    schema_version: u32,
+    history: &mut PriceHistory,
+    key: &str,
+    price: f64,
+    timestamp: &str,
+    timeframe: &str,
+) -> (Vec<IndicatorOutput>, Vec<SignalChangeMessage>) {
+    let (token_address, quote_currency) = split_series_key(key);
+    let mut outputs = Vec::new();
+    let mut changes = Vec::new();
+    let processed_at = chrono::Utc::now().to_rfc3339();
+    
+    for &period in &config.periods {
+        let Some(rsi) = history.rsi(period, config.smoothing) else {
+            continue;
+        };
+        
+        let signal = config.signal_after(rsi, history.signals.get(&period).map(String::as_str));
+        // The first value of a series is not a change
+        if let Some(previous) = history.signals.insert(period, signal.to_string()).filter(|previous| previous != signal) {
+            changes.push(SignalChangeMessage {
+                event: "signal_changed",
+                token_address: token_address.to_string(),
+                quote_currency: quote_currency.to_string(),
+                period,
+                timeframe: timeframe.to_string(),
+                previous_signal: previous,
+                signal: signal.to_string(),
+                rsi_value: rsi,
+                current_price: price,
+                timestamp: timestamp.to_string(),
+                processed_at: processed_at.clone(),
+            });
+        }
+        
+        let averages = match config.smoothing {
+            Smoothing::Wilder => history.wilder_averages(period),
+            Smoothing::Simple => None,
+        };
+        outputs.push(IndicatorOutput::Rsi(RsiMessage {
+            schema_version,
+            token_address: token_address.to_string(),
+            quote_currency: quote_currency.to_string(),
+            rsi_value: rsi,
+            current_price: price,
+            timestamp: timestamp.to_string(),
+            processed_at: processed_at.clone(),
+            period,
+            signal: signal.to_string(),
+            timeframe: timeframe.to_string(),
+            avg_gain: averages.map(|(gain, _)| gain),
+            avg_loss: averages.map(|(_, loss)| loss),
+        }));
+    }
+    
+    (outputs, changes)
+}
+
+/// What a token's RSI histories are shaped by; any change means rebuilding them
+fn series_layout(rsi: &RsiConfig, token_address: &str) -> serde_json::Value {
+    serde_json::json!({
+        "periods": rsi.periods,
+        "weighting": rsi.weighting,
+        "arithmetic": rsi.arithmetic,
+        "mode": rsi.mode_for(token_address),
+        "candle_interval_secs": rsi.candle_interval_secs,
+        "timeframes_secs": rsi.timeframes_secs,
+    })
+}
+
+/// Candle intervals needed by the candle publisher and candle-based indicators
+fn candle_intervals(config: &Config) -> Vec<i64> {
+    let mut intervals = Vec::new();
+    if config.candles.enabled {
+        intervals.extend(&config.candles.intervals_secs);
+    }
+    intervals.extend(IndicatorRegistry::new(config).candle_intervals());
+    if config.uses_candle_rsi() {
+        intervals.push(config.rsi.candle_interval_secs);
+    }
+    intervals.extend(&config.rsi.timeframes_secs);
+    intervals.sort_unstable();
+    intervals.dedup();
+    intervals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Closes from Wilder's worked 14-period example (as tabulated by StockCharts)
+    const WILDER_CLOSES: [f64; 21] = [
+        44.3389, 44.0902, 44.1497, 43.6124, 44.3278, 44.8264, 45.0955, 45.4245, 45.8433, 46.0826, 45.8931, 46.0328,
+        45.6140, 46.2820, 46.2820, 46.0028, 46.0328, 46.4116, 46.2222, 45.6439, 46.2122,
+    ];
+
+    /// RSI after each close from the 15th on
+    const WILDER_RSI: [f64; 7] = [70.53, 66.32, 66.55, 69.41, 66.36, 57.97, 62.93];
+
+    fn trade(token_address: &str, price: f64, time: i64, signature: &str) -> TradeMessage {
+        TradeMessage {
+            token_address: token_address.to_string(),
+            price_in_sol: price,
+            block_time: time.to_string(),
+            transaction_signature: signature.to_string(),
+            is_buy: true,
+            amount_in_sol: 1.0,
+            processed_timestamp: String::new(),
+            quote_currency: String::new(),
+        }
+    }
+
+    #[test]
+    fn wilder_rsi_matches_known_values() {
+        let mut history = PriceHistory::new(100, &[14], RsiWeighting::Equal, Arithmetic::Float);
+        let mut values = Vec::new();
+        for close in WILDER_CLOSES {
+            history.add_price(close, 1.0);
+            values.extend(history.rsi(14, Smoothing::Wilder));
+        }
+
+        assert_eq!(values.len(), WILDER_RSI.len());
+        for (value, expected) in values.iter().zip(WILDER_RSI) {
+            assert!((value - expected).abs() < 0.01, "RSI {} instead of {}", value, expected);
+        }
+    }
+
+    #[test]
+    fn wilder_rsi_needs_a_full_seed_window() {
+        let mut history = PriceHistory::new(100, &[14], RsiWeighting::Equal, Arithmetic::Float);
+        for close in &WILDER_CLOSES[..14] {
+            history.add_price(*close, 1.0);
+        }
+        assert_eq!(history.rsi(14, Smoothing::Wilder), None);
+        assert_eq!(history.rsi(9, Smoothing::Wilder), None);
+    }
+
+    #[test]
+    fn price_history_keeps_the_latest_prices() {
+        let mut history = PriceHistory::new(5, &[3], RsiWeighting::Equal, Arithmetic::Float);
+        for price in 1..=12 {
+            history.add_price(price as f64, 1.0);
+        }
+
+        assert_eq!(history.len(), 5);
+        assert_eq!(history.samples(), 12);
+        assert_eq!(history.recent(5).collect::<Vec<_>>(), [8.0, 9.0, 10.0, 11.0, 12.0]);
+        assert_eq!(history.recent(50).count(), 5);
+        assert_eq!(history.price_ago(0), Some(12.0));
+        assert_eq!(history.price_ago(4), Some(8.0));
+        assert_eq!(history.price_ago(5), None);
+        assert_eq!(history.high_low(5), Some((12.0, 8.0)));
+        // Wilder state keeps going past the buffer; one-sided gains are 100
+        assert_eq!(history.rsi(3, Smoothing::Wilder), Some(100.0));
+    }
+
+    #[test]
+    fn filtered_tokens_are_never_tracked() {
+        let mut config = Config::default();
+        config.filter.deny_tokens.insert("denied".to_string());
+        let mut calculator = RsiCalculator::new(&config);
+
+        assert!(calculator.process_trade(trade("denied", 1.0, 1_700_000_000, "a")).is_empty());
+        calculator.process_trade(trade("allowed", 1.0, 1_700_000_000, "b"));
+
+        assert_eq!(calculator.filtered_trades(), 1);
+        assert!(!calculator.tokens().contains_key("denied"));
+        assert!(calculator.tokens().contains_key("allowed"));
+    }
+
+    #[test]
+    fn redelivered_trades_are_counted_once() {
+        let mut config = Config::default();
+        config.dedup.enabled = true;
+        let mut calculator = RsiCalculator::new(&config);
+
+        for _ in 0..3 {
+            calculator.process_trade(trade("token", 1.0, 1_700_000_000, "same"));
+        }
+        // Trades without a signature can't be told apart, so all of them count
+        for _ in 0..3 {
+            calculator.process_trade(trade("token", 1.0, 1_700_000_000, ""));
+        }
+
+        assert_eq!(calculator.duplicate_trades(), 2);
+        assert_eq!(calculator.samples("token", "tick"), 4);
+    }
+
+    #[test]
+    fn quotes_are_separate_series() {
+        let mut calculator = RsiCalculator::new(&Config::default());
+        calculator.process_trade(trade("token", 1.0, 1_700_000_000, "a"));
+        let mut in_usd = trade("token", 150.0, 1_700_000_000, "a");
+        in_usd.quote_currency = "usd".to_string();
+        calculator.process_trade(in_usd);
+
+        assert_eq!(calculator.samples("token", "tick"), 1);
+        assert_eq!(calculator.samples("token:USD", "tick"), 1);
+        assert_eq!(split_series_key("token:USD"), ("token", "USD"));
+    }
+}
@@ -0,0 +1,170 @@
+use log::{debug, warn};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::MissedTickBehavior;
+
+use crate::config::{Config, MarketIndexConfig};
+use crate::indicators::IndicatorOutput;
+use crate::TradeMessage;
+use crate::{split_series_key, DEFAULT_QUOTE_CURRENCY};
+
+/// Key of every index message, so they all land on one partition in order
+const KEY: &str = "market";
+
+/// Aggregate momentum across the basket, published every interval
+#[derive(Debug, Serialize)]
+pub struct MarketIndexMessage {
+    pub timestamp: String,
+    pub period: usize,
+    pub timeframe: String,
+    /// Tokens with a recent enough RSI
+    pub tokens: usize,
+    pub average_rsi: f64,
+    /// RSI weighted by each token's SOL volume since the previous index;
+    /// None when nothing traded
+    pub volume_weighted_rsi: Option<f64>,
+    pub volume_sol: f64,
+    /// Share of tokens in each zone, in percent; the strong levels count
+    /// towards their zone
+    pub overbought_pct: f64,
+    pub oversold_pct: f64,
+}
+
+/// Latest RSI and recent volume of one token
+#[derive(Default)]
+struct Reading {
+    rsi: Option<(f64, String, Instant)>,
+    // SOL traded since the previous index
+    volume: f64,
+}
+
+/// Collects RSI values and volume from every calculator for the index
+pub struct MarketIndex {
+    config: MarketIndexConfig,
+    period: usize,
+    readings: Mutex<HashMap<String, Reading>>,
+}
+
+impl MarketIndex {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.market_index.clone(),
+            period: config
+                .market_index
+                .period
+                .unwrap_or_else(|| config.rsi.periods.first().copied().unwrap_or_default()),
+            readings: Mutex::default(),
+        }
+    }
+
+    /// Whether a series counts towards the index; volumes are weighed in
+    /// SOL, so only SOL-quoted series do
+    fn in_basket(&self, series_key: &str) -> bool {
+        let (token_address, quote) = split_series_key(series_key);
+        quote == DEFAULT_QUOTE_CURRENCY
+            && (self.config.tokens.is_empty() || self.config.tokens.contains(token_address))
+    }
+
+    pub fn observe_trade(&self, trade: &TradeMessage) {
+        if !self.in_basket(&trade.series_key()) {
+            return;
+        }
+        let mut readings = self.readings.lock().unwrap_or_else(|e| e.into_inner());
+        readings.entry(trade.token_address.clone()).or_default().volume += trade.amount_in_sol;
+    }
+
+    pub fn observe(&self, output: &IndicatorOutput) {
+        let IndicatorOutput::Rsi(msg) = output else {
+            return;
+        };
+        if msg.period != self.period || msg.timeframe != self.config.timeframe || !self.in_basket(&output.series_key()) {
+            return;
+        }
+        let mut readings = self.readings.lock().unwrap_or_else(|e| e.into_inner());
+        readings.entry(msg.token_address.clone()).or_default().rsi =
+            Some((msg.rsi_value, msg.signal.clone(), Instant::now()));
+    }
+
+    /// The index over tokens with a recent RSI, starting a new volume
+    /// window; None while no token qualifies
+    fn take(&self) -> Option<MarketIndexMessage> {
+        let max_age = Duration::from_secs(self.config.max_age_secs);
+        let mut readings = self.readings.lock().unwrap_or_else(|e| e.into_inner());
+        // Tokens gone quiet would otherwise pile up forever
+        readings.retain(|_, reading| {
+            reading.volume > 0.0 || reading.rsi.as_ref().is_some_and(|(_, _, at)| at.elapsed() <= max_age)
+        });
+
+        let mut tokens = 0;
+        let (mut rsi_sum, mut weighted_sum, mut volume) = (0.0, 0.0, 0.0);
+        let (mut overbought, mut oversold) = (0, 0);
+        for reading in readings.values_mut() {
+            let traded = std::mem::take(&mut reading.volume);
+            let Some((rsi, signal, at)) = &reading.rsi else {
+                continue;
+            };
+            if at.elapsed() > max_age {
+                continue;
+            }
+            tokens += 1;
+            rsi_sum += rsi;
+            weighted_sum += rsi * traded;
+            volume += traded;
+            match signal.as_str() {
+                "overbought" | "strongly_overbought" => overbought += 1,
+                "oversold" | "strongly_oversold" => oversold += 1,
+                _ => {}
+            }
+        }
+        if tokens == 0 {
+            return None;
+        }
+
+        let pct = |count: usize| count as f64 / tokens as f64 * 100.0;
+        Some(MarketIndexMessage {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            period: self.period,
+            timeframe: self.config.timeframe.clone(),
+            tokens,
+            average_rsi: rsi_sum / tokens as f64,
+            volume_weighted_rsi: (volume > 0.0).then(|| weighted_sum / volume),
+            volume_sol: volume,
+            overbought_pct: pct(overbought),
+            oversold_pct: pct(oversold),
+        })
+    }
+}
+
+/// Publish the index every `interval_secs` until the process exits
+pub async fn publish(index: Arc<MarketIndex>, producer: FutureProducer) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(index.config.interval_secs));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick is immediate, before anything was observed
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+        let Some(message) = index.take() else {
+            debug!("No token has a recent RSI, skipping the market index");
+            continue;
+        };
+        let payload = match serde_json::to_string(&message) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("⚠️  Failed to serialize market index: {}", e);
+                continue;
+            }
+        };
+        let record = FutureRecord::to(&index.config.topic).key(KEY).payload(&payload);
+        match producer.send(record, Duration::from_secs(0)).await {
+            Ok(_) => debug!(
+                "📈 Market index: {} tokens, average RSI {:.2}",
+                message.tokens, message.average_rsi
+            ),
+            Err((e, _)) => warn!("⚠️  Failed to publish market index to '{}': {}", index.config.topic, e),
+        }
+    }
+}
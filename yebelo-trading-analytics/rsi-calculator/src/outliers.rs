@@ -0,0 +1,204 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::OutlierConfig;
+use crate::{PriceHistory, TradeMessage};
+
+/// Scales a median absolute deviation to a standard deviation for normal data
+const MAD_SCALE: f64 = 1.4826;
+
+/// A trade rejected as a price outlier, published to the quarantine topic
+#[derive(Debug, Serialize)]
+pub struct QuarantinedTrade {
+    pub token_address: String,
+    pub quote_currency: String,
+    pub price_in_sol: f64,
+    pub block_time: String,
+    pub transaction_signature: String,
+    pub is_buy: bool,
+    pub amount_in_sol: f64,
+    /// Median of the recent prices the trade was compared with
+    pub median_price: f64,
+    /// Distance from the median in (scaled) median absolute deviations
+    pub deviations: f64,
+    pub timestamp: String,
+}
+
+/// How far a price strays from a token's recent prices
+pub struct Outlier {
+    pub median: f64,
+    pub deviations: f64,
+    /// Whether the price is above the median
+    pub above: bool,
+}
+
+/// Consecutive outliers of a token on the same side of its median
+///
+/// Rejected prices never enter the history, so without this a token that
+/// genuinely moves to a new level would be rejected forever.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct OutlierStreak {
+    above: bool,
+    count: usize,
+}
+
+impl OutlierStreak {
+    /// Count another outlier; true once `accept_after` in a row fell on the
+    /// same side, until a price within bounds ends the streak
+    pub fn repriced(&mut self, config: &OutlierConfig, outlier: &Outlier) -> bool {
+        if self.count == 0 || self.above != outlier.above {
+            self.above = outlier.above;
+            self.count = 0;
+        }
+        self.count += 1;
+        config.accept_after > 0 && self.count >= config.accept_after
+    }
+
+    /// A price within bounds ends the streak
+    pub fn reset(&mut self) {
+        self.count = 0;
+    }
+}
+
+impl QuarantinedTrade {
+    pub fn new(trade: &TradeMessage, outlier: &Outlier, timestamp: String) -> Self {
+        Self {
+            token_address: trade.token_address.clone(),
+            quote_currency: trade.quote().into_owned(),
+            price_in_sol: trade.price_in_sol,
+            block_time: trade.block_time.clone(),
+            transaction_signature: trade.transaction_signature.clone(),
+            is_buy: trade.is_buy,
+            amount_in_sol: trade.amount_in_sol,
+            median_price: outlier.median,
+            deviations: outlier.deviations,
+            timestamp,
+        }
+    }
+}
+
+/// Check `price` against the last `window` prices of a token
+///
+/// The spread is the scaled median absolute deviation, but never less than `min_deviation_pct` of the
+/// median so a token trading flat can still move. Returns `None` while the
+/// history is shorter than the window or the price is within bounds.
+pub fn check(config: &OutlierConfig, history: &PriceHistory, price: f64) -> Option<Outlier> {
+    if history.len() < config.window {
+        return None;
+    }
+
+    let mut recent: Vec<f64> = history.recent(config.window).collect();
+    let center = median(&mut recent);
+    let mut distances: Vec<f64> = recent.iter().map(|p| (p - center).abs()).collect();
+    let mad = median(&mut distances) * MAD_SCALE;
+
+    let spread = mad.max(center * config.min_deviation_pct / 100.0);
+    if spread <= 0.0 {
+        return None;
+    }
+    let deviations = (price - center).abs() / spread;
+    (deviations > config.max_deviations).then_some(Outlier {
+        median: center,
+        deviations,
+        above: price > center,
+    })
+}
+
+/// Median of a non-empty slice, reordering it
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Arithmetic, RsiWeighting};
+
+    fn config(accept_after: usize) -> OutlierConfig {
+        OutlierConfig {
+            enabled: true,
+            window: 20,
+            accept_after,
+            ..OutlierConfig::default()
+        }
+    }
+
+    /// A history trading flat around 1.0
+    fn flat_history() -> PriceHistory {
+        let mut history = PriceHistory::new(30, &[14], RsiWeighting::Equal, Arithmetic::Float);
+        for i in 0..20 {
+            history.add_price(1.0 + (i % 3) as f64 * 0.001, 1.0);
+        }
+        history
+    }
+
+    /// Feed `prices` the way the calculator does, returning how many were rejected
+    fn feed(config: &OutlierConfig, history: &mut PriceHistory, streak: &mut OutlierStreak, prices: &[f64]) -> usize {
+        let mut rejected = 0;
+        for &price in prices {
+            match check(config, history, price) {
+                Some(outlier) if !streak.repriced(config, &outlier) => {
+                    rejected += 1;
+                    continue;
+                }
+                Some(_) => {}
+                None => streak.reset(),
+            }
+            history.add_price(price, 1.0);
+        }
+        rejected
+    }
+
+    #[test]
+    fn sustained_level_shift_is_accepted() {
+        let config = config(3);
+        let mut history = flat_history();
+        let mut streak = OutlierStreak::default();
+
+        let rejected = feed(&config, &mut history, &mut streak, &[3.0; 40]);
+
+        assert_eq!(rejected, 2);
+        assert!(check(&config, &history, 3.0).is_none());
+        assert!(check(&config, &history, 1.0).is_some());
+    }
+
+    #[test]
+    fn isolated_bad_prints_stay_rejected() {
+        let config = config(3);
+        let mut history = flat_history();
+        let mut streak = OutlierStreak::default();
+
+        let prices: Vec<f64> = (0..30).flat_map(|i| [if i % 2 == 0 { 5.0 } else { 0.2 }, 1.0]).collect();
+        let rejected = feed(&config, &mut history, &mut streak, &prices);
+
+        assert_eq!(rejected, 30);
+        assert!(check(&config, &history, 5.0).is_some());
+    }
+
+    #[test]
+    fn alternating_sides_do_not_build_a_streak() {
+        let config = config(3);
+        let mut history = flat_history();
+        let mut streak = OutlierStreak::default();
+
+        let rejected = feed(&config, &mut history, &mut streak, &[5.0, 0.2, 5.0, 0.2, 5.0, 0.2]);
+
+        assert_eq!(rejected, 6);
+    }
+
+    #[test]
+    fn level_shift_is_never_accepted_when_disabled() {
+        let config = config(0);
+        let mut history = flat_history();
+        let mut streak = OutlierStreak::default();
+
+        let rejected = feed(&config, &mut history, &mut streak, &[2.0; 40]);
+
+        assert_eq!(rejected, 40);
+    }
+}
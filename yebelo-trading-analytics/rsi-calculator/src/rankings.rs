@@ -0,0 +1,190 @@
+use log::{debug, warn};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::MissedTickBehavior;
+
+use crate::config::{Config, RankingsConfig};
+use crate::indicators::IndicatorOutput;
+use crate::TradeMessage;
+use crate::split_series_key;
+
+/// Key of every rankings message, so they all land on one partition in order
+const KEY: &str = "rankings";
+
+/// Leaderboards of the tokens that moved most, published every interval
+#[derive(Debug, Serialize)]
+pub struct RankingsMessage {
+    pub timestamp: String,
+    /// Price and volume changes are measured between consecutive rankings
+    pub interval_secs: u64,
+    pub period: usize,
+    pub timeframe: String,
+    /// Furthest RSI from 50 first
+    pub rsi_extremity: Vec<RankedToken>,
+    /// Largest absolute price change first
+    pub price_change: Vec<RankedToken>,
+    /// Largest volume increase over the previous interval first
+    pub volume_change: Vec<RankedToken>,
+}
+
+/// One token's standing, the same in every leaderboard
+#[derive(Debug, Clone, Serialize)]
+pub struct RankedToken {
+    pub token_address: String,
+    pub quote_currency: String,
+    /// Absent when the RSI has not updated within `max_age_secs`
+    pub rsi: Option<f64>,
+    pub price: f64,
+    /// Absent for a token's first interval
+    pub price_change_pct: Option<f64>,
+    pub volume_sol: f64,
+    /// Absent while nothing traded in the previous interval
+    pub volume_change_pct: Option<f64>,
+}
+
+/// What one token did since the previous ranking, and before
+struct Mover {
+    rsi: Option<(f64, Instant)>,
+    price: f64,
+    // Price at the previous ranking
+    previous_price: Option<f64>,
+    volume: f64,
+    // Volume of the previous interval
+    previous_volume: Option<f64>,
+    last_trade: Instant,
+}
+
+/// Collects prices, volume and RSI values from every calculator for the
+/// rankings
+pub struct Rankings {
+    config: RankingsConfig,
+    period: usize,
+    movers: Mutex<HashMap<String, Mover>>,
+}
+
+impl Rankings {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.rankings.clone(),
+            period: config
+                .rankings
+                .period
+                .unwrap_or_else(|| config.rsi.periods.first().copied().unwrap_or_default()),
+            movers: Mutex::default(),
+        }
+    }
+
+    pub fn observe_trade(&self, trade: &TradeMessage) {
+        let mut movers = self.movers.lock().unwrap_or_else(|e| e.into_inner());
+        let mover = movers.entry(trade.series_key().into_owned()).or_insert_with(|| Mover {
+            rsi: None,
+            price: trade.price_in_sol,
+            previous_price: None,
+            volume: 0.0,
+            previous_volume: None,
+            last_trade: Instant::now(),
+        });
+        mover.price = trade.price_in_sol;
+        mover.volume += trade.amount_in_sol;
+        mover.last_trade = Instant::now();
+    }
+
+    pub fn observe(&self, output: &IndicatorOutput) {
+        let IndicatorOutput::Rsi(msg) = output else {
+            return;
+        };
+        if msg.period != self.period || msg.timeframe != self.config.timeframe {
+            return;
+        }
+        let mut movers = self.movers.lock().unwrap_or_else(|e| e.into_inner());
+        // Only traded tokens are ranked, and every RSI follows a trade
+        if let Some(mover) = movers.get_mut(&*output.series_key()) {
+            mover.rsi = Some((msg.rsi_value, Instant::now()));
+        }
+    }
+
+    /// The leaderboards as of now, starting a new interval; None while no
+    /// token is tracked
+    fn take(&self) -> Option<RankingsMessage> {
+        let max_age = Duration::from_secs(self.config.max_age_secs);
+        let mut movers = self.movers.lock().unwrap_or_else(|e| e.into_inner());
+        // Tokens gone quiet would otherwise pile up forever
+        movers.retain(|_, mover| mover.last_trade.elapsed() <= max_age);
+        if movers.is_empty() {
+            return None;
+        }
+
+        let pct = |from: f64, to: f64| (from > 0.0).then(|| (to - from) / from * 100.0);
+        let ranked: Vec<RankedToken> = movers
+            .iter_mut()
+            .map(|(key, mover)| {
+                let (token_address, quote_currency) = split_series_key(key);
+                let ranked = RankedToken {
+                    token_address: token_address.to_string(),
+                    quote_currency: quote_currency.to_string(),
+                    rsi: mover.rsi.filter(|(_, at)| at.elapsed() <= max_age).map(|(rsi, _)| rsi),
+                    price: mover.price,
+                    price_change_pct: mover.previous_price.and_then(|previous| pct(previous, mover.price)),
+                    volume_sol: mover.volume,
+                    volume_change_pct: mover.previous_volume.and_then(|previous| pct(previous, mover.volume)),
+                };
+                mover.previous_price = Some(mover.price);
+                mover.previous_volume = Some(std::mem::take(&mut mover.volume));
+                ranked
+            })
+            .collect();
+
+        let top = |score: fn(&RankedToken) -> Option<f64>| {
+            let mut scored: Vec<(f64, &RankedToken)> =
+                ranked.iter().filter_map(|token| Some((score(token)?, token))).collect();
+            scored.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+            scored.into_iter().take(self.config.top_n).map(|(_, token)| token.clone()).collect()
+        };
+        Some(RankingsMessage {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            interval_secs: self.config.interval_secs,
+            period: self.period,
+            timeframe: self.config.timeframe.clone(),
+            rsi_extremity: top(|token| token.rsi.map(|rsi| (rsi - 50.0).abs())),
+            price_change: top(|token| token.price_change_pct.map(f64::abs)),
+            volume_change: top(|token| token.volume_change_pct),
+        })
+    }
+}
+
+/// Publish the rankings every `interval_secs` until the process exits
+pub async fn publish(rankings: Arc<Rankings>, producer: FutureProducer) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(rankings.config.interval_secs));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick is immediate, before anything was observed
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+        let Some(message) = rankings.take() else {
+            debug!("No token traded recently, skipping the rankings");
+            continue;
+        };
+        let payload = match serde_json::to_string(&message) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("⚠️  Failed to serialize rankings: {}", e);
+                continue;
+            }
+        };
+        let record = FutureRecord::to(&rankings.config.topic).key(KEY).payload(&payload);
+        match producer.send(record, Duration::from_secs(0)).await {
+            Ok(_) => debug!(
+                "🏆 Rankings: {} RSI extremes, {} price movers, {} volume movers",
+                message.rsi_extremity.len(),
+                message.price_change.len(),
+                message.volume_change.len()
+            ),
+            Err((e, _)) => warn!("⚠️  Failed to publish rankings to '{}': {}", rankings.config.topic, e),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+use log::{error, info, warn};
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::error::KafkaError;
+use std::time::{Duration, Instant};
+
+use crate::config::{KafkaConfig, ReconnectConfig};
+
+/// Upper bound for each metadata request while waiting for the cluster
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Backs the consumer loop off while the cluster is unreachable
+///
+/// librdkafka reconnects to brokers on its own, but a consumer that lost the
+/// whole cluster can come back without a partition assignment. Consecutive
+/// errors grow the pause between polls, and every `resubscribe_after` of
+/// them the subscription is dropped and made again to force a fresh join.
+pub struct Reconnect {
+    config: ReconnectConfig,
+    subscriptions: Vec<String>,
+    errors: u32,
+    // When the current run of errors started
+    since: Option<Instant>,
+}
+
+impl Reconnect {
+    pub fn new(kafka: &KafkaConfig) -> Self {
+        Self {
+            config: kafka.reconnect.clone(),
+            subscriptions: kafka.subscriptions().into_iter().map(str::to_string).collect(),
+            errors: 0,
+            since: None,
+        }
+    }
+
+    /// Record a consumer error and wait before the next poll
+    ///
+    /// Cancel-safe: the error is counted before waiting.
+    pub async fn on_error(&mut self, consumer: &StreamConsumer, e: &KafkaError) {
+        self.errors += 1;
+        self.since.get_or_insert_with(Instant::now);
+        let backoff = self.config.backoff(self.errors);
+        error!("❌ Kafka error ({} in a row): {}, polling again in {:?}", self.errors, e, backoff);
+
+        if self.errors.is_multiple_of(self.config.resubscribe_after) {
+            consumer.unsubscribe();
+            let topics: Vec<&str> = self.subscriptions.iter().map(String::as_str).collect();
+            match consumer.subscribe(&topics) {
+                Ok(()) => warn!("🔁 Re-subscribed to {} after {} consecutive errors", topics.join(", "), self.errors),
+                Err(e) => error!("❌ Failed to re-subscribe: {}", e),
+            }
+        }
+
+        tokio::time::sleep(backoff).await;
+    }
+
+    /// A message arrived, so the cluster is reachable again
+    pub fn on_message(&mut self) {
+        if let Some(since) = self.since.take() {
+            info!(
+                "🔌 Reconnected to Kafka after {} errors over {:.1}s",
+                self.errors,
+                since.elapsed().as_secs_f64()
+            );
+            self.errors = 0;
+        }
+    }
+}
+
+/// Wait until one of the bootstrap brokers answers, backing off between
+/// attempts, for up to `reconnect.startup_timeout_secs`
+pub async fn wait_for_cluster(consumer: &StreamConsumer, kafka: &KafkaConfig) -> Result<()> {
+    let deadline = Instant::now() + Duration::from_secs(kafka.reconnect.startup_timeout_secs);
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let probe = consumer.fetch_metadata(Some(&kafka.input_topic), PROBE_TIMEOUT);
+        let e = match probe {
+            Ok(_) => return Ok(()),
+            Err(e) => e,
+        };
+
+        let backoff = kafka.reconnect.backoff(attempt);
+        if Instant::now() + backoff >= deadline {
+            return Err(e).with_context(|| format!("No Kafka broker reachable at {}", kafka.brokers));
+        }
+        warn!("⚠️  No broker reachable at {} ({}), retrying in {:?}", kafka.brokers, e, backoff);
+        tokio::time::sleep(backoff).await;
+    }
+}
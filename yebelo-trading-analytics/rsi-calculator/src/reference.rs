@@ -0,0 +1,134 @@
+//! Reference fixtures: price series with indicator values from a known TA
+//! implementation, to catch our indicators drifting from the textbook
+//!
+//! A fixture is a TOML file holding the prices, the config to run them under
+//! and one column of expected values per checked output field. Columns are
+//! named `<indicator>:<period>` (or just `<indicator>` for outputs without a
+//! period) followed by a JSON pointer into the output message, e.g.
+//! `RSI:14/rsi_value` or `MA/ema/10`; `nan` marks prices the reference has no
+//! value for yet.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::indicators::IndicatorOutput;
+use crate::{RsiCalculator, TradeMessage};
+
+/// Token the fixture's prices are traded as
+const TOKEN: &str = "REFERENCE";
+
+/// First fixture trade's `block_time`; later ones follow a minute apart
+const START_TIME: i64 = 1_700_000_000;
+
+#[derive(Debug, Deserialize)]
+pub struct Fixture {
+    pub description: String,
+    /// Largest accepted difference from an expected value
+    pub tolerance: f64,
+    pub prices: Vec<f64>,
+    #[serde(default)]
+    pub config: Config,
+    /// Expected values per column, one per price
+    pub expected: BTreeMap<String, Vec<f64>>,
+}
+
+/// A value that differs from the reference by more than the tolerance
+#[derive(Debug)]
+pub struct Drift {
+    pub column: String,
+    /// Index of the price the value was calculated at
+    pub index: usize,
+    pub expected: f64,
+    /// None when no value was produced at all
+    pub actual: Option<f64>,
+}
+
+impl Fixture {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read fixture {}", path.display()))?;
+        let mut fixture: Fixture = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse fixture {}", path.display()))?;
+
+        for (column, values) in &fixture.expected {
+            if values.len() != fixture.prices.len() {
+                anyhow::bail!(
+                    "Fixture column '{}' has {} values for {} prices",
+                    column,
+                    values.len(),
+                    fixture.prices.len()
+                );
+            }
+            if !column.contains('/') {
+                anyhow::bail!("Fixture column '{}' needs a JSON pointer, e.g. '{}/value'", column, column);
+            }
+        }
+        fixture.config.validate()?;
+        Ok(fixture)
+    }
+
+    /// Number of expected values the fixture checks
+    pub fn checks(&self) -> usize {
+        self.expected.values().flatten().filter(|value| !value.is_nan()).count()
+    }
+
+    /// Run the prices through a fresh calculator and compare every expected
+    /// value; empty when all are within tolerance
+    pub fn check(&self) -> Result<Vec<Drift>> {
+        let mut calculator = RsiCalculator::new(&self.config);
+        let mut drift = Vec::new();
+
+        for (index, &price) in self.prices.iter().enumerate() {
+            let trade = TradeMessage {
+                token_address: TOKEN.to_string(),
+                price_in_sol: price,
+                block_time: (START_TIME + index as i64 * 60).to_string(),
+                transaction_signature: format!("reference-{}", index),
+                is_buy: true,
+                amount_in_sol: 1.0,
+                processed_timestamp: String::new(),
+                quote_currency: String::new(),
+            };
+            let outputs = calculator.process_trade(trade);
+
+            for (column, values) in &self.expected {
+                let expected = values[index];
+                if expected.is_nan() {
+                    continue;
+                }
+                let actual = lookup(&outputs, column)?;
+                if !actual.is_some_and(|actual| (actual - expected).abs() <= self.tolerance) {
+                    drift.push(Drift {
+                        column: column.clone(),
+                        index,
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+        Ok(drift)
+    }
+}
+
+/// Value of `column` among one trade's outputs
+fn lookup(outputs: &[IndicatorOutput], column: &str) -> Result<Option<f64>> {
+    let (selector, pointer) = column.split_at(column.find('/').unwrap_or(column.len()));
+    for output in outputs {
+        let matches = match selector.split_once(':') {
+            Some((kind, period)) => output.kind() == kind && output.period().as_deref() == Some(period),
+            None => output.kind() == selector,
+        };
+        if !matches {
+            continue;
+        }
+        let json: serde_json::Value = serde_json::from_str(&output.to_json()?)?;
+        if let Some(value) = json.pointer(pointer).and_then(serde_json::Value::as_f64) {
+            return Ok(Some(value));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,339 @@
+use anyhow::Result;
+use log::{error, info, warn};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
+
+use crate::cli::RunArgs;
+use crate::config::Config;
+
+/// Token resets buffered per calculator before it starts missing some
+const RESET_BUFFER: usize = 64;
+
+/// Where the running config came from, so it can be built again the same way
+pub struct ConfigSource {
+    pub path: Option<PathBuf>,
+    pub args: RunArgs,
+}
+
+impl ConfigSource {
+    /// Config file, then `RSI_CALC_*` variables, then command-line flags
+    pub fn load(&self) -> Result<Config> {
+        let mut config = Config::load(self.path.as_deref())?;
+        self.args.apply(&mut config);
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// A runtime change to one token, made through the admin API
+#[derive(Debug, Clone)]
+pub enum Edit {
+    /// Add the token to `filter.allow_tokens` and drop it from `deny_tokens`
+    Allow(String),
+    /// Drop the token from `filter.allow_tokens`, or deny it when there is no
+    /// allowlist; refused when it is the only allowed token, since an empty
+    /// allowlist processes every token
+    Remove(String),
+    /// Forget the token's indicator state; its next trade starts it afresh
+    Reset(String),
+    /// Override the token's RSI thresholds
+    Thresholds(String, Thresholds),
+}
+
+/// RSI threshold overrides for one token; unset fields are left as they are
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thresholds {
+    pub oversold: Option<f64>,
+    pub overbought: Option<f64>,
+    pub strongly_oversold: Option<f64>,
+    pub strongly_overbought: Option<f64>,
+    pub hysteresis: Option<f64>,
+}
+
+impl Edit {
+    /// Make the change to `config`; resets leave it as it is
+    fn apply(&self, config: &mut Config) -> Result<()> {
+        match self {
+            Edit::Allow(token) => {
+                config.filter.deny_tokens.remove(token);
+                config.filter.allow_tokens.insert(token.clone());
+            }
+            Edit::Remove(token) => {
+                let allow = &mut config.filter.allow_tokens;
+                if allow.is_empty() {
+                    config.filter.deny_tokens.insert(token.clone());
+                } else if allow.len() == 1 && allow.contains(token) {
+                    anyhow::bail!(
+                        "{} is the only token in filter.allow_tokens; allow another one first, since an empty \
+                         allowlist processes every token",
+                        token
+                    );
+                } else {
+                    allow.remove(token);
+                }
+            }
+            Edit::Reset(_) => {}
+            Edit::Thresholds(token, thresholds) => {
+                let overrides = config.tokens.entry(token.clone()).or_default();
+                overrides.oversold = thresholds.oversold.or(overrides.oversold);
+                overrides.overbought = thresholds.overbought.or(overrides.overbought);
+                overrides.strongly_oversold = thresholds.strongly_oversold.or(overrides.strongly_oversold);
+                overrides.strongly_overbought = thresholds.strongly_overbought.or(overrides.strongly_overbought);
+                overrides.hysteresis = thresholds.hysteresis.or(overrides.hysteresis);
+            }
+        }
+        Ok(())
+    }
+}
+
+enum Request {
+    Reload,
+    Edit(Edit),
+}
+
+/// Asks the reload task to reload or edit the config, e.g. from the admin
+/// endpoints
+#[derive(Clone)]
+pub struct ReloadHandle(mpsc::Sender<(Request, oneshot::Sender<Result<String, String>>)>);
+
+impl ReloadHandle {
+    /// Reload now; what changed, or why the new config was refused
+    pub async fn reload(&self) -> Result<String, String> {
+        self.request(Request::Reload).await
+    }
+
+    /// Apply an edit; what it did, or why it was refused
+    pub async fn edit(&self, edit: Edit) -> Result<String, String> {
+        self.request(Request::Edit(edit)).await
+    }
+
+    async fn request(&self, request: Request) -> Result<String, String> {
+        let (reply, outcome) = oneshot::channel();
+        self.0.send((request, reply)).await.map_err(|_| "Reloading is not running".to_string())?;
+        outcome.await.map_err(|_| "Reload was abandoned".to_string())?
+    }
+}
+
+/// Reload requests and the sending ends of what they produce
+pub struct Reloads {
+    requests: mpsc::Receiver<(Request, oneshot::Sender<Result<String, String>>)>,
+    publish: watch::Sender<Arc<Config>>,
+    resets: broadcast::Sender<String>,
+}
+
+/// What a calculator has to pick up
+pub enum Update {
+    Config(Arc<Config>),
+    /// Forget this token's state
+    Reset(String),
+}
+
+/// Every reloaded or edited config and token reset, for one calculator
+pub struct Updates {
+    configs: watch::Receiver<Arc<Config>>,
+    resets: broadcast::Receiver<String>,
+}
+
+impl Updates {
+    /// The config as of now
+    pub fn config(&self) -> Arc<Config> {
+        self.configs.borrow().clone()
+    }
+
+    /// Wait for the next change; never resolves once reloading has stopped
+    ///
+    /// Cancel-safe, so it can sit in a `select!` loop.
+    pub async fn next(&mut self) -> Update {
+        loop {
+            tokio::select! {
+                Ok(()) = self.configs.changed() => return Update::Config(self.configs.borrow_and_update().clone()),
+                reset = self.resets.recv() => match reset {
+                    Ok(token) => return Update::Reset(token),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("⚠️  Missed {} token resets", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => std::future::pending::<()>().await,
+                },
+            }
+        }
+    }
+}
+
+impl Clone for Updates {
+    fn clone(&self) -> Self {
+        Self {
+            configs: self.configs.clone(),
+            resets: self.resets.resubscribe(),
+        }
+    }
+}
+
+/// A handle to request reloads, the task's end, and the updates it
+/// publishes, starting from `config`
+pub fn channel(config: &Config) -> (ReloadHandle, Reloads, Updates) {
+    let (requests_tx, requests) = mpsc::channel(1);
+    let (publish, configs) = watch::channel(Arc::new(config.clone()));
+    let (resets, resets_rx) = broadcast::channel(RESET_BUFFER);
+    let updates = Updates {
+        configs,
+        resets: resets_rx,
+    };
+    (ReloadHandle(requests_tx), Reloads { requests, publish, resets }, updates)
+}
+
+/// Reload on SIGHUP or a handle's request until every receiver is gone
+///
+/// A config that fails to load or validate is refused and the running one
+/// kept. Only the runtime-safe sections of a new config are published; see
+/// [`Config::reload`]. Edits made through the admin API are applied again on
+/// top of every reloaded file, so they last until the next restart.
+pub async fn listen(source: ConfigSource, mut current: Config, mut reloads: Reloads) {
+    let mut signal = hangup_signal();
+    let mut edits: Vec<Edit> = Vec::new();
+
+    loop {
+        let (request, reply) = tokio::select! {
+            _ = reloads.publish.closed() => break,
+            _ = hangup(&mut signal) => {
+                info!("🔄 SIGHUP received, reloading config");
+                (Request::Reload, None)
+            }
+            Some((request, reply)) = reloads.requests.recv() => (request, Some(reply)),
+        };
+
+        let outcome = match request {
+            Request::Reload => {
+                if reply.is_some() {
+                    info!("🔄 Reload requested, reloading config");
+                }
+                reload(&source, &mut current, &edits, &reloads)
+            }
+            Request::Edit(Edit::Reset(token)) => {
+                info!("🧽 Resetting indicator state of {}", token);
+                let _ = reloads.resets.send(token.clone());
+                Ok(format!("Reset {}", token))
+            }
+            Request::Edit(edit) => {
+                let mut edited = current.clone();
+                match edit.apply(&mut edited).and_then(|()| edited.validate()) {
+                    Ok(()) => {
+                        info!("✏️  Applied {:?}", edit);
+                        current = edited;
+                        reloads.publish.send_replace(Arc::new(current.clone()));
+                        edits.push(edit);
+                        Ok("Applied".to_string())
+                    }
+                    Err(e) => {
+                        warn!("⚠️  Refused {:?}: {:#}", edit, e);
+                        Err(format!("{:#}", e))
+                    }
+                }
+            }
+        };
+        if let Some(reply) = reply {
+            let _ = reply.send(outcome);
+        }
+    }
+}
+
+/// Load the config again, with earlier edits on top, and publish it
+fn reload(source: &ConfigSource, current: &mut Config, edits: &[Edit], reloads: &Reloads) -> Result<String, String> {
+    let loaded = source.load().and_then(|mut new| {
+        for edit in edits {
+            edit.apply(&mut new)?;
+        }
+        new.validate()?;
+        Ok(new)
+    });
+    match loaded {
+        Ok(new) => {
+            let (merged, restart) = current.reload(new);
+            *current = merged;
+            reloads.publish.send_replace(Arc::new(current.clone()));
+            if restart {
+                warn!("⚠️  Reloaded indicator, filter and alert settings; other changes need a restart");
+                Ok("Reloaded; changes outside indicator, filter and alert settings need a restart".to_string())
+            } else {
+                info!("✅ Reloaded config");
+                Ok("Reloaded".to_string())
+            }
+        }
+        Err(e) => {
+            error!("❌ Keeping the running config, reload failed: {:#}", e);
+            Err(format!("{:#}", e))
+        }
+    }
+}
+
+#[cfg(unix)]
+type Hangup = Option<tokio::signal::unix::Signal>;
+#[cfg(not(unix))]
+type Hangup = Option<()>;
+
+#[cfg(unix)]
+fn hangup_signal() -> Hangup {
+    match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+        Ok(signal) => Some(signal),
+        Err(e) => {
+            error!("❌ Failed to listen for SIGHUP: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn hangup_signal() -> Hangup {
+    None
+}
+
+/// Resolve on the next SIGHUP; never where there is none
+async fn hangup(signal: &mut Hangup) {
+    #[cfg(unix)]
+    if let Some(signal) = signal {
+        if signal.recv().await.is_some() {
+            return;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = signal;
+    std::future::pending().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowing(tokens: &[&str]) -> Config {
+        let mut config = Config::default();
+        config.filter.allow_tokens = tokens.iter().map(|token| token.to_string()).collect();
+        config
+    }
+
+    #[test]
+    fn removing_the_last_allowed_token_is_refused() {
+        let mut config = allowing(&["A"]);
+        assert!(Edit::Remove("A".to_string()).apply(&mut config).is_err());
+        assert!(config.filter.allow_tokens.contains("A"));
+        assert!(config.filter.deny_tokens.is_empty());
+    }
+
+    #[test]
+    fn removing_an_allowed_token_keeps_the_others() {
+        let mut config = allowing(&["A", "B"]);
+        Edit::Remove("A".to_string()).apply(&mut config).unwrap();
+        assert_eq!(config.filter.allow_tokens.len(), 1);
+        assert!(config.filter.allow_tokens.contains("B"));
+        assert!(config.filter.deny_tokens.is_empty());
+    }
+
+    #[test]
+    fn removing_without_an_allowlist_denies_the_token() {
+        let mut config = allowing(&[]);
+        Edit::Remove("A".to_string()).apply(&mut config).unwrap();
+        assert!(config.filter.allow_tokens.is_empty());
+        assert!(config.filter.deny_tokens.contains("A"));
+    }
+}
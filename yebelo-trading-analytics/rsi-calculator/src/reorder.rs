@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The parts of a trade the indicators consume, held until released
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTrade {
+    pub time: i64,
+    pub price_in_sol: f64,
+    pub amount_in_sol: f64,
+    #[serde(default)]
+    pub is_buy: bool,
+    #[serde(default)]
+    pub transaction_signature: String,
+}
+
+/// Holds one token's trades back for `delay_secs` of trade time so
+/// stragglers from other partitions can be slotted in before the
+/// indicators see them.
+///
+/// A trade is released once the token's watermark (latest `block_time`
+/// seen minus the delay) reaches it. Trades older than what has already
+/// been released are rejected.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReorderBuffer {
+    delay_secs: i64,
+    // Pending trades by time; equal times keep arrival order
+    pending: BTreeMap<i64, Vec<PendingTrade>>,
+    latest: i64,
+    released_up_to: i64,
+}
+
+impl ReorderBuffer {
+    pub fn new(delay_secs: i64) -> Self {
+        Self {
+            delay_secs,
+            pending: BTreeMap::new(),
+            latest: i64::MIN,
+            released_up_to: i64::MIN,
+        }
+    }
+
+    /// Buffer a trade; returns `false` if it is older than trades already
+    /// released and was dropped
+    pub fn push(&mut self, trade: PendingTrade) -> bool {
+        if trade.time < self.released_up_to {
+            return false;
+        }
+        self.latest = self.latest.max(trade.time);
+        self.pending.entry(trade.time).or_default().push(trade);
+        true
+    }
+
+    /// Trades the watermark has passed, oldest first
+    pub fn release(&mut self) -> Vec<PendingTrade> {
+        let watermark = self.latest.saturating_sub(self.delay_secs);
+        self.release_up_to(watermark)
+    }
+
+    /// Every buffered trade, oldest first, e.g. at the end of a replay
+    pub fn drain(&mut self) -> Vec<PendingTrade> {
+        self.release_up_to(i64::MAX)
+    }
+
+    fn release_up_to(&mut self, watermark: i64) -> Vec<PendingTrade> {
+        let held = match watermark.checked_add(1) {
+            Some(next) => self.pending.split_off(&next),
+            None => BTreeMap::new(),
+        };
+        let ready = std::mem::replace(&mut self.pending, held);
+
+        if let Some(&last) = ready.keys().next_back() {
+            self.released_up_to = self.released_up_to.max(last);
+        }
+        ready.into_values().flatten().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(time: i64, price_in_sol: f64) -> PendingTrade {
+        PendingTrade {
+            time,
+            price_in_sol,
+            amount_in_sol: 1.0,
+            is_buy: true,
+            transaction_signature: String::new(),
+        }
+    }
+
+    fn times(trades: &[PendingTrade]) -> Vec<i64> {
+        trades.iter().map(|trade| trade.time).collect()
+    }
+
+    #[test]
+    fn trades_are_released_once_the_watermark_passes_them() {
+        let mut buffer = ReorderBuffer::new(5);
+        assert!(buffer.push(trade(10, 1.0)));
+        assert!(buffer.push(trade(12, 1.0)));
+        assert!(buffer.release().is_empty());
+
+        assert!(buffer.push(trade(15, 1.0)));
+        assert_eq!(times(&buffer.release()), [10]);
+        // A straggler behind the latest trade is slotted in before release
+        assert!(buffer.push(trade(11, 1.0)));
+        assert!(buffer.push(trade(17, 1.0)));
+        assert_eq!(times(&buffer.release()), [11, 12]);
+    }
+
+    #[test]
+    fn equal_times_keep_arrival_order() {
+        let mut buffer = ReorderBuffer::new(60);
+        for (time, price) in [(10, 1.0), (10, 2.0), (8, 3.0), (10, 4.0)] {
+            buffer.push(trade(time, price));
+        }
+        let prices: Vec<f64> = buffer.drain().iter().map(|trade| trade.price_in_sol).collect();
+        assert_eq!(prices, [3.0, 1.0, 2.0, 4.0]);
+    }
+
+    #[test]
+    fn trades_older_than_released_ones_are_rejected() {
+        let mut buffer = ReorderBuffer::new(0);
+        buffer.push(trade(10, 1.0));
+        assert_eq!(times(&buffer.release()), [10]);
+
+        assert!(!buffer.push(trade(9, 1.0)));
+        assert!(buffer.push(trade(10, 1.0)));
+        assert_eq!(times(&buffer.release()), [10]);
+    }
+
+    #[test]
+    fn drain_releases_everything_held() {
+        let mut buffer = ReorderBuffer::new(600);
+        for time in [30, 10, 20] {
+            buffer.push(trade(time, 1.0));
+        }
+        assert!(buffer.release().is_empty());
+        assert_eq!(times(&buffer.drain()), [10, 20, 30]);
+        assert!(buffer.drain().is_empty());
+        assert!(!buffer.push(trade(29, 1.0)));
+    }
+}
@@ -0,0 +1,122 @@
+//! Versioned JSON shapes of [`RsiMessage`]
+//!
+//! Every RSI message carries the `schema_version` it was built for. The
+//! current version serializes [`RsiMessage`] as is; older versions are kept
+//! here as borrowed views so a rollout can keep emitting what existing
+//! consumers parse until they are upgraded.
+
+use serde::{Serialize, Serializer};
+
+use crate::RsiMessage;
+
+/// `schema_version` of [`RsiMessage`] as defined today
+///
+/// - 1: the original fields, without `schema_version` itself
+/// - 2: adds `schema_version` and `processed_at`
+/// - 3: adds `avg_gain` and `avg_loss`
+/// - 4: adds `quote_currency`
+pub const RSI_SCHEMA_VERSION: u32 = 4;
+
+/// Oldest version that can still be emitted
+pub const RSI_MIN_SCHEMA_VERSION: u32 = 1;
+
+/// RSI message as published before versioning
+#[derive(Debug, Serialize)]
+pub struct RsiMessageV1<'a> {
+    pub token_address: &'a str,
+    pub rsi_value: f64,
+    pub current_price: f64,
+    pub timestamp: &'a str,
+    pub period: usize,
+    pub signal: &'a str,
+    pub timeframe: &'a str,
+}
+
+impl<'a> From<&'a RsiMessage> for RsiMessageV1<'a> {
+    fn from(msg: &'a RsiMessage) -> Self {
+        Self {
+            token_address: &msg.token_address,
+            rsi_value: msg.rsi_value,
+            current_price: msg.current_price,
+            timestamp: &msg.timestamp,
+            period: msg.period,
+            signal: &msg.signal,
+            timeframe: &msg.timeframe,
+        }
+    }
+}
+
+/// RSI message before the smoothed averages were published
+#[derive(Debug, Serialize)]
+pub struct RsiMessageV2<'a> {
+    pub schema_version: u32,
+    pub token_address: &'a str,
+    pub rsi_value: f64,
+    pub current_price: f64,
+    pub timestamp: &'a str,
+    pub processed_at: &'a str,
+    pub period: usize,
+    pub signal: &'a str,
+    pub timeframe: &'a str,
+}
+
+impl<'a> From<&'a RsiMessage> for RsiMessageV2<'a> {
+    fn from(msg: &'a RsiMessage) -> Self {
+        Self {
+            schema_version: 2,
+            token_address: &msg.token_address,
+            rsi_value: msg.rsi_value,
+            current_price: msg.current_price,
+            timestamp: &msg.timestamp,
+            processed_at: &msg.processed_at,
+            period: msg.period,
+            signal: &msg.signal,
+            timeframe: &msg.timeframe,
+        }
+    }
+}
+
+/// RSI message before series were split by quote currency
+#[derive(Debug, Serialize)]
+pub struct RsiMessageV3<'a> {
+    pub schema_version: u32,
+    pub token_address: &'a str,
+    pub rsi_value: f64,
+    pub current_price: f64,
+    pub timestamp: &'a str,
+    pub processed_at: &'a str,
+    pub period: usize,
+    pub signal: &'a str,
+    pub timeframe: &'a str,
+    pub avg_gain: Option<f64>,
+    pub avg_loss: Option<f64>,
+}
+
+impl<'a> From<&'a RsiMessage> for RsiMessageV3<'a> {
+    fn from(msg: &'a RsiMessage) -> Self {
+        Self {
+            schema_version: 3,
+            token_address: &msg.token_address,
+            rsi_value: msg.rsi_value,
+            current_price: msg.current_price,
+            timestamp: &msg.timestamp,
+            processed_at: &msg.processed_at,
+            period: msg.period,
+            signal: &msg.signal,
+            timeframe: &msg.timeframe,
+            avg_gain: msg.avg_gain,
+            avg_loss: msg.avg_loss,
+        }
+    }
+}
+
+/// Serialize an RSI message in the shape of its `schema_version`, in any
+/// serde format
+pub fn serialize_rsi<S: Serializer>(msg: &RsiMessage, serializer: S) -> Result<S::Ok, S::Error> {
+    match msg.schema_version {
+        1 => RsiMessageV1::from(msg).serialize(serializer),
+        2 => RsiMessageV2::from(msg).serialize(serializer),
+        3 => RsiMessageV3::from(msg).serialize(serializer),
+        _ => msg.serialize(serializer),
+    }
+}
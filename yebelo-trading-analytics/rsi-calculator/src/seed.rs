@@ -0,0 +1,86 @@
+use anyhow::{Context, Result};
+use log::{debug, info};
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::message::Message;
+use crate::RsiSeed;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::config::{Config, MessageFormat};
+use crate::warmup::{RangeReader, Ranges};
+use crate::service::consumer_client_config;
+use crate::RsiCalculator;
+
+/// Upper bound for each broker query made while locating the tail
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Seed `calculator` from the tail of the RSI output topic
+///
+/// The last `tail_messages` of every partition are read and each Wilder RSI
+/// series resumes from its latest message, so a restart continues the
+/// published values instead of starting over. Needs JSON or MessagePack
+/// output (the csv format publishes JSON), which carry the smoothed averages.
+pub async fn run(config: &Config, calculator: &mut RsiCalculator) -> Result<()> {
+    let format = config.kafka.format;
+    if !matches!(format, MessageFormat::Json | MessageFormat::MsgPack | MessageFormat::Csv) {
+        anyhow::bail!("seeding from '{}' needs JSON or MessagePack output", config.kafka.output_topic);
+    }
+
+    // Never commits, so the group only has to be distinct from the consumer's
+    let consumer: StreamConsumer = consumer_client_config(&config.kafka)
+        .set("group.id", format!("{}-seed", config.kafka.group_id))
+        .set("enable.auto.commit", "false")
+        .set("enable.auto.offset.store", "false")
+        .create()
+        .context("Failed to create seeding consumer")?;
+
+    let topic = config.kafka.output_topic.as_str();
+    let metadata = consumer
+        .fetch_metadata(Some(topic), QUERY_TIMEOUT)
+        .with_context(|| format!("Failed to fetch metadata for '{}'", topic))?;
+    let mut ranges = Ranges::new();
+    for partition in metadata.topics().iter().flat_map(|topic| topic.partitions()) {
+        let (low, high) = consumer.fetch_watermarks(topic, partition.id(), QUERY_TIMEOUT)?;
+        let start = low.max(high - config.cold_start.tail_messages);
+        if start < high {
+            ranges.insert((topic.to_string(), partition.id()), (start, high));
+        }
+    }
+    if ranges.is_empty() {
+        info!("🌱 Nothing to seed from: '{}' is empty", topic);
+        return Ok(());
+    }
+
+    let started = Instant::now();
+    let mut reader = RangeReader::new(consumer, ranges, Duration::from_secs(config.cold_start.timeout_secs))?;
+
+    // Each series is keyed by partition, so the last message read is its latest
+    let mut latest: HashMap<(String, String, usize, String), RsiSeed> = HashMap::new();
+    while let Some(message) = reader.next().await {
+        let Some(payload) = message.payload() else {
+            continue;
+        };
+        let seed = match format {
+            MessageFormat::MsgPack => rmp_serde::from_slice::<RsiSeed>(payload).map_err(anyhow::Error::from),
+            _ => serde_json::from_slice::<RsiSeed>(payload).map_err(anyhow::Error::from),
+        };
+        match seed {
+            Ok(seed) => {
+                let key = (seed.token_address.clone(), seed.quote_currency.clone(), seed.period, seed.timeframe.clone());
+                latest.insert(key, seed);
+            }
+            Err(e) => debug!("Skipping unreadable RSI message while seeding: {}", e),
+        }
+    }
+
+    let seeded = latest.values().filter(|seed| calculator.seed(seed)).count();
+    info!(
+        "🌱 Seeded {} of {} RSI series ({} tokens) from '{}' in {:.1}s",
+        seeded,
+        latest.len(),
+        calculator.tokens().len(),
+        topic,
+        started.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
@@ -0,0 +1,962 @@
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
+use rdkafka::message::Message;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use log::{debug, info, warn, error};
+use anyhow::{Result, Context};
+use opentelemetry::trace::{SpanKind, TraceContextExt};
+use opentelemetry::KeyValue;
+
+use crate::{
+    api, correlation, dead_letter, health, lag, market_index, rankings, reconnect, reload, seed, sol_usd, telemetry,
+    volume, warmup, workers,
+};
+use crate::alerts::Alerts;
+use crate::api::ApiState;
+use crate::codec::Codec;
+use crate::correlation::Correlations;
+use crate::delivery::DeliveryPipeline;
+use crate::headers::OutputHeaders;
+use crate::health::Health;
+use crate::market_index::MarketIndex;
+use crate::rankings::Rankings;
+use crate::reconnect::Reconnect;
+use crate::reload::{ConfigSource, Update};
+use crate::config::{Config, KafkaConfig, MessageFormat};
+use crate::indicators::IndicatorOutput;
+use crate::validation::{Invalid, TradeValidator};
+use crate::{RsiCalculator, TradeMessage};
+use crate::sinks::{SinkTasks, Sinks};
+use crate::sol_usd::SolUsd;
+use crate::state_store::StateStore;
+use crate::transactions::TransactionBatch;
+use crate::volume::Volumes;
+
+/// How long the consumer loop waits for a message before reporting itself idle
+pub(crate) const POLL_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Timeout for transactional producer operations in exactly-once mode
+const TRANSACTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The first 8 characters of a token address, for log lines
+fn short_address(address: &str) -> &str {
+    address.get(..8).unwrap_or(address)
+}
+
+/// Log a freshly calculated indicator value
+pub(crate) fn log_output(output: &IndicatorOutput) {
+    match output {
+        IndicatorOutput::Rsi(rsi_msg) => {
+            let token_short = short_address(&rsi_msg.token_address);
+            
+            info!(
+                "📈 Token: {}... | Price: {:.8} SOL | RSI({}, {}): {:.2} | Signal: {}",
+                token_short,
+                rsi_msg.current_price,
+                rsi_msg.period,
+                rsi_msg.timeframe,
+                rsi_msg.rsi_value,
+                rsi_msg.signal
+            );
+        }
+        other => {
+            debug!("📈 Token: {}... | {} updated", short_address(other.token_address()), other.kind());
+        }
+    }
+}
+
+/// Why a consumed trade was not processed, and the topic it is forwarded to
+///
+/// Invalid trades go to the validation topic, undecodable ones to the
+/// dead-letter topic; either may be disabled.
+pub(crate) fn rejection<'a>(config: &'a Config, error: &anyhow::Error) -> (String, Option<&'a str>) {
+    match error.downcast_ref::<Invalid>() {
+        Some(invalid) => {
+            debug!("Rejected invalid trade: {}", invalid);
+            (format!("validation: {}", invalid), config.validation.topic.as_deref())
+        }
+        None => {
+            warn!("⚠️  Failed to parse trade message: {:#}", error);
+            let topic = config.dead_letter.enabled.then_some(config.dead_letter.topic.as_str());
+            (format!("parse error: {:#}", error), topic)
+        }
+    }
+}
+
+/// Decode a consumed message into its trades, each checked by the validator
+///
+/// A payload that fails to decode is a single error. Trades of a batch come
+/// with their own JSON, so a rejected one can be dead-lettered on its own.
+/// Fields lenient parsing fixed are counted in `health` for the trades that
+/// pass.
+pub(crate) async fn decode_checked(
+    codec: &mut Codec,
+    validator: &mut TradeValidator,
+    health: &Health,
+    topic: &str,
+    payload: &[u8],
+) -> Vec<(Result<TradeMessage>, Option<Vec<u8>>)> {
+    let trades = match codec.decode_trades(topic, payload).await {
+        Ok(trades) => trades,
+        Err(e) => return vec![(Err(e), None)],
+    };
+    let batched = trades.len() > 1;
+    trades
+        .into_iter()
+        .enumerate()
+        .map(|(index, trade)| {
+            let single = batched.then(|| serde_json::to_vec(&trade).ok()).flatten();
+            let checked = validator.check(trade).map_err(anyhow::Error::from);
+            if checked.is_ok() {
+                health.record_fallbacks(codec.fallbacks(index));
+            }
+            (checked, single)
+        })
+        .collect()
+}
+
+/// Summarize the trades that failed validation, by reason
+pub(crate) fn log_rejections(validator: &TradeValidator) {
+    if validator.rejected_total() > 0 {
+        let reasons: Vec<String> = validator
+            .rejected()
+            .iter()
+            .map(|(invalid, count)| format!("{} {}", count, invalid))
+            .collect();
+        info!("🚫 Invalid trades: {}", reasons.join(", "));
+    }
+}
+
+/// Summarize the trade fields lenient parsing had to fix, by field
+pub(crate) fn log_fallbacks(health: &Health) {
+    let fallbacks = health.fallbacks();
+    if fallbacks.is_empty() {
+        return;
+    }
+    let fields: Vec<String> = fallbacks
+        .iter()
+        .map(|((field, fallback), count)| format!("{} {} {}", count, field, fallback))
+        .collect();
+    info!("🩹 Leniently parsed fields: {}", fields.join(", "));
+}
+
+/// Everything besides Kafka that sees each trade and indicator output
+pub(crate) struct Observers {
+    alerts: Option<Alerts>,
+    api: Option<Arc<ApiState>>,
+    market_index: Option<Arc<MarketIndex>>,
+    correlations: Option<Arc<Correlations>>,
+    volumes: Option<Arc<Volumes>>,
+    rankings: Option<Arc<Rankings>>,
+    sinks: Sinks,
+    /// Reference price trades are also calculated in USD with
+    sol_usd: Option<Arc<SolUsd>>,
+}
+
+impl Observers {
+    /// Handles for another calculator (worker) sharing the same destinations
+    pub(crate) fn fork(&self) -> Self {
+        Self {
+            alerts: self.alerts.as_ref().map(Alerts::fork),
+            api: self.api.clone(),
+            market_index: self.market_index.clone(),
+            correlations: self.correlations.clone(),
+            volumes: self.volumes.clone(),
+            rankings: self.rankings.clone(),
+            sinks: self.sinks.clone(),
+            sol_usd: self.sol_usd.clone(),
+        }
+    }
+    
+    /// Calculate a trade, then its USD-normalized copy when there is one
+    pub(crate) fn calculate(&self, calculator: &mut RsiCalculator, trade: TradeMessage) -> Vec<IndicatorOutput> {
+        sol_usd::calculate(self.sol_usd.as_deref(), calculator, trade)
+    }
+    
+    pub(crate) async fn trade(&self, trade: &TradeMessage) {
+        if let Some(api) = &self.api {
+            api.record_trade(trade);
+        }
+        if let Some(market_index) = &self.market_index {
+            market_index.observe_trade(trade);
+        }
+        if let Some(correlations) = &self.correlations {
+            correlations.observe_trade(trade);
+        }
+        if let Some(volumes) = &self.volumes {
+            volumes.observe_trade(trade);
+        }
+        if let Some(rankings) = &self.rankings {
+            rankings.observe_trade(trade);
+        }
+        self.sinks.write_trade(trade).await;
+    }
+    
+    /// After a trade was calculated: its token's state and any evictions
+    pub(crate) fn calculated(&self, token_address: &str, calculator: &mut RsiCalculator) {
+        let evicted = calculator.take_evicted();
+        if let Some(api) = &self.api {
+            api.record_state(token_address, calculator);
+            api.record_evictions(&evicted);
+        }
+    }
+    
+    /// Outputs that never reached their topic, by token
+    pub(crate) fn produce_failed(&self, token_addresses: &[String]) {
+        if let Some(api) = &self.api {
+            api.record_produce_failures(token_addresses);
+        }
+    }
+    
+    pub(crate) async fn output(&mut self, output: &IndicatorOutput, calculator: &RsiCalculator) {
+        if let Some(alerts) = &mut self.alerts {
+            alerts.observe(output);
+        }
+        if let Some(api) = &self.api {
+            api.observe(output, calculator);
+        }
+        if let Some(market_index) = &self.market_index {
+            market_index.observe(output);
+        }
+        if let Some(rankings) = &self.rankings {
+            rankings.observe(output);
+        }
+        self.sinks.write_output(output).await;
+    }
+    
+    /// Pick up reloaded alert routes
+    pub(crate) fn reconfigure(&mut self, config: &Config) {
+        match &mut self.alerts {
+            Some(alerts) => alerts.reconfigure(&config.alerts),
+            None if config.alerts.enabled => warn!("⚠️  Alerts were disabled at startup; enabling them needs a restart"),
+            None => {}
+        }
+    }
+    
+    /// Drop every handle and wait for the sinks to write what is queued
+    async fn close(self, tasks: SinkTasks, timeout: Duration) {
+        drop(self);
+        if tokio::time::timeout(timeout, tasks.join()).await.is_err() {
+            warn!("⚠️  Sinks did not finish writing within {}s", timeout.as_secs());
+        }
+    }
+}
+
+/// Base client config shared by every Kafka client: brokers plus SASL/TLS settings
+pub(crate) fn kafka_client_config(kafka: &KafkaConfig) -> ClientConfig {
+    let mut client = ClientConfig::new();
+    client
+        .set("bootstrap.servers", &kafka.brokers)
+        .set("security.protocol", kafka.security_protocol.as_str())
+        .set("reconnect.backoff.ms", kafka.reconnect.initial_backoff_ms.to_string())
+        .set("reconnect.backoff.max.ms", kafka.reconnect.max_backoff_ms.to_string());
+    
+    let optional = [
+        ("sasl.mechanism", kafka.sasl_mechanism.clone()),
+        ("sasl.username", kafka.sasl_username.clone()),
+        ("sasl.password", kafka.sasl_password.clone()),
+        ("ssl.ca.location", kafka.ssl_ca_location.as_ref().map(|p| p.display().to_string())),
+        ("ssl.certificate.location", kafka.ssl_certificate_location.as_ref().map(|p| p.display().to_string())),
+        ("ssl.key.location", kafka.ssl_key_location.as_ref().map(|p| p.display().to_string())),
+        ("ssl.key.password", kafka.ssl_key_password.clone()),
+    ];
+    for (key, value) in optional {
+        if let Some(value) = value {
+            client.set(key, value);
+        }
+    }
+    
+    client
+}
+
+/// Client settings shared by every consumer, with `[kafka.consumer.properties]`
+/// on top
+pub(crate) fn consumer_client_config(kafka: &KafkaConfig) -> ClientConfig {
+    let mut client = kafka_client_config(kafka);
+    client.set("session.timeout.ms", kafka.session_timeout_ms.to_string());
+    for (key, value) in &kafka.consumer.properties {
+        client.set(key, value);
+    }
+    client
+}
+
+/// Create Kafka consumer for reading trade data
+fn create_consumer(kafka: &KafkaConfig) -> Result<StreamConsumer> {
+    let consumer: StreamConsumer = consumer_client_config(kafka)
+        .set("group.id", &kafka.group_id)
+        // Offsets are stored once a trade's output is acknowledged and committed
+        // in batches, so nothing is committed before it has been published
+        .set("enable.auto.commit", "false")
+        .set("enable.auto.offset.store", "false")
+        .set("auto.offset.reset", "earliest") // Start from beginning if no offset stored
+        .create()
+        .context("Failed to create consumer")?;
+    
+    consumer
+        .subscribe(&kafka.subscriptions())
+        .context("Failed to subscribe to topic")?;
+    
+    Ok(consumer)
+}
+
+/// Client settings shared by every producer, including its reliability options
+fn producer_client_config(kafka: &KafkaConfig) -> ClientConfig {
+    let mut client = kafka_client_config(kafka);
+    client
+        .set("message.timeout.ms", kafka.message_timeout_ms.to_string())
+        .set("compression.type", &kafka.compression);
+    
+    let optional = [
+        ("acks", kafka.acks.clone()),
+        ("enable.idempotence", kafka.enable_idempotence.map(|enabled| enabled.to_string())),
+        ("retries", kafka.retries.map(|retries| retries.to_string())),
+        ("linger.ms", kafka.linger_ms.map(|ms| ms.to_string())),
+        ("batch.size", kafka.batch_size.map(|bytes| bytes.to_string())),
+    ];
+    for (key, value) in optional {
+        if let Some(value) = value {
+            client.set(key, value);
+        }
+    }
+    for (key, value) in &kafka.producer.properties {
+        client.set(key, value);
+    }
+    
+    client
+}
+
+/// Create Kafka producer for publishing RSI data
+pub(crate) fn create_producer(kafka: &KafkaConfig) -> Result<FutureProducer> {
+    let producer: FutureProducer = producer_client_config(kafka)
+        .create()
+        .context("Failed to create producer")?;
+    
+    Ok(producer)
+}
+
+/// Create a transactional producer for exactly-once mode
+fn create_transactional_producer(kafka: &KafkaConfig, transactional_id: &str) -> Result<FutureProducer> {
+    let producer: FutureProducer = producer_client_config(kafka)
+        .set("transactional.id", transactional_id)
+        .create()
+        .context("Failed to create transactional producer")?;
+    
+    // Fences off any previous instance using the same transactional id
+    producer
+        .init_transactions(TRANSACTION_TIMEOUT)
+        .context("Failed to initialize transactions")?;
+    
+    Ok(producer)
+}
+
+/// Consume trades, calculate RSI and publish results until SIGTERM/SIGINT
+///
+/// `source` is read again on SIGHUP or `POST /config/reload` to pick up
+/// changed indicator, filter and alert settings; the admin API edits them
+/// directly.
+pub async fn run(mut config: Config, source: ConfigSource) -> Result<()> {
+    info!("🚀 Starting RSI Calculator Service");
+    
+    let (reload_handle, reloads, mut updates) = reload::channel(&config);
+    tokio::spawn(reload::listen(source, config.clone(), reloads));
+    
+    // Start health probes before connecting so /healthz answers during startup
+    let health = Arc::new(Health::new(Duration::from_secs(config.health.stall_timeout_secs)));
+    if config.health.enabled {
+        let health_config = config.health.clone();
+        let health = Arc::clone(&health);
+        tokio::spawn(async move {
+            if let Err(e) = health::serve(&health_config, health).await {
+                error!("❌ {:#}", e);
+            }
+        });
+        info!("🩺 Serving /healthz and /readyz on {}", config.health.bind_addr);
+    }
+    
+    let api = config.api.enabled.then(|| Arc::new(ApiState::new(&config.api, reload_handle.clone())));
+    if let Some(api) = &api {
+        let api_config = config.api.clone();
+        let api = Arc::clone(api);
+        tokio::spawn(async move {
+            if let Err(e) = api::serve(&api_config, api).await {
+                error!("❌ {:#}", e);
+            }
+        });
+        info!("🌐 Serving latest indicator values and /ws stream on {}", config.api.bind_addr);
+    }
+    
+    let tracer_provider = telemetry::init(&config.telemetry)?;
+    if config.telemetry.enabled {
+        info!("🔭 Exporting traces to {}", config.telemetry.otlp_endpoint);
+    }
+    
+    // Create consumer and producer
+    for (client, properties) in [("consumer", &config.kafka.consumer), ("producer", &config.kafka.producer)] {
+        if !properties.properties.is_empty() {
+            info!("🔧 librdkafka {} properties: {}", client, properties.describe());
+        }
+    }
+    let consumer = Arc::new(create_consumer(&config.kafka)?);
+    reconnect::wait_for_cluster(&consumer, &config.kafka).await?;
+    health.set_subscribed(true);
+    if config.lag.enabled {
+        tokio::spawn(lag::monitor(config.lag.clone(), Arc::clone(&consumer), Arc::clone(&health)));
+        info!("🐢 Measuring consumer lag every {}s", config.lag.interval_secs);
+    }
+    let producer = match &config.kafka.transactional_id {
+        Some(id) => create_transactional_producer(&config.kafka, id)?,
+        None => create_producer(&config.kafka)?,
+    };
+    let mut transaction = config
+        .kafka
+        .transactional_id
+        .as_ref()
+        .map(|_| TransactionBatch::new(TRANSACTION_TIMEOUT));
+    
+    let mut codec = Codec::new(&config)?;
+    
+    // Initialize RSI calculator
+    let mut calculator = RsiCalculator::new(&config);
+    
+    // Restore indicator state from the last checkpoint, if enabled
+    let state_store = if config.state.enabled {
+        let store = StateStore::open(&config.state, &config.kafka).await?;
+        if let Some(restored) = store.load(&calculator.state_fingerprint()).await? {
+            info!("💾 Restored state for {} tokens from {}", restored.tokens.len(), store.describe());
+            calculator.restore(restored);
+        }
+        Some(store)
+    } else {
+        None
+    };
+    
+    // Reference prices come first so warmed-up trades get their USD series too
+    let sol_usd = if config.usd_normalization.enabled {
+        Some(sol_usd::start(&config).await?)
+    } else {
+        None
+    };
+
+    // Replay recent history unless a checkpoint already brought state back,
+    // or failing that resume from the last published values
+    if config.warmup.enabled && calculator.tokens().is_empty() {
+        if let Err(e) = warmup::run(&config, &mut codec, &mut calculator, sol_usd.as_deref()).await {
+            warn!("⚠️  Warm-up failed, starting cold: {:#}", e);
+        }
+    }
+    if config.cold_start.enabled && calculator.tokens().is_empty() {
+        if let Err(e) = seed::run(&config, &mut calculator).await {
+            warn!("⚠️  Seeding failed, starting cold: {:#}", e);
+        }
+    }
+    let checkpoint_interval = Duration::from_secs(config.state.checkpoint_interval_secs);
+    let mut last_checkpoint = Instant::now();
+    
+    info!("✅ Connected to Redpanda at {}", config.kafka.brokers);
+    if let Some(id) = &config.kafka.transactional_id {
+        info!("🔒 Exactly-once mode: committing in transactions as '{}'", id);
+    }
+    if config.kafka.format == MessageFormat::Avro {
+        info!("📜 Using Avro with Schema Registry at {}", config.schema_registry.url);
+    }
+    info!(
+        "📊 Calculating {:?}-period RSI ({:?} smoothing) for incoming trades",
+        config.rsi.periods,
+        config.rsi.smoothing
+    );
+    if !config.rsi.timeframes_secs.is_empty() {
+        info!("📊 Also publishing RSI on {:?}s candles", config.rsi.timeframes_secs);
+    }
+    calculator.indicators().log_enabled();
+    if config.signal_events.enabled {
+        info!("🚦 Publishing RSI signal changes to '{}'", config.signal_events.topic);
+    }
+    if !config.filter.allow_tokens.is_empty() || !config.filter.deny_tokens.is_empty() {
+        info!(
+            "🔎 Token filter: {} allowed, {} denied",
+            config.filter.allow_tokens.len(),
+            config.filter.deny_tokens.len()
+        );
+    }
+    if config.dedup.enabled {
+        info!(
+            "🧹 Dropping duplicate trades (last {} signatures per token, {}s TTL)",
+            config.dedup.capacity,
+            config.dedup.ttl_secs
+        );
+    }
+    if config.reorder.enabled {
+        info!("⏳ Reordering trades by block_time ({}s watermark delay)", config.reorder.delay_secs);
+    }
+    if config.eviction.enabled {
+        info!(
+            "🗑️  Evicting tokens idle for {}s (max {} tokens)",
+            config.eviction.idle_ttl_secs,
+            match config.eviction.max_tokens {
+                0 => "unlimited".to_string(),
+                max => max.to_string(),
+            }
+        );
+    }
+    if config.dead_letter.enabled {
+        info!("📮 Forwarding unparseable trades to '{}'", config.dead_letter.topic);
+    }
+    let alerts = Alerts::start(&config.alerts)?;
+    if alerts.is_some() {
+        let channels: Vec<&str> = [("Telegram", config.alerts.telegram.enabled), ("Slack", config.alerts.slack.enabled)]
+            .into_iter()
+            .filter_map(|(name, enabled)| enabled.then_some(name))
+            .collect();
+        info!("🔔 Sending signal alerts to {}", channels.join(" and "));
+    }
+    let (sinks, sink_tasks) = Sinks::start(&config)?;
+    if config.clickhouse.enabled {
+        info!(
+            "🗄️  Writing indicators{} to ClickHouse at {}",
+            if config.clickhouse.write_trades { " and trades" } else { "" },
+            config.clickhouse.url
+        );
+    }
+    if config.influxdb.enabled {
+        info!(
+            "📉 Writing RSI and price points to InfluxDB bucket '{}' at {}",
+            config.influxdb.bucket,
+            config.influxdb.url
+        );
+    }
+    if config.redis.enabled {
+        info!("🧰 Caching latest indicator values in Redis under '{}*'", config.redis.key_prefix);
+    }
+    let market_index = if config.market_index.enabled {
+        // Its own producer: the index is published outside any transaction
+        let index = Arc::new(MarketIndex::new(&config));
+        tokio::spawn(market_index::publish(Arc::clone(&index), create_producer(&config.kafka)?));
+        info!(
+            "📈 Publishing a market RSI index to '{}' every {}s",
+            config.market_index.topic,
+            config.market_index.interval_secs
+        );
+        Some(index)
+    } else {
+        None
+    };
+    let correlations = if config.correlation.enabled {
+        let correlations = Arc::new(Correlations::new(&config.correlation));
+        tokio::spawn(correlation::publish(Arc::clone(&correlations), create_producer(&config.kafka)?));
+        info!(
+            "🔗 Publishing {}-token return correlations to '{}' every {}s",
+            config.correlation.tokens.len(),
+            config.correlation.topic,
+            config.correlation.publish_interval_secs
+        );
+        Some(correlations)
+    } else {
+        None
+    };
+    let volumes = if config.volume.enabled {
+        let volumes = Arc::new(Volumes::new(&config.volume));
+        tokio::spawn(volume::publish(Arc::clone(&volumes), create_producer(&config.kafka)?));
+        info!(
+            "📦 Publishing {:?}s volume windows to '{}' every {}s",
+            config.volume.windows_secs,
+            config.volume.topic,
+            config.volume.interval_secs
+        );
+        Some(volumes)
+    } else {
+        None
+    };
+    let rankings = if config.rankings.enabled {
+        let rankings = Arc::new(Rankings::new(&config));
+        tokio::spawn(rankings::publish(Arc::clone(&rankings), create_producer(&config.kafka)?));
+        info!(
+            "🏆 Publishing top {} movers to '{}' every {}s",
+            config.rankings.top_n,
+            config.rankings.topic,
+            config.rankings.interval_secs
+        );
+        Some(rankings)
+    } else {
+        None
+    };
+    let mut observers = Observers {
+        alerts,
+        api,
+        market_index,
+        correlations,
+        volumes,
+        rankings,
+        sinks,
+        sol_usd,
+    };
+    let drain_timeout = Duration::from_secs(config.shutdown.drain_timeout_secs);
+    if config.candles.enabled {
+        info!(
+            "🕯️  Publishing {:?}s candles to '{}'",
+            config.candles.intervals_secs,
+            config.candles.topic
+        );
+    }
+    info!("🔄 Listening for messages on {:?}...\n", config.kafka.subscriptions());
+    
+    // Consume, calculate and publish run as separate stages unless calculator
+    // state and offsets have to move in lockstep (transactions, checkpoints)
+    if config.kafka.transactional_id.is_none() && !config.state.enabled {
+        let result = workers::run(updates, &consumer, &producer, &mut codec, &observers, &health, calculator).await;
+        observers.close(sink_tasks, drain_timeout).await;
+        telemetry::shutdown(tracer_provider);
+        return result;
+    }
+    
+    let mut message_count = 0u64;
+    let mut published_count = 0u64;
+    let mut dead_lettered_count = 0u64;
+    let mut uncommitted = 0u64;
+    let mut last_commit = Instant::now();
+    let commit_interval = Duration::from_millis(config.kafka.commit_interval_ms);
+    let mut delivery_failure = None;
+    
+    // Outputs are pipelined to the broker; a trade's offset is stored once all
+    // of its outputs are acknowledged (in exactly-once mode the transaction
+    // commit covers delivery instead)
+    let mut pipeline: DeliveryPipeline<(String, i32, i64)> = DeliveryPipeline::new(&producer, config.kafka.max_in_flight, &config.retry);
+    let output_headers = OutputHeaders::new(&config.headers);
+    let mut validator = TradeValidator::new(&config.validation);
+    let mut reconnect = Reconnect::new(&config.kafka);
+    
+    // Stop consuming on SIGTERM/SIGINT; the message in hand is always finished first
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    
+    // Main message processing loop
+    loop {
+        // Poll with a timeout so an idle topic still counts as progress
+        let received = tokio::select! {
+            _ = &mut shutdown => break,
+            update = updates.next() => {
+                match update {
+                    Update::Config(new) => {
+                        calculator.reconfigure(&new);
+                        validator.reconfigure(&new.validation);
+                        observers.reconfigure(&new);
+                        config = (*new).clone();
+                    }
+                    Update::Reset(token) => {
+                        calculator.reset_token(&token);
+                    }
+                }
+                continue;
+            }
+            done = pipeline.next(), if !pipeline.is_empty() => {
+                published_count += done.published;
+                observers.produce_failed(&done.failed);
+                if !done.delivered {
+                    if let Some(txn) = &mut transaction {
+                        txn.abort(&producer);
+                    }
+                    let (topic, partition, offset) = done.source;
+                    delivery_failure = Some(format!(
+                        "Failed to publish output for {}[{}] offset {}",
+                        topic, partition, offset
+                    ));
+                    break;
+                }
+                if transaction.is_none() {
+                    let (topic, partition, offset) = done.source;
+                    if let Err(e) = consumer.store_offset(&topic, partition, offset) {
+                        warn!("Failed to store offset: {}", e);
+                    }
+                    uncommitted += 1;
+                }
+                continue;
+            }
+            received = tokio::time::timeout(POLL_TIMEOUT, consumer.recv()) => received,
+        };
+        health.record_poll();
+        
+        // Commit offsets of fully published trades in batches
+        if uncommitted >= config.kafka.commit_batch_size
+            || (uncommitted > 0 && last_commit.elapsed() >= commit_interval)
+        {
+            if let Some(txn) = &mut transaction {
+                if let Err(e) = txn.commit(&producer, &consumer) {
+                    txn.abort(&producer);
+                    delivery_failure = Some(format!("{:#}", e));
+                    break;
+                }
+                uncommitted = 0;
+                last_commit = Instant::now();
+            } else {
+                match consumer.commit_consumer_state(rdkafka::consumer::CommitMode::Async) {
+                    Ok(()) => {
+                        uncommitted = 0;
+                        last_commit = Instant::now();
+                    }
+                    Err(e) => warn!("Failed to commit offsets: {}", e),
+                }
+            }
+        }
+        
+        let Ok(received) = received else {
+            health.set_kafka_connected(consumer.assignment().is_ok_and(|tpl| tpl.count() > 0));
+            continue;
+        };
+        
+        match received {
+            Ok(message) => {
+                message_count += 1;
+                reconnect.on_message();
+                health.set_kafka_connected(true);
+                health.record_message();
+                
+                // Whether every output of this trade reached Kafka
+                let mut delivered = true;
+                
+                // Outputs (including dead letters) join the open transaction
+                if let Some(txn) = &mut transaction {
+                    if let Err(e) = txn.begin(&producer) {
+                        delivery_failure = Some(format!("{:#}", e));
+                        break;
+                    }
+                }
+                
+                pipeline.start((message.topic().to_string(), message.partition(), message.offset()));
+                let trade_span = telemetry::consume_span(&message);
+                
+                // Extract message payload
+                if let Some(payload) = message.payload() {
+                    // Deserialize the trade(s) in the configured wire format,
+                    // then check each before it reaches the calculator
+                    for (trade, single) in decode_checked(&mut codec, &mut validator, &health, message.topic(), payload).await {
+                        match trade {
+                            Ok(trade) => {
+                                observers.trade(&trade).await;
+                                let mut carried = output_headers.carry(&message);
+                            
+                                // Process trade and calculate indicators
+                                let token_address = trade.series_key().into_owned();
+                                let outputs = {
+                                    let calculate = telemetry::stage_span(&trade_span, "calculate", SpanKind::Internal);
+                                    let outputs = observers.calculate(&mut calculator, trade);
+                                    calculate.span().set_attribute(KeyValue::new("indicator.outputs", outputs.len() as i64));
+                                    outputs
+                                };
+                                observers.calculated(&token_address, &mut calculator);
+                            
+                                // Outputs continue the trace from the publish span
+                                let publish = telemetry::stage_span(&trade_span, "publish", SpanKind::Producer);
+                                telemetry::inject(&publish, &mut carried);
+                                for output in outputs {
+                                    log_output(&output);
+                                    observers.output(&output, &calculator).await;
+                                
+                                    for topic in output.topics(&config) {
+                                        // Serialize indicator message for the topic
+                                        let encoded = match codec.encode(&output, topic).await {
+                                            Ok(encoded) => encoded,
+                                            Err(e) => {
+                                                error!("❌ Failed to encode {}: {:#}", output.kind(), e);
+                                                delivered = false;
+                                                continue;
+                                            }
+                                        };
+                                    
+                                        // Publish with the trade's carried headers
+                                        let key = output.key(&config.kafka.key_format);
+                                        let record = FutureRecord::to(topic)
+                                            .key(&key)
+                                            .payload(&encoded)
+                                            .headers(output_headers.build(&carried));
+                                    
+                                        // Queue without waiting for the broker's acknowledgement
+                                        pipeline.send(record, output.kind(), &output.series_key()).await;
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                let (reason, topic) = rejection(&config, &e);
+                                if let Some(topic) = topic {
+                                    match dead_letter::forward(&producer, topic, &message, single.as_deref(), &reason).await {
+                                        Ok(()) => dead_lettered_count += 1,
+                                        Err(e) => {
+                                            error!("❌ {:#}", e);
+                                            delivered = false;
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                
+                // Print statistics every 50 messages
+                if message_count.is_multiple_of(50) {
+                    info!(
+                        "📊 Stats: Processed {} trades | Published {} indicator values ({} in flight) | Filtered {} | Invalid {} | Duplicates {} | Outliers {} | Late trades {} | Evicted {} idle, {} over cap | Dead-lettered {}",
+                        message_count,
+                        published_count,
+                        pipeline.in_flight(),
+                        calculator.filtered_trades(),
+                        validator.rejected_total(),
+                        calculator.duplicate_trades(),
+                        calculator.outlier_trades(),
+                        calculator.late_trades(),
+                        calculator.evicted_idle(),
+                        calculator.evicted_lru(),
+                        dead_lettered_count
+                    );
+                }
+                
+                // Never commit past a trade whose output was lost: stop here so the
+                // next run resumes from the last committed offset (at-least-once)
+                if !delivered {
+                    if let Some(txn) = &mut transaction {
+                        txn.abort(&producer);
+                    }
+                    delivery_failure = Some(format!(
+                        "Failed to publish output for {}[{}] offset {}",
+                        message.topic(),
+                        message.partition(),
+                        message.offset()
+                    ));
+                    break;
+                }
+                if let Some(txn) = &mut transaction {
+                    txn.record(&message);
+                    uncommitted += 1;
+                }
+                
+                // Periodically checkpoint indicator state for restart recovery
+                if let Some(store) = &state_store {
+                    if last_checkpoint.elapsed() >= checkpoint_interval {
+                        last_checkpoint = Instant::now();
+                        
+                        match store
+                            .save(&calculator.state_fingerprint(), calculator.tokens(), calculator.watermark())
+                            .await
+                        {
+                            Ok(count) => debug!("💾 Checkpointed state for {} tokens", count),
+                            Err(e) => error!("❌ Failed to checkpoint state: {:#}", e),
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                health.set_kafka_connected(false);
+                tokio::select! {
+                    _ = &mut shutdown => break,
+                    _ = reconnect.on_error(&consumer, &e) => {}
+                }
+            }
+        }
+    }
+    
+    log_rejections(&validator);
+    log_fallbacks(&health);
+    match &delivery_failure {
+        Some(reason) => error!("❌ {}, stopping without committing it", reason),
+        None => info!("🛑 Shutdown requested, draining (up to {}s)...", config.shutdown.drain_timeout_secs),
+    }
+    // After a failure nothing still in flight may be committed
+    let pipeline = delivery_failure.is_none().then_some(&mut pipeline);
+    match tokio::time::timeout(drain_timeout, drain(&consumer, &producer, pipeline, transaction.as_mut(), state_store.as_ref(), &calculator, drain_timeout)).await {
+        Ok(drained) => info!("👋 Processed {} trades, published {} indicator values", message_count, published_count + drained),
+        Err(_) => warn!("⚠️  Drain timed out after {}s, exiting anyway", config.shutdown.drain_timeout_secs),
+    }
+    observers.close(sink_tasks, drain_timeout).await;
+    telemetry::shutdown(tracer_provider);
+    
+    match delivery_failure {
+        Some(reason) => Err(anyhow::anyhow!(reason)),
+        None => Ok(()),
+    }
+}
+
+/// Resolve on the first SIGINT (Ctrl+C) or SIGTERM
+pub(crate) async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("❌ Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                error!("❌ Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Flush pending output, commit consumed offsets and write a final checkpoint
+///
+/// Returns how many indicator values were acknowledged while draining.
+async fn drain(
+    consumer: &StreamConsumer,
+    producer: &FutureProducer,
+    pipeline: Option<&mut DeliveryPipeline<(String, i32, i64)>>,
+    transaction: Option<&mut TransactionBatch>,
+    state_store: Option<&StateStore>,
+    calculator: &RsiCalculator,
+    timeout: Duration,
+) -> u64 {
+    if let Err(e) = producer.flush(timeout) {
+        error!("❌ Failed to flush producer: {}", e);
+    }
+    
+    // Store offsets of trades still in flight, up to the first lost output
+    let mut published = 0;
+    if let Some(pipeline) = pipeline {
+        while !pipeline.is_empty() {
+            let done = pipeline.next().await;
+            published += done.published;
+            if !done.delivered {
+                break;
+            }
+            let (topic, partition, offset) = done.source;
+            if transaction.is_none() {
+                if let Err(e) = consumer.store_offset(&topic, partition, offset) {
+                    warn!("Failed to store offset: {}", e);
+                }
+            }
+        }
+    }
+    
+    if let Some(txn) = transaction {
+        if let Err(e) = txn.commit(producer, consumer) {
+            error!("❌ {:#}", e);
+            txn.abort(producer);
+        }
+    } else if let Err(e) = consumer.commit_consumer_state(rdkafka::consumer::CommitMode::Sync) {
+        // Nothing consumed yet is also reported as an error; not worth a warning
+        debug!("Failed to commit offsets on shutdown: {}", e);
+    }
+    
+    if let Some(store) = state_store {
+        match store
+            .save(&calculator.state_fingerprint(), calculator.tokens(), calculator.watermark())
+            .await
+        {
+            Ok(count) => info!("💾 Checkpointed state for {} tokens", count),
+            Err(e) => error!("❌ Failed to checkpoint state: {:#}", e),
+        }
+    }
+    
+    published
+}
\ No newline at end of file
@@ -0,0 +1,121 @@
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use super::{BatchSink, SinkQueue};
+use crate::config::ClickHouseConfig;
+use crate::indicators::IndicatorOutput;
+use crate::TradeMessage;
+
+/// Writes indicator outputs (and optionally raw trades) to ClickHouse over
+/// its HTTP interface as `JSONEachRow` inserts.
+///
+/// Expected tables (names configurable):
+///
+/// ```sql
+/// CREATE TABLE indicators (
+///     indicator String, token_address String, data String,
+///     inserted_at DateTime64(3) DEFAULT now64(3)
+/// ) ENGINE = MergeTree ORDER BY (token_address, inserted_at);
+///
+/// CREATE TABLE trades (
+///     token_address String, price_in_sol Float64, block_time String,
+///     transaction_signature String, is_buy Bool, amount_in_sol Float64
+/// ) ENGINE = MergeTree ORDER BY (token_address, block_time);
+/// ```
+#[derive(Clone)]
+pub struct ClickHouse {
+    indicators: SinkQueue,
+    trades: Option<SinkQueue>,
+}
+
+impl ClickHouse {
+    pub fn start(config: &ClickHouseConfig, tasks: &mut Vec<JoinHandle<()>>) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("Failed to create ClickHouse client")?;
+
+        let table = |name: &str| Table {
+            client: client.clone(),
+            config: config.clone(),
+            table: name.to_string(),
+        };
+
+        let (indicators, handle) = super::spawn(table(&config.indicator_table), &config.batch);
+        tasks.push(handle);
+
+        let trades = config.write_trades.then(|| {
+            let (queue, handle) = super::spawn(table(&config.trade_table), &config.batch);
+            tasks.push(handle);
+            queue
+        });
+
+        Ok(Self { indicators, trades })
+    }
+
+    pub async fn write_output(&self, output: &IndicatorOutput) {
+        let Ok(data) = output.to_json() else {
+            return;
+        };
+        let row = json!({
+            "indicator": output.kind(),
+            "token_address": output.token_address(),
+            "data": data,
+        });
+        self.indicators.send(row.to_string()).await;
+    }
+
+    pub async fn write_trade(&self, trade: &TradeMessage) {
+        let Some(trades) = &self.trades else {
+            return;
+        };
+        let row = json!({
+            "token_address": trade.token_address,
+            "price_in_sol": trade.price_in_sol,
+            "block_time": trade.block_time,
+            "transaction_signature": trade.transaction_signature,
+            "is_buy": trade.is_buy,
+            "amount_in_sol": trade.amount_in_sol,
+        });
+        trades.send(row.to_string()).await;
+    }
+}
+
+/// Inserts into one ClickHouse table
+struct Table {
+    client: reqwest::Client,
+    config: ClickHouseConfig,
+    table: String,
+}
+
+impl BatchSink for Table {
+    type Item = String;
+
+    fn name(&self) -> &'static str {
+        "ClickHouse"
+    }
+
+    async fn flush(&mut self, lines: &[String]) -> Result<()> {
+        let query = format!("INSERT INTO {} FORMAT JSONEachRow", self.table);
+        let mut request = self
+            .client
+            .post(&self.config.url)
+            .query(&[("database", self.config.database.as_str()), ("query", query.as_str())])
+            .body(lines.join("\n"));
+        if let Some(username) = &self.config.username {
+            request = request.header("X-ClickHouse-User", username);
+        }
+        if let Some(password) = &self.config.password {
+            request = request.header("X-ClickHouse-Key", password);
+        }
+
+        request
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .with_context(|| format!("Failed to insert {} rows into '{}'", lines.len(), self.table))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::{Context, Result};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use super::{BatchSink, SinkQueue};
+use crate::config::InfluxDbConfig;
+use crate::indicators::IndicatorOutput;
+use crate::TradeMessage;
+
+/// Writes RSI values and trade prices to InfluxDB 2.x as line protocol:
+///
+/// ```text
+/// rsi,token=<address>,period=14,timeframe=tick rsi=28.4,price=0.0012,signal="oversold" <ms>
+/// price,token=<address> price=0.0012,amount=1.5,is_buy=true <ms>
+/// ```
+#[derive(Clone)]
+pub struct InfluxDb {
+    points: SinkQueue,
+    rsi_measurement: String,
+    price_measurement: String,
+}
+
+impl InfluxDb {
+    pub fn start(config: &InfluxDbConfig, tasks: &mut Vec<JoinHandle<()>>) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("Failed to create InfluxDB client")?;
+
+        let writer = Writer {
+            client,
+            url: format!("{}/api/v2/write", config.url.trim_end_matches('/')),
+            config: config.clone(),
+        };
+        let (points, handle) = super::spawn(writer, &config.batch);
+        tasks.push(handle);
+
+        Ok(Self {
+            points,
+            rsi_measurement: escape(&config.rsi_measurement),
+            price_measurement: escape(&config.price_measurement),
+        })
+    }
+
+    pub async fn write_output(&self, output: &IndicatorOutput) {
+        let IndicatorOutput::Rsi(msg) = output else {
+            return;
+        };
+        let Ok(time) = chrono::DateTime::parse_from_rfc3339(&msg.timestamp) else {
+            return;
+        };
+
+        let line = format!(
+            "{},token={},period={},timeframe={} rsi={},price={},signal=\"{}\" {}",
+            self.rsi_measurement,
+            escape(&msg.token_address),
+            msg.period,
+            escape(&msg.timeframe),
+            msg.rsi_value,
+            msg.current_price,
+            msg.signal,
+            time.timestamp_millis()
+        );
+        self.points.send(line).await;
+    }
+
+    pub async fn write_trade(&self, trade: &TradeMessage) {
+        let Some(secs) = trade.block_time_secs() else {
+            return;
+        };
+
+        let line = format!(
+            "{},token={} price={},amount={},is_buy={} {}",
+            self.price_measurement,
+            escape(&trade.token_address),
+            trade.price_in_sol,
+            trade.amount_in_sol,
+            trade.is_buy,
+            secs * 1000
+        );
+        self.points.send(line).await;
+    }
+}
+
+/// Escape commas, spaces and equals signs in measurement names and tag values
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | ' ' | '=') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Posts batches to the v2 write API
+struct Writer {
+    client: reqwest::Client,
+    url: String,
+    config: InfluxDbConfig,
+}
+
+impl BatchSink for Writer {
+    type Item = String;
+
+    fn name(&self) -> &'static str {
+        "InfluxDB"
+    }
+
+    async fn flush(&mut self, lines: &[String]) -> Result<()> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .query(&[
+                ("org", self.config.org.as_str()),
+                ("bucket", self.config.bucket.as_str()),
+                ("precision", "ms"),
+            ])
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .body(lines.join("\n"));
+        if let Some(token) = &self.config.token {
+            request = request.header("Authorization", format!("Token {}", token));
+        }
+
+        request
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .with_context(|| format!("Failed to write {} points to bucket '{}'", lines.len(), self.config.bucket))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use anyhow::Result;
+use log::{error, warn};
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::config::{BatchConfig, Config};
+use crate::indicators::IndicatorOutput;
+use crate::TradeMessage;
+
+pub mod clickhouse;
+pub mod influxdb;
+pub mod redis;
+
+use clickhouse::ClickHouse;
+use influxdb::InfluxDb;
+use self::redis::Redis;
+
+/// Attempts per batch before it is dropped
+const MAX_ATTEMPTS: u32 = 3;
+
+/// An external store written to in batches of queued items (usually
+/// pre-formatted lines)
+pub trait BatchSink: Send + 'static {
+    type Item: Send + 'static;
+
+    fn name(&self) -> &'static str;
+
+    /// Write one batch; retried on error
+    fn flush(&mut self, items: &[Self::Item]) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Sending side of a sink's queue; cheap to clone
+///
+/// The queue is bounded, so writers wait (backpressure) while the store is
+/// slower than the stream.
+pub struct SinkQueue<T = String> {
+    name: &'static str,
+    items: mpsc::Sender<T>,
+}
+
+// Derived Clone would needlessly require `T: Clone`
+impl<T> Clone for SinkQueue<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name,
+            items: self.items.clone(),
+        }
+    }
+}
+
+impl<T> SinkQueue<T> {
+    pub async fn send(&self, item: T) {
+        if self.items.send(item).await.is_err() {
+            warn!("⚠️  {} sink stopped, dropping write", self.name);
+        }
+    }
+}
+
+/// Run `sink` on its own task, flushing every `batch_size` items or
+/// `flush_interval_ms`, whichever comes first
+pub fn spawn<S: BatchSink>(sink: S, batch: &BatchConfig) -> (SinkQueue<S::Item>, JoinHandle<()>) {
+    let (tx, rx) = mpsc::channel(batch.queue_size);
+    let queue = SinkQueue { name: sink.name(), items: tx };
+    let handle = tokio::spawn(run_batches(
+        sink,
+        rx,
+        batch.batch_size,
+        Duration::from_millis(batch.flush_interval_ms),
+    ));
+    (queue, handle)
+}
+
+async fn run_batches<S: BatchSink>(
+    mut sink: S,
+    mut items: mpsc::Receiver<S::Item>,
+    batch_size: usize,
+    flush_interval: Duration,
+) {
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut ticker = tokio::time::interval(flush_interval);
+
+    loop {
+        tokio::select! {
+            item = items.recv() => match item {
+                Some(item) => {
+                    batch.push(item);
+                    if batch.len() < batch_size {
+                        continue;
+                    }
+                }
+                // Every queue handle dropped: write what is left and stop
+                None => {
+                    write_batch(&mut sink, &mut batch).await;
+                    return;
+                }
+            },
+            _ = ticker.tick() => {}
+        }
+        write_batch(&mut sink, &mut batch).await;
+    }
+}
+
+/// Write and clear `batch`, retrying with backoff before giving up on it
+async fn write_batch<S: BatchSink>(sink: &mut S, batch: &mut Vec<S::Item>) {
+    if batch.is_empty() {
+        return;
+    }
+
+    let mut delay = Duration::from_millis(500);
+    for attempt in 1..=MAX_ATTEMPTS {
+        match sink.flush(batch).await {
+            Ok(()) => break,
+            Err(e) if attempt < MAX_ATTEMPTS => {
+                warn!("⚠️  {} write failed (attempt {}): {:#}", sink.name(), attempt, e);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            Err(e) => error!("❌ Dropping {} rows for {}: {:#}", batch.len(), sink.name(), e),
+        }
+    }
+    batch.clear();
+}
+
+/// Every enabled external store, fed alongside the Kafka outputs
+#[derive(Clone, Default)]
+pub struct Sinks {
+    clickhouse: Option<ClickHouse>,
+    influxdb: Option<InfluxDb>,
+    redis: Option<Redis>,
+}
+
+/// Background tasks of the sinks, awaited on shutdown
+pub struct SinkTasks(Vec<JoinHandle<()>>);
+
+impl Sinks {
+    pub fn start(config: &Config) -> Result<(Self, SinkTasks)> {
+        let mut tasks = Vec::new();
+
+        let clickhouse = if config.clickhouse.enabled {
+            Some(ClickHouse::start(&config.clickhouse, &mut tasks)?)
+        } else {
+            None
+        };
+
+        let influxdb = if config.influxdb.enabled {
+            Some(InfluxDb::start(&config.influxdb, &mut tasks)?)
+        } else {
+            None
+        };
+
+        let redis = if config.redis.enabled {
+            Some(Redis::start(&config.redis, &mut tasks)?)
+        } else {
+            None
+        };
+
+        Ok((Self { clickhouse, influxdb, redis }, SinkTasks(tasks)))
+    }
+
+    pub async fn write_trade(&self, trade: &TradeMessage) {
+        if let Some(clickhouse) = &self.clickhouse {
+            clickhouse.write_trade(trade).await;
+        }
+        if let Some(influxdb) = &self.influxdb {
+            influxdb.write_trade(trade).await;
+        }
+    }
+
+    pub async fn write_output(&self, output: &IndicatorOutput) {
+        if let Some(clickhouse) = &self.clickhouse {
+            clickhouse.write_output(output).await;
+        }
+        if let Some(influxdb) = &self.influxdb {
+            influxdb.write_output(output).await;
+        }
+        if let Some(redis) = &self.redis {
+            redis.write_output(output).await;
+        }
+    }
+}
+
+impl SinkTasks {
+    /// Wait for every sink to write what is queued; all `Sinks` handles must
+    /// be dropped first
+    pub async fn join(self) {
+        for task in self.0 {
+            task.await.ok();
+        }
+    }
+}
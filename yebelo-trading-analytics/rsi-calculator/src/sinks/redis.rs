@@ -0,0 +1,108 @@
+use anyhow::{Context, Result};
+use redis::aio::ConnectionManager;
+use tokio::task::JoinHandle;
+
+use super::{BatchSink, SinkQueue};
+use crate::config::RedisConfig;
+use crate::indicators::IndicatorOutput;
+
+/// One hash field to set
+pub struct Snapshot {
+    key: String,
+    field: String,
+    json: String,
+}
+
+/// Keeps the latest indicator values of every token in Redis hashes.
+///
+/// Each token has one hash, `<key_prefix><token>` (e.g. `rsi:<address>`),
+/// with a field per series holding its latest JSON message: `rsi:14:tick`,
+/// `macd`, `candle:1m`, ... The key expires `ttl_secs` after the token's
+/// last update, so tokens that stop trading disappear on their own.
+#[derive(Clone)]
+pub struct Redis {
+    snapshots: SinkQueue<Snapshot>,
+    key_prefix: String,
+}
+
+impl Redis {
+    pub fn start(config: &RedisConfig, tasks: &mut Vec<JoinHandle<()>>) -> Result<Self> {
+        let client = redis::Client::open(config.url.as_str())
+            .with_context(|| format!("Invalid Redis URL '{}'", config.url))?;
+
+        let writer = Writer {
+            client,
+            connection: None,
+            ttl_secs: config.ttl_secs,
+        };
+        let (snapshots, handle) = super::spawn(writer, &config.batch);
+        tasks.push(handle);
+
+        Ok(Self {
+            snapshots,
+            key_prefix: config.key_prefix.clone(),
+        })
+    }
+
+    pub async fn write_output(&self, output: &IndicatorOutput) {
+        let Ok(json) = output.to_json() else {
+            return;
+        };
+        let field = match output {
+            IndicatorOutput::Rsi(msg) => format!("rsi:{}:{}", msg.period, msg.timeframe),
+            IndicatorOutput::Candle(msg) => format!("candle:{}", msg.interval),
+            other => other.kind().to_lowercase(),
+        };
+
+        let snapshot = Snapshot {
+            key: format!("{}{}", self.key_prefix, output.token_address()),
+            field,
+            json,
+        };
+        self.snapshots.send(snapshot).await;
+    }
+}
+
+/// Writes each batch as one pipeline of HSET + EXPIRE commands
+struct Writer {
+    client: redis::Client,
+    // Connected on first use; the manager reconnects by itself afterwards
+    connection: Option<ConnectionManager>,
+    ttl_secs: i64,
+}
+
+impl BatchSink for Writer {
+    type Item = Snapshot;
+
+    fn name(&self) -> &'static str {
+        "Redis"
+    }
+
+    async fn flush(&mut self, snapshots: &[Snapshot]) -> Result<()> {
+        let connection = match &mut self.connection {
+            Some(connection) => connection,
+            None => {
+                let connection = self
+                    .client
+                    .get_connection_manager()
+                    .await
+                    .context("Failed to connect to Redis")?;
+                self.connection.insert(connection)
+            }
+        };
+
+        let mut pipe = redis::pipe();
+        for snapshot in snapshots {
+            pipe.hset(&snapshot.key, &snapshot.field, &snapshot.json).ignore();
+            if self.ttl_secs > 0 {
+                pipe.expire(&snapshot.key, self.ttl_secs).ignore();
+            }
+        }
+
+        let () = pipe
+            .query_async(connection)
+            .await
+            .with_context(|| format!("Failed to write {} snapshots", snapshots.len()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+use anyhow::{anyhow, Context, Result};
+use log::{debug, info, warn};
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::message::Message;
+use rdkafka::{Offset, TopicPartitionList};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+use crate::config::{Config, PriceSource, UsdNormalizationConfig};
+use crate::indicators::IndicatorOutput;
+use crate::warmup::{RangeReader, Ranges, QUERY_TIMEOUT};
+use crate::service::consumer_client_config;
+use crate::{RsiCalculator, TradeMessage};
+use crate::DEFAULT_QUOTE_CURRENCY;
+
+/// Quote currency of USD-normalized series
+const USD: &str = "USD";
+
+/// SOL/USD reference prices by time, fed by the consumer or poller
+pub struct SolUsd {
+    config: UsdNormalizationConfig,
+    // (Unix seconds, price), oldest first
+    rates: Mutex<VecDeque<(i64, f64)>>,
+}
+
+impl SolUsd {
+    pub fn new(config: &UsdNormalizationConfig) -> Self {
+        Self {
+            config: config.clone(),
+            rates: Mutex::default(),
+        }
+    }
+
+    /// The price quoted closest to `time`, unless even that one is more than
+    /// `max_age_secs` away from it
+    fn rate_at(&self, time: i64) -> Option<f64> {
+        let rates = self.rates.lock().unwrap_or_else(|e| e.into_inner());
+        let after = rates.partition_point(|&(at, _)| at < time);
+        let before = after.checked_sub(1).and_then(|index| rates.get(index));
+        let (at, price) = [before, rates.get(after)]
+            .into_iter()
+            .flatten()
+            .min_by_key(|(at, _)| at.abs_diff(time))?;
+        (at.abs_diff(time) <= self.config.max_age_secs).then_some(*price)
+    }
+
+    /// Record the price quoted at `time`, keeping the history in time order
+    fn insert(&self, time: i64, price: f64) {
+        let mut rates = self.rates.lock().unwrap_or_else(|e| e.into_inner());
+        let index = rates.partition_point(|&(at, _)| at <= time);
+        rates.insert(index, (time, price));
+    }
+
+    /// Record a live price and forget those older than `history_secs` before
+    /// the newest one
+    fn update(&self, time: i64, price: f64) {
+        self.insert(time, price);
+        let mut rates = self.rates.lock().unwrap_or_else(|e| e.into_inner());
+        let newest = rates.back().map_or(time, |&(at, _)| at);
+        let cutoff = newest.saturating_sub(self.config.history_secs as i64);
+        while rates.front().is_some_and(|&(at, _)| at < cutoff) {
+            rates.pop_front();
+        }
+    }
+
+    /// A SOL-quoted trade repriced in USD at the rate closest to its
+    /// `block_time`; None for other quotes or when no rate is close enough
+    ///
+    /// Only the price is converted: `amount_in_sol` stays in SOL, so whale,
+    /// flow, liquidity and volume thresholds mean the same on both series.
+    pub fn convert(&self, trade: &TradeMessage) -> Option<TradeMessage> {
+        if trade.quote() != DEFAULT_QUOTE_CURRENCY {
+            return None;
+        }
+        let sol_usd = self.rate_at(trade.block_time_secs()?)?;
+        Some(TradeMessage {
+            token_address: trade.token_address.clone(),
+            price_in_sol: trade.price_in_sol * sol_usd,
+            block_time: trade.block_time.clone(),
+            transaction_signature: trade.transaction_signature.clone(),
+            is_buy: trade.is_buy,
+            amount_in_sol: trade.amount_in_sol,
+            processed_timestamp: trade.processed_timestamp.clone(),
+            quote_currency: USD.to_string(),
+        })
+    }
+
+    /// Read the price out of a JSON payload
+    fn parse(&self, payload: &[u8]) -> Result<f64> {
+        let value: Value = serde_json::from_slice(payload).context("Invalid JSON")?;
+        let field = self.config.price_field.trim();
+        let price = field
+            .split('.')
+            .filter(|_| !field.is_empty())
+            .try_fold(&value, |value, name| value.get(name))
+            .ok_or_else(|| anyhow!("No '{}' field", field))?;
+        let price = match price {
+            Value::String(s) => s.trim().parse().ok(),
+            price => price.as_f64(),
+        };
+        match price {
+            Some(price) if price.is_finite() && price > 0.0 => Ok(price),
+            _ => Err(anyhow!("'{}' is not a positive number", field)),
+        }
+    }
+
+    /// Read back the prices published to the reference topic since `since`
+    /// (Unix seconds); returns how many were loaded
+    ///
+    /// Used before warm-up and by backfill so replayed trades are converted
+    /// at the rate of their own time. The http source has no history.
+    pub async fn load_history(&self, config: &Config, since: i64) -> Result<usize> {
+        if self.config.source != PriceSource::Kafka {
+            return Ok(0);
+        }
+        let topic = self.config.topic.as_str();
+        let consumer: StreamConsumer = consumer_client_config(&config.kafka)
+            .set("group.id", format!("{}-sol-usd", config.kafka.group_id))
+            .set("enable.auto.commit", "false")
+            .create()
+            .context("Failed to create SOL/USD history consumer")?;
+
+        let metadata = consumer
+            .fetch_metadata(Some(topic), QUERY_TIMEOUT)
+            .with_context(|| format!("Failed to fetch metadata for '{}'", topic))?;
+        let mut query = TopicPartitionList::new();
+        for partition in metadata.topics().iter().flat_map(|topic| topic.partitions()) {
+            query.add_partition_offset(topic, partition.id(), Offset::Offset(since.saturating_mul(1000)))?;
+        }
+        let offsets = consumer
+            .offsets_for_times(query, QUERY_TIMEOUT)
+            .context("Failed to look up SOL/USD offsets by time")?;
+
+        let mut ranges = Ranges::new();
+        for partition in offsets.elements() {
+            // No offset means nothing was published since then
+            let Offset::Offset(start) = partition.offset() else {
+                continue;
+            };
+            let (_, end) = consumer.fetch_watermarks(topic, partition.partition(), QUERY_TIMEOUT)?;
+            if start < end {
+                ranges.insert((topic.to_string(), partition.partition()), (start, end));
+            }
+        }
+        if ranges.is_empty() {
+            return Ok(0);
+        }
+
+        let mut reader = RangeReader::new(consumer, ranges, Duration::from_secs(config.warmup.timeout_secs))?;
+        let mut loaded = 0;
+        while let Some(message) = reader.next().await {
+            match message.payload().map(|payload| self.parse(payload)) {
+                Some(Ok(price)) => {
+                    self.insert(message_time(&message), price);
+                    loaded += 1;
+                }
+                Some(Err(e)) => debug!("Skipping SOL/USD message at offset {}: {:#}", message.offset(), e),
+                None => {}
+            }
+        }
+        Ok(loaded)
+    }
+}
+
+/// Calculate a trade, then its USD-normalized copy when there is one
+pub fn calculate(
+    sol_usd: Option<&SolUsd>,
+    calculator: &mut RsiCalculator,
+    trade: TradeMessage,
+) -> Vec<IndicatorOutput> {
+    let in_usd = sol_usd.and_then(|sol_usd| sol_usd.convert(&trade));
+    let mut outputs = calculator.process_trade(trade);
+    if let Some(in_usd) = in_usd {
+        outputs.extend(calculator.process_trade(in_usd));
+    }
+    outputs
+}
+
+/// Load the last `history_secs` of prices, then keep them current from the
+/// configured source until the process exits
+pub async fn start(config: &Config) -> Result<Arc<SolUsd>> {
+    let usd = &config.usd_normalization;
+    let sol_usd = Arc::new(SolUsd::new(usd));
+    match usd.source {
+        PriceSource::Kafka => {
+            let since = chrono::Utc::now().timestamp() - usd.history_secs as i64;
+            match sol_usd.load_history(config, since).await {
+                Ok(loaded) => info!("💵 Loaded {} SOL/USD prices from '{}'", loaded, usd.topic),
+                Err(e) => warn!("⚠️  Failed to load SOL/USD history: {:#}", e),
+            }
+
+            // Never commits: history is read back by time on every start
+            let consumer: StreamConsumer = consumer_client_config(&config.kafka)
+                .set("group.id", format!("{}-sol-usd", config.kafka.group_id))
+                .set("enable.auto.commit", "false")
+                .set("auto.offset.reset", "latest")
+                .create()
+                .context("Failed to create SOL/USD consumer")?;
+            consumer
+                .subscribe(&[usd.topic.as_str()])
+                .context("Failed to subscribe to the SOL/USD topic")?;
+            tokio::spawn(consume(Arc::clone(&sol_usd), consumer));
+            info!("💵 Calculating SOL-quoted tokens in USD too, priced from '{}'", usd.topic);
+        }
+        PriceSource::Http => {
+            let client = reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .context("Failed to create SOL/USD HTTP client")?;
+            // One price up front, so warm-up can convert the latest trades
+            fetch(&sol_usd, &client).await;
+            tokio::spawn(poll(Arc::clone(&sol_usd), client));
+            info!(
+                "💵 Calculating SOL-quoted tokens in USD too, priced from {} every {}s",
+                usd.url,
+                usd.poll_interval_secs
+            );
+        }
+    }
+    Ok(sol_usd)
+}
+
+/// When a reference price was quoted: the message timestamp, or now
+fn message_time(message: &impl Message) -> i64 {
+    message
+        .timestamp()
+        .to_millis()
+        .map(|millis| millis.div_euclid(1000))
+        .unwrap_or_else(|| chrono::Utc::now().timestamp())
+}
+
+/// Keep the prices current from the reference topic until the process exits
+async fn consume(sol_usd: Arc<SolUsd>, consumer: StreamConsumer) {
+    loop {
+        match consumer.recv().await {
+            Ok(message) => {
+                let Some(payload) = message.payload() else {
+                    continue;
+                };
+                match sol_usd.parse(payload) {
+                    Ok(price) => {
+                        debug!("💵 SOL/USD {}", price);
+                        sol_usd.update(message_time(&message), price);
+                    }
+                    Err(e) => warn!("⚠️  Ignoring SOL/USD message on '{}': {:#}", message.topic(), e),
+                }
+            }
+            Err(e) => {
+                warn!("⚠️  Failed to read SOL/USD price: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+/// Poll the price oracle every `poll_interval_secs` until the process exits
+async fn poll(sol_usd: Arc<SolUsd>, client: reqwest::Client) {
+    let interval = Duration::from_secs(sol_usd.config.poll_interval_secs);
+    let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+        fetch(&sol_usd, &client).await;
+    }
+}
+
+/// Ask the oracle for the current price once
+async fn fetch(sol_usd: &SolUsd, client: &reqwest::Client) {
+    let response = client
+        .get(&sol_usd.config.url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status);
+    let body = match response {
+        Ok(response) => response.bytes().await,
+        Err(e) => Err(e),
+    };
+    match body.context("Request failed").and_then(|body| sol_usd.parse(&body)) {
+        Ok(price) => {
+            debug!("💵 SOL/USD {}", price);
+            sol_usd.update(chrono::Utc::now().timestamp(), price);
+        }
+        Err(e) => warn!("⚠️  Failed to poll SOL/USD price from {}: {:#}", sol_usd.config.url, e),
+    }
+}
@@ -0,0 +1,321 @@
+use anyhow::{Context, Result};
+use rdkafka::admin::{AdminClient, AdminOptions, NewTopic, TopicReplication};
+use rdkafka::client::DefaultClientContext;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::error::RDKafkaErrorCode;
+use rdkafka::message::Message;
+use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
+use rdkafka::{Offset, TopicPartitionList};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::{KafkaConfig, StateBackend, StateConfig};
+use crate::{RestoredState, TokenState};
+
+const TOKEN_PREFIX: &str = "token/";
+const FINGERPRINT_KEY: &str = "meta/fingerprint";
+const WATERMARK_KEY: &str = "meta/watermark";
+
+/// Where per-token indicator state is checkpointed
+///
+/// State is only restored when it was written with the same indicator
+/// configuration; otherwise the calculator warms up from scratch.
+pub enum StateStore {
+    Sled(SledStateStore),
+    Kafka(Box<KafkaStateStore>),
+}
+
+impl StateStore {
+    pub async fn open(config: &StateConfig, kafka: &KafkaConfig) -> Result<Self> {
+        match config.backend {
+            StateBackend::Sled => Ok(StateStore::Sled(SledStateStore::open(&config.path)?)),
+            StateBackend::Kafka => Ok(StateStore::Kafka(Box::new(KafkaStateStore::open(kafka, &config.topic).await?))),
+        }
+    }
+
+    /// Human-readable location used in logs
+    pub fn describe(&self) -> String {
+        match self {
+            StateStore::Sled(store) => format!("sled:{}", store.path),
+            StateStore::Kafka(store) => format!("kafka:{}", store.topic),
+        }
+    }
+
+    /// Load every persisted token state, if it matches `fingerprint`
+    pub async fn load(&self, fingerprint: &str) -> Result<Option<RestoredState>> {
+        let entries = match self {
+            StateStore::Sled(store) => store.load()?,
+            StateStore::Kafka(store) => store.load().await?,
+        };
+
+        restore_entries(entries, fingerprint)
+    }
+
+    /// Write a full checkpoint of all token states; tokens saved before but
+    /// no longer tracked (evicted or reset) are deleted
+    pub async fn save(
+        &self,
+        fingerprint: &str,
+        tokens: &HashMap<String, TokenState>,
+        watermark: i64,
+    ) -> Result<usize> {
+        let mut entries = Vec::with_capacity(tokens.len() + 2);
+        for (token, state) in tokens {
+            let value = serde_json::to_vec(state).context("Failed to serialize token state")?;
+            entries.push((format!("{}{}", TOKEN_PREFIX, token), value));
+        }
+        entries.push((FINGERPRINT_KEY.to_string(), fingerprint.as_bytes().to_vec()));
+        entries.push((WATERMARK_KEY.to_string(), watermark.to_string().into_bytes()));
+
+        match self {
+            StateStore::Sled(store) => store.save(entries).await?,
+            StateStore::Kafka(store) => store.save(entries).await?,
+        }
+
+        Ok(tokens.len())
+    }
+}
+
+/// Rebuild calculator state from raw key/value entries
+fn restore_entries(entries: HashMap<String, Vec<u8>>, fingerprint: &str) -> Result<Option<RestoredState>> {
+    match entries.get(FINGERPRINT_KEY) {
+        Some(stored) if stored.as_slice() == fingerprint.as_bytes() => {}
+        Some(_) => {
+            log::warn!("⚠️  Indicator config changed since last checkpoint, discarding saved state");
+            return Ok(None);
+        }
+        None => return Ok(None),
+    }
+
+    let mut tokens = HashMap::new();
+    for (key, value) in &entries {
+        let Some(token) = key.strip_prefix(TOKEN_PREFIX) else {
+            continue;
+        };
+
+        match serde_json::from_slice::<TokenState>(value) {
+            Ok(state) => {
+                tokens.insert(token.to_string(), state);
+            }
+            Err(e) => log::warn!("⚠️  Skipping unreadable state for {}: {}", token, e),
+        }
+    }
+
+    let watermark = entries
+        .get(WATERMARK_KEY)
+        .and_then(|value| std::str::from_utf8(value).ok()?.parse().ok());
+
+    Ok(Some(RestoredState { tokens, watermark }))
+}
+
+/// Embedded key-value store (sled) on local disk
+pub struct SledStateStore {
+    db: sled::Db,
+    path: String,
+}
+
+impl SledStateStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        let db = sled::open(path)
+            .with_context(|| format!("Failed to open state store at {}", path.display()))?;
+
+        Ok(Self {
+            db,
+            path: path.display().to_string(),
+        })
+    }
+
+    fn load(&self) -> Result<HashMap<String, Vec<u8>>> {
+        let mut entries = HashMap::new();
+
+        for entry in self.db.iter() {
+            let (key, value) = entry.context("Failed to read state store")?;
+            entries.insert(String::from_utf8_lossy(&key).into_owned(), value.to_vec());
+        }
+
+        Ok(entries)
+    }
+
+    /// Write all entries atomically and flush them to disk, removing token
+    /// keys that are not among them
+    async fn save(&self, entries: Vec<(String, Vec<u8>)>) -> Result<()> {
+        let current: HashSet<&[u8]> = entries.iter().map(|(key, _)| key.as_bytes()).collect();
+        let mut batch = sled::Batch::default();
+        for key in self.db.scan_prefix(TOKEN_PREFIX).keys() {
+            let key = key.context("Failed to read state store")?;
+            if !current.contains(&*key) {
+                batch.remove(key);
+            }
+        }
+        for (key, value) in &entries {
+            batch.insert(key.as_bytes(), value.as_slice());
+        }
+
+        self.db.apply_batch(batch).context("Failed to write checkpoint")?;
+        self.db.flush_async().await.context("Failed to flush checkpoint")?;
+
+        Ok(())
+    }
+}
+
+/// Log-compacted Kafka topic keyed by token, so the service needs no local disk
+pub struct KafkaStateStore {
+    producer: FutureProducer,
+    kafka: KafkaConfig,
+    group_id: String,
+    topic: String,
+    // Token keys the topic holds a state for, so dropped ones get a tombstone
+    written: Mutex<HashSet<String>>,
+}
+
+impl KafkaStateStore {
+    pub async fn open(kafka: &KafkaConfig, topic: &str) -> Result<Self> {
+        ensure_compacted_topic(kafka, topic).await?;
+
+        Ok(Self {
+            producer: crate::service::create_producer(kafka)?,
+            kafka: kafka.clone(),
+            group_id: format!("{}-state-restore", kafka.group_id),
+            topic: topic.to_string(),
+            written: Mutex::default(),
+        })
+    }
+
+    /// Read the state topic from the beginning up to its current end
+    async fn load(&self) -> Result<HashMap<String, Vec<u8>>> {
+        let consumer: StreamConsumer = crate::service::consumer_client_config(&self.kafka)
+            .set("group.id", &self.group_id)
+            .set("enable.auto.commit", "false")
+            .create()
+            .context("Failed to create state restore consumer")?;
+
+        let metadata = consumer
+            .fetch_metadata(Some(&self.topic), Duration::from_secs(10))
+            .context("Failed to fetch state topic metadata")?;
+
+        // Only read up to the end offsets observed now
+        let mut assignment = TopicPartitionList::new();
+        let mut remaining = HashMap::new();
+        for partition in metadata.topics().iter().flat_map(|topic| topic.partitions()) {
+            let (low, high) = consumer
+                .fetch_watermarks(&self.topic, partition.id(), Duration::from_secs(10))
+                .context("Failed to fetch state topic watermarks")?;
+
+            if high > low {
+                assignment.add_partition_offset(&self.topic, partition.id(), Offset::Beginning)?;
+                remaining.insert(partition.id(), high);
+            }
+        }
+
+        let mut entries: HashMap<String, Vec<u8>> = HashMap::new();
+        if remaining.is_empty() {
+            return Ok(entries);
+        }
+        consumer.assign(&assignment).context("Failed to assign state topic")?;
+
+        while !remaining.is_empty() {
+            let message = tokio::time::timeout(Duration::from_secs(30), consumer.recv())
+                .await
+                .context("Timed out reading state topic")?
+                .context("Failed to read state topic")?;
+
+            if let Some(key) = message.key() {
+                let key = String::from_utf8_lossy(key).into_owned();
+                match message.payload() {
+                    Some(value) => entries.insert(key, value.to_vec()),
+                    None => entries.remove(&key), // Tombstone
+                };
+            }
+
+            if let Some(&high) = remaining.get(&message.partition()) {
+                if message.offset() + 1 >= high {
+                    remaining.remove(&message.partition());
+                }
+            }
+        }
+
+        *self.written.lock().unwrap_or_else(|e| e.into_inner()) =
+            entries.keys().filter(|key| key.starts_with(TOKEN_PREFIX)).cloned().collect();
+        Ok(entries)
+    }
+
+    /// Produce every entry keyed by name, plus a tombstone for every token
+    /// key written before but missing now, and wait for all deliveries
+    async fn save(&self, entries: Vec<(String, Vec<u8>)>) -> Result<()> {
+        let current: HashSet<String> = entries
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| key.starts_with(TOKEN_PREFIX))
+            .cloned()
+            .collect();
+        let dropped: Vec<String> = self
+            .written
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .difference(&current)
+            .cloned()
+            .collect();
+
+        let records = entries
+            .iter()
+            .map(|(key, value)| (key, Some(value.as_slice())))
+            .chain(dropped.iter().map(|key| (key, None)));
+        let mut deliveries = Vec::with_capacity(entries.len() + dropped.len());
+
+        for (key, value) in records {
+            let mut record = FutureRecord::to(&self.topic).key(key.as_str());
+            if let Some(value) = value {
+                record = record.payload(value);
+            }
+            loop {
+                match self.producer.send_result(record) {
+                    Ok(delivery) => {
+                        deliveries.push(delivery);
+                        break;
+                    }
+                    Err((e, rejected)) if e.rdkafka_error_code() == Some(RDKafkaErrorCode::QueueFull) => {
+                        // Let queued messages drain before retrying
+                        record = rejected;
+                        self.producer.flush(Duration::from_millis(100)).ok();
+                    }
+                    Err((e, _)) => return Err(e).context("Failed to queue state checkpoint"),
+                }
+            }
+        }
+
+        for delivery in deliveries {
+            match delivery.await {
+                Ok(Ok(_)) => {}
+                Ok(Err((e, _))) => return Err(e).context("Failed to write state checkpoint"),
+                Err(_) => anyhow::bail!("State checkpoint delivery was cancelled"),
+            }
+        }
+
+        *self.written.lock().unwrap_or_else(|e| e.into_inner()) = current;
+        Ok(())
+    }
+}
+
+/// Create the state topic with compaction enabled if it does not exist yet
+async fn ensure_compacted_topic(kafka: &KafkaConfig, topic: &str) -> Result<()> {
+    let admin: AdminClient<DefaultClientContext> = crate::service::kafka_client_config(kafka)
+        .create()
+        .context("Failed to create Kafka admin client")?;
+
+    let new_topic = NewTopic::new(topic, 1, TopicReplication::Fixed(1)).set("cleanup.policy", "compact");
+    let results = admin
+        .create_topics(&[new_topic], &AdminOptions::new())
+        .await
+        .context("Failed to create state topic")?;
+
+    for result in results {
+        match result {
+            Ok(_) | Err((_, RDKafkaErrorCode::TopicAlreadyExists)) => {}
+            Err((name, code)) => anyhow::bail!("Failed to create state topic {}: {}", name, code),
+        }
+    }
+
+    Ok(())
+}
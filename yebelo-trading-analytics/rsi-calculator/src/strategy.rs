@@ -0,0 +1,170 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::config::BacktestConfig;
+use crate::indicators::IndicatorOutput;
+
+/// Results of one strategy scope: all tokens together, or a single token
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Performance {
+    /// "total" or the token address
+    pub scope: String,
+    /// Moves into an entry or exit signal
+    pub signals: u64,
+    /// Closed round trips, including positions closed at the last price
+    pub trades: u64,
+    pub wins: u64,
+    /// Winning share of closed trades, from 0 to 1
+    pub win_rate: f64,
+    /// Realized profit and loss after fees, in SOL
+    pub pnl_sol: f64,
+    /// pnl_sol as a percentage of the capital put into all trades
+    pub return_pct: f64,
+    /// Largest fall of cumulative PnL from its previous peak, in SOL
+    pub max_drawdown_sol: f64,
+    #[serde(skip)]
+    peak_sol: f64,
+}
+
+impl Performance {
+    fn new(scope: &str) -> Self {
+        Self {
+            scope: scope.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn record_trade(&mut self, pnl: f64, size: f64) {
+        self.trades += 1;
+        if pnl > 0.0 {
+            self.wins += 1;
+        }
+        self.pnl_sol += pnl;
+        self.peak_sol = self.peak_sol.max(self.pnl_sol);
+        self.max_drawdown_sol = self.max_drawdown_sol.max(self.peak_sol - self.pnl_sol);
+
+        self.win_rate = self.wins as f64 / self.trades as f64;
+        self.return_pct = self.pnl_sol / (size * self.trades as f64) * 100.0;
+    }
+}
+
+/// Performance of the strategy over a replay, overall and per token
+#[derive(Debug, Serialize)]
+pub struct BacktestReport {
+    pub summary: Performance,
+    pub tokens: Vec<Performance>,
+}
+
+/// A long position opened on an entry signal
+struct Position {
+    token_address: String,
+    entry_price: f64,
+}
+
+/// Trades a signal-driven long-only strategy over indicator outputs
+///
+/// Each series (token, period and timeframe) is traded on its own: a move
+/// into an entry signal opens a position of `position_size_sol` at the
+/// price of the trade that produced it, a move into an exit signal closes it.
+/// Positions still open at the end are closed at the token's last price.
+pub struct Backtest {
+    config: BacktestConfig,
+    // Last signal and open position per series
+    signals: HashMap<String, String>,
+    positions: HashMap<String, Position>,
+    last_prices: HashMap<String, f64>,
+    summary: Performance,
+    tokens: BTreeMap<String, Performance>,
+}
+
+impl Backtest {
+    pub fn new(config: &BacktestConfig) -> Self {
+        Self {
+            config: config.clone(),
+            signals: HashMap::new(),
+            positions: HashMap::new(),
+            last_prices: HashMap::new(),
+            summary: Performance::new("total"),
+            tokens: BTreeMap::new(),
+        }
+    }
+
+    /// Apply the strategy to an output calculated for a trade at `price`
+    pub fn on_output(&mut self, output: &IndicatorOutput, price: f64) {
+        // Each quote currency of a token is traded separately
+        let series_key = output.series_key();
+        let token_address = series_key.as_ref();
+        self.last_prices.insert(token_address.to_string(), price);
+
+        if !self.config.indicator.eq_ignore_ascii_case(output.kind()) {
+            return;
+        }
+        if self.config.period.is_some_and(|period| output.period() != Some(period.to_string())) {
+            return;
+        }
+        if self.config.timeframe.as_ref().is_some_and(|timeframe| output.timeframe().as_ref() != Some(timeframe)) {
+            return;
+        }
+        let Some(signal) = output.signal() else {
+            return;
+        };
+
+        // Only a move into a signal triggers, not every value that stays in it
+        let series = format!("{}:{}", token_address, output.key("{period}:{timeframe}"));
+        if self.signals.insert(series.clone(), signal.to_string()).as_deref() == Some(signal) {
+            return;
+        }
+
+        let entry = self.config.entry_signals.iter().any(|s| s == signal);
+        let exit = self.config.exit_signals.iter().any(|s| s == signal);
+        if entry || exit {
+            self.summary.signals += 1;
+            self.token(token_address).signals += 1;
+        }
+
+        if entry && !self.positions.contains_key(&series) {
+            let position = Position {
+                token_address: token_address.to_string(),
+                entry_price: price,
+            };
+            self.positions.insert(series, position);
+        } else if exit {
+            if let Some(position) = self.positions.remove(&series) {
+                self.close(&position, price);
+            }
+        }
+    }
+
+    /// Close what is still open at each token's last price and build the report
+    pub fn finish(mut self) -> BacktestReport {
+        let positions: Vec<Position> = self.positions.drain().map(|(_, position)| position).collect();
+        for position in positions {
+            let price = self.last_prices.get(&position.token_address).copied().unwrap_or(position.entry_price);
+            self.close(&position, price);
+        }
+
+        BacktestReport {
+            summary: self.summary,
+            tokens: self.tokens.into_values().collect(),
+        }
+    }
+
+    fn close(&mut self, position: &Position, price: f64) {
+        let size = self.config.position_size_sol;
+        let gross = if position.entry_price > 0.0 {
+            size * (price / position.entry_price - 1.0)
+        } else {
+            0.0
+        };
+        let pnl = gross - size * self.config.fee_pct / 100.0;
+
+        self.summary.record_trade(pnl, size);
+        self.token(&position.token_address).record_trade(pnl, size);
+    }
+
+    fn token(&mut self, token_address: &str) -> &mut Performance {
+        self.tokens
+            .entry(token_address.to_string())
+            .or_insert_with(|| Performance::new(token_address))
+    }
+}
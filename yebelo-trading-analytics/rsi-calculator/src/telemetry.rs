@@ -0,0 +1,112 @@
+use anyhow::{Context as _, Result};
+use log::warn;
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::{SpanKind, TraceContextExt, Tracer};
+use opentelemetry::{global, Context, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::TracerProvider;
+use opentelemetry_sdk::{runtime, Resource};
+use rdkafka::message::{Headers, Message};
+
+use crate::config::TelemetryConfig;
+use crate::headers::Carried;
+
+const TRACER: &str = "rsi-calculator";
+
+/// Export spans over OTLP and propagate W3C trace context in Kafka headers
+///
+/// While telemetry is disabled no provider is installed, so every span below
+/// is a no-op and no trace headers are written.
+pub fn init(config: &TelemetryConfig) -> Result<Option<TracerProvider>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(&config.otlp_endpoint)
+        .build()
+        .context("Failed to create OTLP span exporter")?;
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new("service.name", config.service_name.clone())]))
+        .build();
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(provider.clone());
+    Ok(Some(provider))
+}
+
+/// Export the spans still buffered before exiting
+pub fn shutdown(provider: Option<TracerProvider>) {
+    if let Some(provider) = provider {
+        if let Err(e) = provider.shutdown() {
+            warn!("Failed to flush traces: {}", e);
+        }
+    }
+}
+
+/// Start the span covering a consumed trade, continuing the upstream trace
+/// when the message carries trace context
+///
+/// The span ends once the returned context and every stage span derived
+/// from it are dropped.
+pub fn consume_span<M: Message>(message: &M) -> Context {
+    let parent = global::get_text_map_propagator(|propagator| propagator.extract(&MessageHeaders(message)));
+    let tracer = global::tracer(TRACER);
+    let span = tracer
+        .span_builder(format!("{} process", message.topic()))
+        .with_kind(SpanKind::Consumer)
+        .with_attributes([
+            KeyValue::new("messaging.system", "kafka"),
+            KeyValue::new("messaging.destination.name", message.topic().to_string()),
+            KeyValue::new("messaging.kafka.destination.partition", i64::from(message.partition())),
+            KeyValue::new("messaging.kafka.message.offset", message.offset()),
+        ])
+        .start_with_context(&tracer, &parent);
+    parent.with_span(span)
+}
+
+/// Start a child span of a trade for one pipeline stage, ended when dropped
+pub fn stage_span(trade: &Context, name: &'static str, kind: SpanKind) -> Context {
+    let tracer = global::tracer(TRACER);
+    let span = tracer.span_builder(name).with_kind(kind).start_with_context(&tracer, trade);
+    trade.with_span(span)
+}
+
+/// Write the trace context of `cx` into an output's headers so consumers
+/// of the output topics continue the trace
+pub fn inject(cx: &Context, carried: &mut Carried) {
+    global::get_text_map_propagator(|propagator| propagator.inject_context(cx, &mut CarriedInjector(carried)));
+}
+
+/// Reads trace context from a consumed message's headers
+struct MessageHeaders<'a, M>(&'a M);
+
+impl<M: Message> Extractor for MessageHeaders<'_, M> {
+    fn get(&self, key: &str) -> Option<&str> {
+        let headers = self.0.headers()?;
+        headers
+            .iter()
+            .find(|header| header.key == key)
+            .and_then(|header| std::str::from_utf8(header.value?).ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0
+            .headers()
+            .map(|headers| headers.iter().map(|header| header.key).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Writes trace context into carried headers, replacing any propagated copy
+struct CarriedInjector<'a>(&'a mut Carried);
+
+impl Injector for CarriedInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.retain(|(name, _)| name != key);
+        self.0.push((key.to_string(), value.into_bytes()));
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::{Context, Result};
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::message::Message;
+use rdkafka::producer::{FutureProducer, Producer};
+use rdkafka::{Offset, TopicPartitionList};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Groups published messages and the offsets of the trades that produced
+/// them into one Kafka transaction per commit batch (exactly-once mode).
+///
+/// Downstream consumers reading with `isolation.level=read_committed` only
+/// see outputs whose input offsets were committed in the same transaction.
+pub struct TransactionBatch {
+    open: bool,
+    // Next offset to consume, by (topic, partition)
+    offsets: HashMap<(String, i32), i64>,
+    timeout: Duration,
+}
+
+impl TransactionBatch {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            open: false,
+            offsets: HashMap::new(),
+            timeout,
+        }
+    }
+
+    /// Start a transaction unless one is already open
+    pub fn begin(&mut self, producer: &FutureProducer) -> Result<()> {
+        if !self.open {
+            producer.begin_transaction().context("Failed to begin transaction")?;
+            self.open = true;
+        }
+        Ok(())
+    }
+
+    /// Include a fully processed trade's offset in the current transaction
+    pub fn record<M: Message>(&mut self, message: &M) {
+        self.offsets
+            .insert((message.topic().to_string(), message.partition()), message.offset() + 1);
+    }
+
+    /// Commit consumed offsets together with everything produced since `begin`
+    pub fn commit(&mut self, producer: &FutureProducer, consumer: &StreamConsumer) -> Result<()> {
+        if !self.open {
+            return Ok(());
+        }
+
+        let mut offsets = TopicPartitionList::new();
+        for ((topic, partition), offset) in &self.offsets {
+            offsets.add_partition_offset(topic, *partition, Offset::Offset(*offset))?;
+        }
+        let group = consumer
+            .group_metadata()
+            .context("Consumer group metadata unavailable")?;
+
+        producer
+            .send_offsets_to_transaction(&offsets, &group, self.timeout)
+            .context("Failed to add offsets to transaction")?;
+        producer
+            .commit_transaction(self.timeout)
+            .context("Failed to commit transaction")?;
+
+        self.open = false;
+        self.offsets.clear();
+        Ok(())
+    }
+
+    /// Discard everything produced since `begin`
+    pub fn abort(&mut self, producer: &FutureProducer) {
+        if self.open {
+            if let Err(e) = producer.abort_transaction(self.timeout) {
+                log::error!("❌ Failed to abort transaction: {}", e);
+            }
+            self.open = false;
+            self.offsets.clear();
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use log::warn;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::config::ValidationConfig;
+use crate::TradeMessage;
+
+/// Why a trade failed validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Invalid {
+    MissingTokenAddress,
+    NonPositivePrice,
+    NonPositiveAmount,
+    UnparseableBlockTime,
+    FutureBlockTime,
+    StaleBlockTime,
+}
+
+impl Invalid {
+    /// Counter and header name of the reason
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Invalid::MissingTokenAddress => "missing_token_address",
+            Invalid::NonPositivePrice => "non_positive_price",
+            Invalid::NonPositiveAmount => "non_positive_amount",
+            Invalid::UnparseableBlockTime => "unparseable_block_time",
+            Invalid::FutureBlockTime => "future_block_time",
+            Invalid::StaleBlockTime => "stale_block_time",
+        }
+    }
+}
+
+impl fmt::Display for Invalid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.reason())
+    }
+}
+
+impl std::error::Error for Invalid {}
+
+/// Sanity checks on decoded trades before they reach the calculator, with a
+/// counter per failure reason
+pub struct TradeValidator {
+    config: ValidationConfig,
+    rejected: BTreeMap<Invalid, u64>,
+}
+
+impl TradeValidator {
+    pub fn new(config: &ValidationConfig) -> Self {
+        Self {
+            config: config.clone(),
+            rejected: BTreeMap::new(),
+        }
+    }
+
+    /// Check trades against new rules from now on, keeping the counts
+    pub fn reconfigure(&mut self, config: &ValidationConfig) {
+        self.config = config.clone();
+    }
+
+    /// Pass `trade` through, or fail with the reason it is invalid
+    ///
+    /// In flag mode (`reject = false`) invalid trades are only counted and
+    /// logged, then passed through as well.
+    pub fn check(&mut self, trade: TradeMessage) -> Result<TradeMessage, Invalid> {
+        if !self.config.enabled {
+            return Ok(trade);
+        }
+        let Err(invalid) = self.validate(&trade) else {
+            return Ok(trade);
+        };
+
+        *self.rejected.entry(invalid).or_default() += 1;
+        if self.config.reject {
+            return Err(invalid);
+        }
+        warn!("⚠️  Processing invalid trade {} ({})", trade.transaction_signature, invalid);
+        Ok(trade)
+    }
+
+    fn validate(&self, trade: &TradeMessage) -> Result<(), Invalid> {
+        if trade.token_address.trim().is_empty() {
+            return Err(Invalid::MissingTokenAddress);
+        }
+        // Also catches NaN
+        if !(trade.price_in_sol > 0.0 && trade.price_in_sol.is_finite()) {
+            return Err(Invalid::NonPositivePrice);
+        }
+        if !(trade.amount_in_sol > 0.0 && trade.amount_in_sol.is_finite()) {
+            return Err(Invalid::NonPositiveAmount);
+        }
+
+        let time = trade.block_time_secs().ok_or(Invalid::UnparseableBlockTime)?;
+        let now = chrono::Utc::now().timestamp();
+        if time > now + self.config.max_future_secs {
+            return Err(Invalid::FutureBlockTime);
+        }
+        if self.config.max_age_secs.is_some_and(|max_age| time < now - max_age) {
+            return Err(Invalid::StaleBlockTime);
+        }
+        Ok(())
+    }
+
+    /// Trades that failed validation so far, by reason
+    pub fn rejected(&self) -> &BTreeMap<Invalid, u64> {
+        &self.rejected
+    }
+
+    /// Trades that failed validation so far
+    pub fn rejected_total(&self) -> u64 {
+        self.rejected.values().sum()
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::{Context, Result};
+use log::{error, info};
+use crate::reference::Fixture;
+use std::path::{Path, PathBuf};
+
+use crate::cli::VerifyArgs;
+
+/// Fixtures checked when none are given
+const FIXTURE_DIR: &str = "fixtures/reference";
+
+/// Most drifted values listed per fixture
+const MAX_LISTED: usize = 20;
+
+/// Check every fixture and fail if any value drifted from its reference
+///
+/// Each fixture runs under its own config, not the loaded one, so the
+/// result only depends on the code.
+pub fn run(args: &VerifyArgs) -> Result<()> {
+    let paths = if args.fixtures.is_empty() {
+        fixtures_in(Path::new(FIXTURE_DIR))?
+    } else {
+        args.fixtures.clone()
+    };
+    if paths.is_empty() {
+        anyhow::bail!("No reference fixtures found in {}", FIXTURE_DIR);
+    }
+
+    let mut failed = 0;
+    for path in &paths {
+        let fixture = Fixture::load(path)?;
+        let drift = fixture.check()?;
+        if drift.is_empty() {
+            info!("✅ {}: {} values match ({})", path.display(), fixture.checks(), fixture.description);
+            continue;
+        }
+
+        failed += 1;
+        error!(
+            "❌ {}: {} of {} values drifted beyond {} ({})",
+            path.display(),
+            drift.len(),
+            fixture.checks(),
+            fixture.tolerance,
+            fixture.description
+        );
+        for drift in drift.iter().take(MAX_LISTED) {
+            match drift.actual {
+                Some(actual) => error!(
+                    "   {} at price #{}: expected {}, got {} ({:+e})",
+                    drift.column,
+                    drift.index,
+                    drift.expected,
+                    actual,
+                    actual - drift.expected
+                ),
+                None => error!(
+                    "   {} at price #{}: expected {}, got nothing",
+                    drift.column, drift.index, drift.expected
+                ),
+            }
+        }
+        if drift.len() > MAX_LISTED {
+            error!("   ... and {} more", drift.len() - MAX_LISTED);
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} of {} reference fixtures drifted", failed, paths.len());
+    }
+    info!("✅ All {} reference fixtures match", paths.len());
+    Ok(())
+}
+
+/// TOML files in `dir`, sorted
+fn fixtures_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "toml") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
@@ -0,0 +1,165 @@
+use log::{debug, warn};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::MissedTickBehavior;
+
+use crate::config::VolumeConfig;
+use crate::TradeMessage;
+use crate::candles::format_interval;
+use crate::split_series_key;
+
+/// Rolling trade volume of one token, published every interval
+#[derive(Debug, Serialize)]
+pub struct VolumeMessage {
+    pub token_address: String,
+    pub quote_currency: String,
+    pub timestamp: String,
+    /// One entry per configured window, shortest first
+    pub windows: Vec<VolumeWindow>,
+}
+
+/// Trades of one token over the last `window_secs`
+#[derive(Debug, Default, Serialize)]
+pub struct VolumeWindow {
+    /// e.g. "1m", "5m", "1h"
+    pub window: String,
+    pub window_secs: u64,
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    pub buy_count: u64,
+    pub sell_count: u64,
+    pub trade_count: u64,
+}
+
+/// Trades that arrived within the same second
+#[derive(Default)]
+struct Bucket {
+    second: u64,
+    buy_volume: f64,
+    sell_volume: f64,
+    buy_count: u64,
+    sell_count: u64,
+}
+
+/// Per-second trade buckets of every token, fed by every calculator
+pub struct Volumes {
+    config: VolumeConfig,
+    started: Instant,
+    // Oldest first, covering at most the longest window
+    buckets: Mutex<HashMap<String, VecDeque<Bucket>>>,
+}
+
+impl Volumes {
+    pub fn new(config: &VolumeConfig) -> Self {
+        Self {
+            config: config.clone(),
+            started: Instant::now(),
+            buckets: Mutex::default(),
+        }
+    }
+
+    fn now(&self) -> u64 {
+        self.started.elapsed().as_secs()
+    }
+
+    fn longest(&self) -> u64 {
+        self.config.windows_secs.iter().copied().max().unwrap_or_default()
+    }
+
+    pub fn observe_trade(&self, trade: &TradeMessage) {
+        let second = self.now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let token = buckets.entry(trade.series_key().into_owned()).or_default();
+        if token.back().is_none_or(|bucket| bucket.second != second) {
+            token.push_back(Bucket { second, ..Bucket::default() });
+        }
+        let Some(bucket) = token.back_mut() else {
+            return;
+        };
+        if trade.is_buy {
+            bucket.buy_volume += trade.amount_in_sol;
+            bucket.buy_count += 1;
+        } else {
+            bucket.sell_volume += trade.amount_in_sol;
+            bucket.sell_count += 1;
+        }
+    }
+
+    /// Stats of every token that traded within the longest window; tokens
+    /// that did not are forgotten
+    fn take(&self) -> Vec<VolumeMessage> {
+        let now = self.now();
+        let longest = self.longest();
+        let timestamp = chrono::Utc::now().to_rfc3339();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        buckets.retain(|_, token| {
+            while token.front().is_some_and(|bucket| now - bucket.second >= longest) {
+                token.pop_front();
+            }
+            !token.is_empty()
+        });
+
+        buckets
+            .iter()
+            .map(|(key, token)| VolumeMessage {
+                token_address: split_series_key(key).0.to_string(),
+                quote_currency: split_series_key(key).1.to_string(),
+                timestamp: timestamp.clone(),
+                windows: self
+                    .config
+                    .windows_secs
+                    .iter()
+                    .map(|&window_secs| {
+                        let mut stats = VolumeWindow {
+                            window: format_interval(window_secs as i64),
+                            window_secs,
+                            ..VolumeWindow::default()
+                        };
+                        for bucket in token.iter().rev().take_while(|bucket| now - bucket.second < window_secs) {
+                            stats.buy_volume += bucket.buy_volume;
+                            stats.sell_volume += bucket.sell_volume;
+                            stats.buy_count += bucket.buy_count;
+                            stats.sell_count += bucket.sell_count;
+                        }
+                        stats.trade_count = stats.buy_count + stats.sell_count;
+                        stats
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// Publish every token's volume stats every `interval_secs` until the
+/// process exits
+pub async fn publish(volumes: Arc<Volumes>, producer: FutureProducer) {
+    let topic = &volumes.config.topic;
+    let mut ticker = tokio::time::interval(Duration::from_secs(volumes.config.interval_secs));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick is immediate, before anything was observed
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+        let messages = volumes.take();
+        let mut published = 0;
+        for message in &messages {
+            let payload = match serde_json::to_string(message) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    warn!("⚠️  Failed to serialize volume stats for {}: {}", message.token_address, e);
+                    continue;
+                }
+            };
+            let record = FutureRecord::to(topic).key(&message.token_address).payload(&payload);
+            match producer.send(record, Duration::from_secs(0)).await {
+                Ok(_) => published += 1,
+                Err((e, _)) => warn!("⚠️  Failed to publish volume stats to '{}': {}", topic, e),
+            }
+        }
+        debug!("📦 Published volume stats for {}/{} tokens", published, messages.len());
+    }
+}
@@ -0,0 +1,214 @@
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::message::{Message, OwnedMessage};
+use rdkafka::{Offset, TopicPartitionList};
+use crate::validation::TradeValidator;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::codec::Codec;
+use crate::config::Config;
+use crate::sol_usd::{self, SolUsd};
+use crate::service::{consumer_client_config, POLL_TIMEOUT};
+use crate::RsiCalculator;
+
+/// Upper bound for each broker query made while planning the replay
+pub const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Offsets to read per (topic, partition): start and end (exclusive)
+pub type Ranges = HashMap<(String, i32), (i64, i64)>;
+
+/// Replay the trades just before the consumer group's committed offsets
+/// through `calculator`, discarding its outputs
+///
+/// A separate consumer is assigned the input partitions directly, so it
+/// neither joins the group nor commits; the main consumer resumes at the
+/// committed offsets as usual afterwards. Partitions the group never
+/// committed on have no history to replay, and pattern subscriptions are
+/// skipped. SOL-quoted trades are calculated in USD too when `sol_usd` is
+/// given, at the rates read back for their time.
+pub async fn run(
+    config: &Config,
+    codec: &mut Codec,
+    calculator: &mut RsiCalculator,
+    sol_usd: Option<&SolUsd>,
+) -> Result<()> {
+    let consumer: StreamConsumer = consumer_client_config(&config.kafka)
+        .set("group.id", &config.kafka.group_id)
+        .set("enable.auto.commit", "false")
+        .set("enable.auto.offset.store", "false")
+        .create()
+        .context("Failed to create warm-up consumer")?;
+
+    let ranges = plan(config, &consumer)?;
+    if ranges.is_empty() {
+        info!("🔥 Nothing to warm up from: no committed offsets with history before them");
+        return Ok(());
+    }
+
+    let total: i64 = ranges.values().map(|(start, end)| end - start).sum();
+    info!("🔥 Warming up on up to {} trades from {} partitions", total, ranges.len());
+    let mut reader = RangeReader::new(consumer, ranges, Duration::from_secs(config.warmup.timeout_secs))?;
+
+    // Trades rejected live must not shape the warmed state either
+    let mut validator = TradeValidator::new(&config.validation);
+    let started = Instant::now();
+    let mut replayed = 0u64;
+
+    while let Some(message) = reader.next().await {
+        let Some(payload) = message.payload() else {
+            continue;
+        };
+        let trades = match codec.decode_trades(message.topic(), payload).await {
+            Ok(trades) => trades,
+            Err(e) => {
+                debug!("Skipping trade during warm-up: {:#}", e);
+                continue;
+            }
+        };
+        for trade in trades {
+            match validator.check(trade) {
+                Ok(trade) => {
+                    sol_usd::calculate(sol_usd, calculator, trade);
+                    replayed += 1;
+                }
+                Err(e) => debug!("Skipping trade during warm-up: {}", e),
+            }
+        }
+    }
+
+    info!(
+        "🔥 Warmed up {} tokens from {} trades in {:.1}s",
+        calculator.tokens().len(),
+        replayed,
+        started.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+/// Reads a range of offsets on each of a set of partitions, then stops
+pub struct RangeReader {
+    consumer: StreamConsumer,
+    // Partitions not yet read to their end
+    ranges: Ranges,
+    deadline: Instant,
+    timeout: Duration,
+}
+
+impl RangeReader {
+    /// Assign `consumer` every partition in `ranges` at its start offset;
+    /// reading gives up after `timeout`
+    pub fn new(consumer: StreamConsumer, ranges: Ranges, timeout: Duration) -> Result<Self> {
+        let mut assignment = TopicPartitionList::new();
+        for ((topic, partition), &(start, _)) in &ranges {
+            assignment.add_partition_offset(topic, *partition, Offset::Offset(start))?;
+        }
+        consumer.assign(&assignment).context("Failed to assign partitions")?;
+
+        Ok(Self {
+            consumer,
+            ranges,
+            deadline: Instant::now() + timeout,
+            timeout,
+        })
+    }
+
+    /// The next message in range; None once every partition reached its end
+    /// offset or the timeout passed
+    pub async fn next(&mut self) -> Option<OwnedMessage> {
+        while !self.ranges.is_empty() {
+            if Instant::now() >= self.deadline {
+                warn!(
+                    "⚠️  Gave up reading after {}s with {} partitions unfinished",
+                    self.timeout.as_secs(),
+                    self.ranges.len()
+                );
+                return None;
+            }
+
+            let message = match tokio::time::timeout(POLL_TIMEOUT, self.consumer.recv()).await {
+                Ok(Ok(message)) => message,
+                Ok(Err(e)) => {
+                    warn!("⚠️  Consumer error while reading history: {}", e);
+                    continue;
+                }
+                Err(_) => continue,
+            };
+
+            let key = (message.topic().to_string(), message.partition());
+            let Some(&(_, end)) = self.ranges.get(&key) else {
+                continue;
+            };
+            if message.offset() + 1 >= end {
+                self.ranges.remove(&key);
+            }
+            if message.offset() < end {
+                return Some(message.detach());
+            }
+        }
+        None
+    }
+}
+
+/// Work out which offsets to replay on every input partition
+fn plan(config: &Config, consumer: &StreamConsumer) -> Result<Ranges> {
+    let mut partitions = TopicPartitionList::new();
+    for topic in config.kafka.subscriptions() {
+        if topic.starts_with('^') {
+            warn!("⚠️  Warm-up skips pattern subscription '{}'", topic);
+            continue;
+        }
+        let metadata = consumer
+            .fetch_metadata(Some(topic), QUERY_TIMEOUT)
+            .with_context(|| format!("Failed to fetch metadata for '{}'", topic))?;
+        for partition in metadata.topics().iter().flat_map(|topic| topic.partitions()) {
+            partitions.add_partition_offset(topic, partition.id(), Offset::Invalid)?;
+        }
+    }
+    let committed = consumer
+        .committed_offsets(partitions, QUERY_TIMEOUT)
+        .context("Failed to fetch committed offsets")?;
+
+    // First offset at or after the lookback time, per partition
+    let by_time = match config.warmup.lookback_minutes {
+        Some(minutes) => {
+            let since = chrono::Utc::now().timestamp_millis() - minutes as i64 * 60_000;
+            let mut query = TopicPartitionList::new();
+            for partition in committed.elements() {
+                query.add_partition_offset(partition.topic(), partition.partition(), Offset::Offset(since))?;
+            }
+            let offsets = consumer
+                .offsets_for_times(query, QUERY_TIMEOUT)
+                .context("Failed to look up offsets by time")?;
+            Some(offsets.to_topic_map())
+        }
+        None => None,
+    };
+
+    let mut ranges = Ranges::new();
+    for partition in committed.elements() {
+        let Offset::Offset(end) = partition.offset() else {
+            continue;
+        };
+        let key = (partition.topic().to_string(), partition.partition());
+        let (low, _) = consumer.fetch_watermarks(partition.topic(), partition.partition(), QUERY_TIMEOUT)?;
+
+        let mut start = low;
+        if let Some(messages) = config.warmup.lookback_messages {
+            start = start.max(end - messages);
+        }
+        if let Some(by_time) = &by_time {
+            // No message since the lookback time means nothing to replay
+            start = match by_time.get(&key) {
+                Some(&Offset::Offset(offset)) => start.max(offset),
+                _ => end,
+            };
+        }
+
+        if start < end {
+            ranges.insert(key, (start, end));
+        }
+    }
+    Ok(ranges)
+}
@@ -0,0 +1,636 @@
+use anyhow::{anyhow, Result};
+use log::{error, info, warn};
+use opentelemetry::trace::{SpanKind, TraceContextExt};
+use opentelemetry::KeyValue;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::message::Message;
+use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
+use rdkafka::{Offset, TopicPartitionList};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::codec::Codec;
+use crate::delivery::{Delivered, DeliveryPipeline};
+use crate::headers::{Carried, OutputHeaders};
+use crate::health::Health;
+use crate::reconnect::Reconnect;
+use crate::reload::{Update, Updates};
+use crate::telemetry;
+use crate::service::{
+    decode_checked, log_fallbacks, log_output, log_rejections, rejection, shutdown_signal, Observers, POLL_TIMEOUT,
+};
+use crate::validation::TradeValidator;
+use crate::{dead_letter, split_series_key, RestoredState, RsiCalculator, TradeMessage};
+
+/// A decoded trade handed to the worker that owns its token
+struct Job {
+    trade: TradeMessage,
+    topic: String,
+    partition: i32,
+    offset: i64,
+    headers: Carried,
+    // The trade's span, ended once its outputs are queued on the producer
+    trace: opentelemetry::Context,
+}
+
+/// An encoded output waiting for the producer stage
+struct Record {
+    topic: String,
+    key: String,
+    payload: Vec<u8>,
+    kind: &'static str,
+    series_key: String,
+}
+
+/// Every output of one job, handed from its worker to the producer stage
+struct Publish {
+    topic: String,
+    partition: i32,
+    offset: i64,
+    records: Vec<Record>,
+    headers: Carried,
+    trace: opentelemetry::Context,
+    // Whether every output could be encoded
+    encoded: bool,
+}
+
+/// Reported back to the consumer loop once a job's outputs are acknowledged
+struct Done {
+    topic: String,
+    partition: i32,
+    offset: i64,
+    published: u64,
+    // Token of every output that never reached its topic
+    failed: Vec<String>,
+    delivered: bool,
+}
+
+/// The calculate and produce stages, each on their own tasks
+///
+/// Trades are routed to calculator shards by a hash of the token address, so
+/// every token is always handled by the same worker and its trades stay in
+/// order. Workers hand encoded outputs to a single producer task over a
+/// bounded queue, so a slow broker never blocks calculation directly.
+struct WorkerPool {
+    senders: Vec<mpsc::Sender<Job>>,
+    handles: Vec<JoinHandle<()>>,
+    publisher: JoinHandle<()>,
+    // Raised by the producer stage while the producer queue is full
+    backpressure: Arc<AtomicBool>,
+}
+
+impl WorkerPool {
+    fn spawn(
+        updates: &Updates,
+        producer: &FutureProducer,
+        observers: &Observers,
+        done_tx: mpsc::UnboundedSender<Done>,
+        mut calculator: RsiCalculator,
+    ) -> Result<Self> {
+        let config = updates.config();
+
+        let (publish_tx, publish_rx) = mpsc::channel(config.workers.output_queue_size);
+        let pipeline = DeliveryPipeline::new(producer, config.kafka.max_in_flight, &config.retry);
+        let backpressure = pipeline.backpressure();
+        let headers = OutputHeaders::new(&config.headers);
+        let publisher = tokio::spawn(publisher(pipeline, headers, publish_rx, done_tx));
+
+        // Warmed-up token state goes to the worker its trades are routed to
+        let restored = calculator.take_state();
+        let mut shards: Vec<RestoredState> = (0..config.workers.count)
+            .map(|_| RestoredState {
+                tokens: HashMap::new(),
+                watermark: restored.watermark,
+            })
+            .collect();
+        // Every quote of a token is routed with the token
+        for (key, state) in restored.tokens {
+            let (token_address, _) = split_series_key(&key);
+            shards[shard_of(token_address, config.workers.count)].tokens.insert(key, state);
+        }
+
+        let mut senders = Vec::with_capacity(config.workers.count);
+        let mut handles = Vec::with_capacity(config.workers.count);
+        for (id, restored) in shards.into_iter().enumerate() {
+            let (tx, rx) = mpsc::channel(config.workers.queue_size);
+            let codec = Codec::new(&config)?;
+            handles.push(tokio::spawn(worker(
+                id,
+                updates.clone(),
+                restored,
+                codec,
+                observers.fork(),
+                rx,
+                publish_tx.clone(),
+            )));
+            senders.push(tx);
+        }
+
+        Ok(Self {
+            senders,
+            handles,
+            publisher,
+            backpressure,
+        })
+    }
+
+    /// Close the queues and wait for every stage to finish what is queued
+    async fn shutdown(self) {
+        drop(self.senders);
+        for handle in self.handles {
+            handle.await.ok();
+        }
+        // The workers held the only senders of the producer stage's queue
+        self.publisher.await.ok();
+    }
+
+    /// Queue a trade on its token's worker, waiting if that queue is full
+    async fn dispatch(&self, job: Job) -> Result<()> {
+        let shard = shard_of(&job.trade.token_address, self.senders.len());
+        self.senders[shard]
+            .send(job)
+            .await
+            .map_err(|_| anyhow!("Worker {} stopped unexpectedly", shard))
+    }
+}
+
+/// Worker a token's trades are routed to
+fn shard_of(token_address: &str, shards: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    token_address.hash(&mut hasher);
+    (hasher.finish() % shards as u64) as usize
+}
+
+/// Calculate one shard's trades and encode their outputs for publishing
+async fn worker(
+    id: usize,
+    mut updates: Updates,
+    restored: RestoredState,
+    mut codec: Codec,
+    mut observers: Observers,
+    mut jobs: mpsc::Receiver<Job>,
+    publish: mpsc::Sender<Publish>,
+) {
+    let mut config = updates.config();
+    let mut calculator = RsiCalculator::new(&config);
+    calculator.restore(restored);
+
+    loop {
+        let job = tokio::select! {
+            job = jobs.recv() => job,
+            update = updates.next() => {
+                match update {
+                    Update::Config(new) => {
+                        config = new;
+                        calculator.reconfigure(&config);
+                        observers.reconfigure(&config);
+                    }
+                    Update::Reset(token) => {
+                        calculator.reset_token(&token);
+                    }
+                }
+                continue;
+            }
+        };
+        let Some(job) = job else {
+            break;
+        };
+        let mut records = Vec::new();
+        let mut encoded = true;
+
+        observers.trade(&job.trade).await;
+        let token_address = job.trade.series_key().into_owned();
+        let outputs = {
+            let calculate = telemetry::stage_span(&job.trace, "calculate", SpanKind::Internal);
+            let outputs = observers.calculate(&mut calculator, job.trade);
+            calculate.span().set_attribute(KeyValue::new("indicator.outputs", outputs.len() as i64));
+            outputs
+        };
+        observers.calculated(&token_address, &mut calculator);
+        for output in outputs {
+            log_output(&output);
+            observers.output(&output, &calculator).await;
+
+            for topic in output.topics(&config) {
+                match codec.encode(&output, topic).await {
+                    Ok(payload) => records.push(Record {
+                        topic: topic.to_string(),
+                        key: output.key(&config.kafka.key_format),
+                        payload,
+                        kind: output.kind(),
+                        series_key: output.series_key().into_owned(),
+                    }),
+                    Err(e) => {
+                        error!("❌ Worker {}: failed to encode {}: {:#}", id, output.kind(), e);
+                        encoded = false;
+                    }
+                }
+            }
+        }
+
+        let batch = Publish {
+            topic: job.topic,
+            partition: job.partition,
+            offset: job.offset,
+            records,
+            headers: job.headers,
+            trace: job.trace,
+            encoded,
+        };
+        // Waits while the producer stage is behind
+        if publish.send(batch).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Producer stage: pipeline every job's outputs to the broker and report
+/// each job once they are all acknowledged
+async fn publisher(
+    mut pipeline: DeliveryPipeline<(String, i32, i64)>,
+    headers: OutputHeaders,
+    mut batches: mpsc::Receiver<Publish>,
+    done: mpsc::UnboundedSender<Done>,
+) {
+    loop {
+        let batch = tokio::select! {
+            resolved = pipeline.next(), if !pipeline.is_empty() => {
+                if report(&done, resolved).is_err() {
+                    return;
+                }
+                continue;
+            }
+            batch = batches.recv() => batch,
+        };
+        let Some(mut batch) = batch else {
+            break;
+        };
+
+        // Outputs continue the trace from the publish span
+        let publish = telemetry::stage_span(&batch.trace, "publish", SpanKind::Producer);
+        telemetry::inject(&publish, &mut batch.headers);
+
+        pipeline.start((batch.topic, batch.partition, batch.offset));
+        if !batch.encoded {
+            pipeline.fail();
+        }
+        for record in &batch.records {
+            let future_record = FutureRecord::to(&record.topic)
+                .key(&record.key)
+                .payload(&record.payload)
+                .headers(headers.build(&batch.headers));
+            pipeline.send(future_record, record.kind, &record.series_key).await;
+        }
+    }
+
+    // Every worker stopped: report what is still in flight before stopping
+    while !pipeline.is_empty() {
+        if report(&done, pipeline.next().await).is_err() {
+            return;
+        }
+    }
+}
+
+/// Tell the consumer loop a job's outputs are acknowledged (or lost)
+fn report(done: &mpsc::UnboundedSender<Done>, resolved: Delivered<(String, i32, i64)>) -> Result<()> {
+    let (topic, partition, offset) = resolved.source;
+    done.send(Done {
+        topic,
+        partition,
+        offset,
+        published: resolved.published,
+        failed: resolved.failed,
+        delivered: resolved.delivered,
+    })
+    .map_err(|_| anyhow!("Consumer loop stopped"))
+}
+
+/// Tracks in-flight offsets per partition so an offset is only committed
+/// once every earlier message of that partition has been published
+#[derive(Default)]
+struct OffsetTracker {
+    partitions: HashMap<(String, i32), PartitionOffsets>,
+}
+
+#[derive(Default)]
+struct PartitionOffsets {
+    // Holds on each unfinished offset: one per trade of a batched message
+    in_flight: BTreeMap<i64, usize>,
+    // One past the highest finished offset
+    finished_up_to: i64,
+    // Last offset stored for commit
+    stored: i64,
+}
+
+impl OffsetTracker {
+    /// Hold an offset until a matching `finish`
+    fn start(&mut self, topic: &str, partition: i32, offset: i64) {
+        *self
+            .partitions
+            .entry((topic.to_string(), partition))
+            .or_default()
+            .in_flight
+            .entry(offset)
+            .or_default() += 1;
+    }
+
+    /// Release one hold on an offset; returns the next offset to commit if it
+    /// advanced
+    fn finish(&mut self, topic: &str, partition: i32, offset: i64) -> Option<i64> {
+        let state = self.partitions.get_mut(&(topic.to_string(), partition))?;
+        if let Some(holds) = state.in_flight.get_mut(&offset) {
+            *holds -= 1;
+            if *holds > 0 {
+                return None;
+            }
+            state.in_flight.remove(&offset);
+        }
+        state.finished_up_to = state.finished_up_to.max(offset + 1);
+
+        let committable = state.in_flight.keys().next().copied().unwrap_or(state.finished_up_to);
+        (committable > state.stored).then(|| {
+            state.stored = committable;
+            committable
+        })
+    }
+}
+
+/// Store a committable offset so the next commit picks it up
+fn store_offset(consumer: &StreamConsumer, topic: &str, partition: i32, offset: i64) {
+    let mut offsets = TopicPartitionList::new();
+    let stored = offsets
+        .add_partition_offset(topic, partition, Offset::Offset(offset))
+        .and_then(|()| consumer.store_offsets(&offsets));
+    if let Err(e) = stored {
+        warn!("Failed to store offset: {}", e);
+    }
+}
+
+/// Consumer stage: decode here, calculate on the workers, publish on the
+/// producer task, and commit offsets as contiguous runs complete
+pub async fn run(
+    mut updates: Updates,
+    consumer: &StreamConsumer,
+    producer: &FutureProducer,
+    codec: &mut Codec,
+    observers: &Observers,
+    health: &Health,
+    calculator: RsiCalculator,
+) -> Result<()> {
+    let config = updates.config();
+    let (done_tx, mut done) = mpsc::unbounded_channel();
+    let pool = WorkerPool::spawn(&updates, producer, observers, done_tx, calculator)?;
+    info!("🧵 Processing trades on {} calculator workers and a producer task", config.workers.count);
+
+    let mut tracker = OffsetTracker::default();
+    let output_headers = OutputHeaders::new(&config.headers);
+    let mut validator = TradeValidator::new(&config.validation);
+    let mut reconnect = Reconnect::new(&config.kafka);
+    let mut message_count = 0u64;
+    let mut published_count = 0u64;
+    let mut uncommitted = 0u64;
+    let mut last_commit = Instant::now();
+    let commit_interval = Duration::from_millis(config.kafka.commit_interval_ms);
+    let mut delivery_failure = None;
+    let mut paused = false;
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        // Stop fetching while the producer is backed up rather than piling
+        // trades into the queues; the loop keeps polling to stay in the group
+        let backpressure = pool.backpressure.load(Ordering::Relaxed);
+        if backpressure != paused {
+            let toggled = consumer.assignment().and_then(|assignment| {
+                if backpressure {
+                    consumer.pause(&assignment)
+                } else {
+                    consumer.resume(&assignment)
+                }
+            });
+            match toggled {
+                Ok(()) => {
+                    paused = backpressure;
+                    if paused {
+                        warn!("⏸️  Producer queue full, pausing consumption");
+                    } else {
+                        info!("▶️  Producer caught up, resuming consumption");
+                    }
+                }
+                Err(e) => warn!("Failed to pause/resume partitions: {}", e),
+            }
+        }
+
+        if uncommitted >= config.kafka.commit_batch_size
+            || (uncommitted > 0 && last_commit.elapsed() >= commit_interval)
+        {
+            match consumer.commit_consumer_state(CommitMode::Async) {
+                Ok(()) => {
+                    uncommitted = 0;
+                    last_commit = Instant::now();
+                }
+                Err(e) => warn!("Failed to commit offsets: {}", e),
+            }
+        }
+
+        tokio::select! {
+            _ = &mut shutdown => break,
+            update = updates.next() => {
+                if let Update::Config(new) = update {
+                    validator.reconfigure(&new.validation);
+                }
+            }
+            Some(result) = done.recv() => {
+                observers.produce_failed(&result.failed);
+                if !result.delivered {
+                    delivery_failure = Some(failure_reason(&result));
+                    break;
+                }
+                published_count += result.published;
+                if let Some(offset) = tracker.finish(&result.topic, result.partition, result.offset) {
+                    store_offset(consumer, &result.topic, result.partition, offset);
+                    uncommitted += 1;
+                }
+            }
+            received = tokio::time::timeout(POLL_TIMEOUT, consumer.recv()) => {
+                health.record_poll();
+                let message = match received {
+                    Err(_) => {
+                        health.set_kafka_connected(consumer.assignment().is_ok_and(|tpl| tpl.count() > 0));
+                        continue;
+                    }
+                    Ok(Err(e)) => {
+                        health.set_kafka_connected(false);
+                        tokio::select! {
+                            _ = &mut shutdown => break,
+                            _ = reconnect.on_error(consumer, &e) => {}
+                        }
+                        continue;
+                    }
+                    Ok(Ok(message)) => message,
+                };
+                message_count += 1;
+                reconnect.on_message();
+                health.set_kafka_connected(true);
+                health.record_message();
+
+                let (topic, partition, offset) = (message.topic().to_string(), message.partition(), message.offset());
+                tracker.start(&topic, partition, offset);
+
+                let trades = match message.payload() {
+                    Some(payload) => decode_checked(codec, &mut validator, health, &topic, payload).await,
+                    None => vec![(Err(anyhow!("Empty payload")), None)],
+                };
+                // Every trade of a batch holds the offset until its outputs are done
+                for (trade, single) in trades {
+                    match trade {
+                        Ok(trade) => {
+                            let headers = output_headers.carry(&message);
+                            let trace = telemetry::consume_span(&message);
+                            tracker.start(&topic, partition, offset);
+                            let job = Job { trade, topic: topic.clone(), partition, offset, headers, trace };
+                            pool.dispatch(job).await?
+                        }
+                        Err(e) => {
+                            let (reason, target) = rejection(&config, &e);
+                            if let Some(target) = target {
+                                let forwarded =
+                                    dead_letter::forward(producer, target, &message, single.as_deref(), &reason).await;
+                                if let Err(e) = forwarded {
+                                    delivery_failure = Some(format!("{:#}", e));
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                }
+                if delivery_failure.is_some() {
+                    break;
+                }
+
+                // Release the message's own hold; with nothing to publish the
+                // offset is done immediately
+                if let Some(offset) = tracker.finish(&topic, partition, offset) {
+                    store_offset(consumer, &topic, partition, offset);
+                    uncommitted += 1;
+                }
+            }
+        }
+    }
+
+    log_rejections(&validator);
+    log_fallbacks(health);
+    match &delivery_failure {
+        Some(reason) => error!("❌ {}, stopping without committing it", reason),
+        None => info!("🛑 Shutdown requested, draining (up to {}s)...", config.shutdown.drain_timeout_secs),
+    }
+
+    // Let the workers finish what is queued, then commit what completed in order
+    let drain_timeout = Duration::from_secs(config.shutdown.drain_timeout_secs);
+    let drain = async {
+        pool.shutdown().await;
+        while let Some(result) = done.recv().await {
+            if !result.delivered {
+                delivery_failure.get_or_insert_with(|| failure_reason(&result));
+                continue;
+            }
+            published_count += result.published;
+            if delivery_failure.is_none() {
+                if let Some(offset) = tracker.finish(&result.topic, result.partition, result.offset) {
+                    store_offset(consumer, &result.topic, result.partition, offset);
+                }
+            }
+        }
+
+        if let Err(e) = producer.flush(drain_timeout) {
+            error!("❌ Failed to flush producer: {}", e);
+        }
+        if let Err(e) = consumer.commit_consumer_state(CommitMode::Sync) {
+            log::debug!("Failed to commit offsets on shutdown: {}", e);
+        }
+    };
+    match tokio::time::timeout(drain_timeout, drain).await {
+        Ok(()) => info!("👋 Processed {} trades, published {} indicator values", message_count, published_count),
+        Err(_) => warn!("⚠️  Drain timed out after {}s, exiting anyway", config.shutdown.drain_timeout_secs),
+    }
+
+    match delivery_failure {
+        Some(reason) => Err(anyhow!(reason)),
+        None => Ok(()),
+    }
+}
+
+fn failure_reason(result: &Done) -> String {
+    format!(
+        "Failed to publish output for {}[{}] offset {}",
+        result.topic, result.partition, result.offset
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracking(offsets: &[i64]) -> OffsetTracker {
+        let mut tracker = OffsetTracker::default();
+        for &offset in offsets {
+            tracker.start("trades", 0, offset);
+        }
+        tracker
+    }
+
+    #[test]
+    fn offsets_only_advance_to_the_lowest_held_one() {
+        let mut tracker = tracking(&[5, 6, 7]);
+
+        // 5 is still held, so finishing 6 commits no further than 5
+        assert_eq!(tracker.finish("trades", 0, 6), Some(5));
+        assert_eq!(tracker.finish("trades", 0, 7), None);
+        assert_eq!(tracker.finish("trades", 0, 5), Some(8));
+    }
+
+    #[test]
+    fn finishing_in_order_commits_each_offset() {
+        let mut tracker = tracking(&[5, 6, 7]);
+
+        assert_eq!(tracker.finish("trades", 0, 5), Some(6));
+        assert_eq!(tracker.finish("trades", 0, 6), Some(7));
+        assert_eq!(tracker.finish("trades", 0, 7), Some(8));
+    }
+
+    #[test]
+    fn batched_offsets_wait_for_every_trade() {
+        let mut tracker = tracking(&[3, 3, 4]);
+
+        assert_eq!(tracker.finish("trades", 0, 3), None);
+        assert_eq!(tracker.finish("trades", 0, 4), Some(3));
+        assert_eq!(tracker.finish("trades", 0, 3), Some(5));
+    }
+
+    #[test]
+    fn partitions_are_tracked_separately() {
+        let mut tracker = tracking(&[10]);
+        tracker.start("trades", 1, 20);
+
+        assert_eq!(tracker.finish("trades", 1, 20), Some(21));
+        assert_eq!(tracker.finish("trades", 0, 10), Some(11));
+        assert_eq!(tracker.finish("trades", 2, 30), None);
+    }
+
+    #[test]
+    fn tokens_stay_on_one_shard() {
+        for token in ["So11111111111111111111111111111111111111112", "token-a", "token-b", ""] {
+            let shard = shard_of(token, 4);
+            assert!(shard < 4);
+            assert!((0..10).all(|_| shard_of(token, 4) == shard));
+        }
+        assert_eq!(shard_of("token-a", 1), 0);
+    }
+}
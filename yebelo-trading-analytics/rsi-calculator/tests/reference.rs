@@ -0,0 +1,30 @@
+//! Every reference fixture must match within its tolerance, the same check
+//! the `verify` subcommand runs
+
+use rsi_calculator::reference::Fixture;
+use std::path::Path;
+
+#[test]
+fn reference_fixtures_match() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/reference");
+    let mut paths: Vec<_> = std::fs::read_dir(&dir)
+        .expect("fixture directory is readable")
+        .map(|entry| entry.expect("fixture entry is readable").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no fixtures in {}", dir.display());
+
+    for path in paths {
+        let fixture = Fixture::load(&path).unwrap_or_else(|e| panic!("{}: {:#}", path.display(), e));
+        assert!(fixture.checks() > 0, "{} checks nothing", path.display());
+
+        let drift = fixture.check().unwrap_or_else(|e| panic!("{}: {:#}", path.display(), e));
+        assert!(
+            drift.is_empty(),
+            "{} drifted from its reference: {:?}",
+            path.display(),
+            drift
+        );
+    }
+}
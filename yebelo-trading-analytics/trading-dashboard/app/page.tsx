@@ -5,7 +5,7 @@
  * Displays real-time price and RSI charts for pump.fun tokens
  */
 
-import { useState, useEffect } from 'react';
+import { useState, useEffect, useRef } from 'react';
 import { 
   LineChart, 
   Line, 
@@ -26,6 +26,7 @@ interface RsiData {
   timestamp: string;
   period: number;
   signal: string;
+  timeframe?: string; // "tick" or a candle interval, e.g. "5m"
 }
 
 interface ChartDataPoint {
@@ -40,6 +41,10 @@ export default function TradingDashboard() {
   const [availableTokens, setAvailableTokens] = useState<string[]>([]);
   const [chartData, setChartData] = useState<ChartDataPoint[]>([]);
   const [currentData, setCurrentData] = useState<RsiData | null>(null);
+  // Latest RSI per timeframe, e.g. { "1m": ..., "5m": ..., "15m": ... }
+  const [timeframes, setTimeframes] = useState<Record<string, RsiData>>({});
+  // The charts follow the first timeframe received for the selected token
+  const chartTimeframe = useRef<string | null>(null);
   const [isConnected, setIsConnected] = useState(false);
   const [error, setError] = useState<string | null>(null);
 
@@ -50,6 +55,9 @@ export default function TradingDashboard() {
 
   // Connect to SSE stream when token changes
   useEffect(() => {
+    chartTimeframe.current = null;
+    setTimeframes({});
+    setChartData([]);
     connectToStream();
 
     // Cleanup on unmount or token change
@@ -96,7 +104,16 @@ export default function TradingDashboard() {
     eventSource.onmessage = (event) => {
       try {
         const rsiData: RsiData = JSON.parse(event.data);
-        
+        const timeframe = rsiData.timeframe ?? 'tick';
+        setTimeframes((prev) => ({ ...prev, [timeframe]: rsiData }));
+
+        if (chartTimeframe.current === null) {
+          chartTimeframe.current = timeframe;
+        }
+        if (timeframe !== chartTimeframe.current) {
+          return;
+        }
+
         // Update current values
         setCurrentData(rsiData);
 
@@ -136,10 +153,14 @@ export default function TradingDashboard() {
    */
   const getSignalColor = (signal: string) => {
     switch (signal) {
+      case 'strongly_oversold':
+        return 'text-green-300 font-semibold';
       case 'oversold':
         return 'text-green-400';
       case 'overbought':
         return 'text-red-400';
+      case 'strongly_overbought':
+        return 'text-red-300 font-semibold';
       default:
         return 'text-gray-400';
     }
@@ -208,7 +229,9 @@ export default function TradingDashboard() {
 
             {/* Current RSI */}
             <div className="bg-gradient-to-br from-purple-900/40 to-purple-800/40 border border-purple-700/50 rounded-lg p-6">
-              <div className="text-sm text-gray-400 mb-1">RSI ({currentData.period} Period)</div>
+              <div className="text-sm text-gray-400 mb-1">
+                RSI ({currentData.period} Period, {currentData.timeframe ?? 'tick'})
+              </div>
               <div className="text-3xl font-bold text-purple-300">
                 {currentData.rsi_value.toFixed(2)}
               </div>
@@ -224,6 +247,19 @@ export default function TradingDashboard() {
           </div>
         )}
 
+        {/* RSI per Timeframe */}
+        {Object.keys(timeframes).length > 1 && (
+          <div className="grid grid-cols-2 md:grid-cols-4 gap-4 mb-8">
+            {Object.entries(timeframes).map(([timeframe, data]) => (
+              <div key={timeframe} className="bg-gray-800/50 border border-gray-700 rounded-lg p-4">
+                <div className="text-sm text-gray-400 mb-1">RSI {timeframe}</div>
+                <div className="text-2xl font-bold text-purple-300">{data.rsi_value.toFixed(2)}</div>
+                <div className={`text-sm uppercase ${getSignalColor(data.signal)}`}>{data.signal}</div>
+              </div>
+            ))}
+          </div>
+        )}
+
         {/* Charts */}
         <div className="grid grid-cols-1 gap-8">
           {/* Price Chart */}